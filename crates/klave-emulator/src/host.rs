@@ -0,0 +1,835 @@
+use crate::https::HttpsStubs;
+use crate::ledger::Ledger;
+use klave::host::mock::MockHost;
+use klave::host::Host as _;
+use std::collections::BTreeMap;
+
+use wasmtime::component::{HasSelf, Linker};
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        inline: "
+            package klave:emulator;
+
+            world app {
+                import klave:sdk/sdk;
+                import klave:sdk/sdk-v2;
+            }
+        ",
+        path: "../klave/wit/sdk",
+    });
+}
+
+use bindings::klave::sdk::{sdk, sdk_v2};
+use klave::crypto::subtle_idl_v2 as v2;
+pub use klave::host::mock::Notification;
+
+/// Kind of export being run by the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    Registration,
+    Query,
+    Transaction,
+}
+
+/// State kept by the emulator between calls
+pub struct Session {
+    pub ledger: Ledger,
+    /// Runs every import but `https-query`, with the semantics of the SDK's native tests.
+    /// It holds the ledger of the current call; keys only live as long as the emulator process.
+    pub host: MockHost,
+    pub https: HttpsStubs,
+    /// Context values given on the command line, overriding the generated ones
+    pub context: BTreeMap<String, String>,
+}
+
+/// Store data of a single call
+pub struct HostState {
+    pub session: Session,
+}
+
+impl HostState {
+    /// Start a call: its ledger writes are undone by [`MockHost::end_transaction`] unless committed
+    pub fn new(session: Session, context: BTreeMap<String, String>) -> HostState {
+        let host = &session.host;
+        host.clear_context();
+        for (param, value) in &context {
+            host.set_context(param, value);
+        }
+        host.take_notifications();
+        host.begin_transaction();
+        HostState { session }
+    }
+
+    fn host(&self) -> &MockHost {
+        &self.session.host
+    }
+}
+
+/// Define every `klave:sdk/sdk` and `klave:sdk/sdk-v2` import in `linker`
+pub fn add_to_linker(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    sdk::add_to_linker::<_, HasSelf<_>>(linker, |state| state)?;
+    sdk_v2::add_to_linker::<_, HasSelf<_>>(linker, |state| state)
+}
+
+impl sdk::Host for HostState {
+    fn add_user_query(&mut self, query_name: String) {
+        self.host().add_user_query(&query_name);
+    }
+
+    fn add_user_transaction(&mut self, transaction_name: String) {
+        self.host().add_user_transaction(&transaction_name);
+    }
+
+    fn notify(&mut self, msg: String) {
+        self.host().notify(&msg);
+    }
+
+    fn notify_error(&mut self, msg: String) {
+        self.host().notify_error(&msg);
+    }
+
+    fn on_success_notify(&mut self, msg: String) {
+        self.host().on_success_notify(&msg);
+    }
+
+    fn query_context(&mut self, param: String) -> Result<String, String> {
+        self.host().query_context(&param)
+    }
+
+    fn read_ledger(&mut self, table: String, key: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().read_ledger(&table, &key)
+    }
+
+    fn list_keys_from_ledger(&mut self, table: String) -> Result<String, String> {
+        self.host().list_keys_from_ledger(&table)
+    }
+
+    fn key_exists_in_ledger(&mut self, table: String, key: Vec<u8>) -> Result<bool, String> {
+        self.host().key_exists_in_ledger(&table, &key)
+    }
+
+    fn write_ledger(&mut self, table: String, key: Vec<u8>, value: Vec<u8>) -> Result<(), String> {
+        self.host().write_ledger(&table, &key, &value)
+    }
+
+    fn remove_from_ledger(&mut self, table: String, key: Vec<u8>) -> Result<(), String> {
+        self.host().remove_from_ledger(&table, &key)
+    }
+
+    fn load_lightgbm_model(&mut self, name: String, model: String) -> Result<(), String> {
+        self.host().load_lightgbm_model(&name, &model)
+    }
+
+    fn unload_lightgbm_model(&mut self, name: String) -> Result<(), String> {
+        self.host().unload_lightgbm_model(&name)
+    }
+
+    fn infer_from_lightgbm_model(
+        &mut self,
+        name: String,
+        data: Vec<f64>,
+        nb_outputs: i32,
+    ) -> Result<Vec<f64>, String> {
+        self.host()
+            .infer_from_lightgbm_model(&name, &data, nb_outputs)
+    }
+
+    fn key_exists(&mut self, key_name: String) -> Result<bool, String> {
+        self.host().key_exists(&key_name)
+    }
+
+    fn generate_key(
+        &mut self,
+        key_name: String,
+        algorithm: i32,
+        algo_metadata: String,
+        extractable: i32,
+        usages: Vec<u8>,
+    ) -> Result<String, String> {
+        self.host()
+            .generate_key(&key_name, algorithm, &algo_metadata, extractable, &usages)
+    }
+
+    fn import_key(
+        &mut self,
+        key_name: String,
+        key_format: i32,
+        key_data: Vec<u8>,
+        algorithm: i32,
+        algo_metadata: String,
+        extractable: i32,
+        usages: Vec<u8>,
+    ) -> Result<String, String> {
+        self.host().import_key(
+            &key_name,
+            key_format,
+            &key_data,
+            algorithm,
+            &algo_metadata,
+            extractable,
+            &usages,
+        )
+    }
+
+    fn export_key(&mut self, key_name: String, key_format: i32) -> Result<Vec<u8>, String> {
+        self.host().export_key(&key_name, key_format)
+    }
+
+    fn get_public_key(&mut self, key_name: String) -> Result<Vec<u8>, String> {
+        self.host().get_public_key(&key_name)
+    }
+
+    fn get_public_key_as_cryptokey(&mut self, key_name: String) -> Result<String, String> {
+        self.host().get_public_key_as_cryptokey(&key_name)
+    }
+
+    fn derive_key(
+        &mut self,
+        base_key_name: String,
+        derivation_algorithm: i32,
+        derivation_metadata: String,
+        derived_key_algorithm: i32,
+        derived_key_metadata: String,
+        extractable: i32,
+        usages: Vec<u8>,
+    ) -> Result<String, String> {
+        self.host().derive_key(
+            &base_key_name,
+            derivation_algorithm,
+            &derivation_metadata,
+            derived_key_algorithm,
+            &derived_key_metadata,
+            extractable,
+            &usages,
+        )
+    }
+
+    fn derive_bits(
+        &mut self,
+        base_key_name: String,
+        derivation_algorithm: i32,
+        derivation_metadata: String,
+        length: i32,
+    ) -> Result<Vec<u8>, String> {
+        self.host().derive_bits(
+            &base_key_name,
+            derivation_algorithm,
+            &derivation_metadata,
+            length,
+        )
+    }
+
+    fn encrypt(
+        &mut self,
+        key_name: String,
+        encrypt_algo_id: i32,
+        encrypt_metadata: String,
+        plain_txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        self.host()
+            .encrypt(&key_name, encrypt_algo_id, &encrypt_metadata, &plain_txt)
+    }
+
+    fn decrypt(
+        &mut self,
+        key_name: String,
+        decrypt_algo_id: i32,
+        decrypt_metadata: String,
+        cipher_txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        self.host()
+            .decrypt(&key_name, decrypt_algo_id, &decrypt_metadata, &cipher_txt)
+    }
+
+    fn sign(
+        &mut self,
+        key_name: String,
+        sign_algo_id: i32,
+        sign_metadata: String,
+        txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        self.host()
+            .sign(&key_name, sign_algo_id, &sign_metadata, &txt)
+    }
+
+    fn verify(
+        &mut self,
+        key_name: String,
+        sign_algo_id: i32,
+        sign_metadata: String,
+        txt: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Result<bool, String> {
+        self.host()
+            .verify(&key_name, sign_algo_id, &sign_metadata, &txt, &signature)
+    }
+
+    fn digest(
+        &mut self,
+        algo_id: i32,
+        hash_metadata: String,
+        txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        self.host().digest(algo_id, &hash_metadata, &txt)
+    }
+
+    fn unwrap_key(
+        &mut self,
+        decrypt_key_name: String,
+        decrypt_algo_id: i32,
+        decrypt_metadata: String,
+        key_name_to_import: String,
+        key_format: i32,
+        wrapped_key_data: Vec<u8>,
+        algorithm: i32,
+        algo_metadata: String,
+        extractable: i32,
+        usages: Vec<u8>,
+    ) -> Result<String, String> {
+        self.host().unwrap_key(
+            &decrypt_key_name,
+            decrypt_algo_id,
+            &decrypt_metadata,
+            &key_name_to_import,
+            key_format,
+            &wrapped_key_data,
+            algorithm,
+            &algo_metadata,
+            extractable,
+            &usages,
+        )
+    }
+
+    fn wrap_key(
+        &mut self,
+        key_name_to_export: String,
+        key_format: i32,
+        encrypt_key_name: String,
+        encrypt_algo_id: i32,
+        encrypt_metadata: String,
+    ) -> Result<Vec<u8>, String> {
+        self.host().wrap_key(
+            &key_name_to_export,
+            key_format,
+            &encrypt_key_name,
+            encrypt_algo_id,
+            &encrypt_metadata,
+        )
+    }
+
+    fn save_key(&mut self, key_name: String) -> Result<(), String> {
+        self.host().save_key(&key_name)
+    }
+
+    fn persist_key(&mut self, key_persist_params: String) -> Result<(), String> {
+        self.host().persist_key(&key_persist_params)
+    }
+
+    fn load_key(&mut self, key_name: String) -> Result<String, String> {
+        self.host().load_key(&key_name)
+    }
+
+    fn delete_key(&mut self, key_name: String) -> Result<(), String> {
+        self.host().delete_key(&key_name)
+    }
+
+    fn generate_simple_encryption_key(&mut self, key_name: String) -> Result<(), String> {
+        self.host().generate_simple_encryption_key(&key_name)
+    }
+
+    fn generate_simple_signing_key(&mut self, key_name: String) -> Result<(), String> {
+        self.host().generate_simple_signing_key(&key_name)
+    }
+
+    fn simple_encrypt(&mut self, key_name: String, plain_txt: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().simple_encrypt(&key_name, &plain_txt)
+    }
+
+    fn simple_decrypt(&mut self, key_name: String, cipher_txt: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().simple_decrypt(&key_name, &cipher_txt)
+    }
+
+    fn simple_sign(&mut self, key_name: String, txt: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().simple_sign(&key_name, &txt)
+    }
+
+    fn simple_verify(
+        &mut self,
+        key_name: String,
+        txt: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Result<bool, String> {
+        self.host().simple_verify(&key_name, &txt, &signature)
+    }
+
+    fn simple_digest(&mut self, txt: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().simple_digest(&txt)
+    }
+
+    fn get_random_bytes(&mut self, len: i32) -> Result<Vec<u8>, String> {
+        self.host().get_random_bytes(len)
+    }
+
+    fn https_query(&mut self, request: String) -> Result<String, String> {
+        self.session.https.respond(&request)
+    }
+
+    fn get_quote(&mut self, challenge: Vec<u8>) -> Result<Vec<u8>, String> {
+        self.host().get_quote(&challenge)
+    }
+
+    fn verify_quote(&mut self, current_time: i64, quote_binary: Vec<u8>) -> Result<String, String> {
+        self.host().verify_quote(current_time, &quote_binary)
+    }
+
+    fn parse_quote(&mut self, quote: Vec<u8>) -> Result<String, String> {
+        self.host().parse_quote(&quote)
+    }
+
+    fn connection_open(&mut self, uri: String) -> Result<String, String> {
+        self.host().connection_open(&uri)
+    }
+
+    fn sql_query(&mut self, connection: String, query: String) -> Result<String, String> {
+        self.host().sql_query(&connection, &query)
+    }
+
+    fn sql_exec(&mut self, connection: String, command: String) -> Result<String, String> {
+        self.host().sql_exec(&connection, &command)
+    }
+
+    fn start_recording(&mut self) {
+        self.host().start_recording();
+    }
+
+    fn stop_recording(&mut self) {
+        self.host().stop_recording();
+    }
+
+    fn cancel_transaction(&mut self) {
+        self.host().cancel_transaction();
+    }
+}
+
+// The typed calls are run by the string-based ones, through the SDK's compatibility mapping
+impl sdk_v2::Host for HostState {
+    fn generate_key(
+        &mut self,
+        key_name: String,
+        algorithm: sdk_v2::KeyAlgorithm,
+        extractable: bool,
+        usages: Vec<sdk_v2::KeyUsage>,
+    ) -> Result<String, String> {
+        let (algorithm, algo_metadata) = v2::KeyAlgorithm::from(algorithm).to_v1();
+        sdk::Host::generate_key(
+            self,
+            key_name,
+            algorithm,
+            algo_metadata,
+            extractable as i32,
+            usage_ids(usages),
+        )
+    }
+
+    fn import_key(
+        &mut self,
+        key_name: String,
+        key_format: sdk_v2::KeyFormat,
+        key_data: Vec<u8>,
+        algorithm: sdk_v2::KeyAlgorithm,
+        extractable: bool,
+        usages: Vec<sdk_v2::KeyUsage>,
+    ) -> Result<String, String> {
+        let (algorithm, algo_metadata) = v2::KeyAlgorithm::from(algorithm).to_v1();
+        sdk::Host::import_key(
+            self,
+            key_name,
+            v2::KeyFormat::from(key_format).to_v1(),
+            key_data,
+            algorithm,
+            algo_metadata,
+            extractable as i32,
+            usage_ids(usages),
+        )
+    }
+
+    fn export_key(
+        &mut self,
+        key_name: String,
+        key_format: sdk_v2::KeyFormat,
+    ) -> Result<Vec<u8>, String> {
+        sdk::Host::export_key(self, key_name, v2::KeyFormat::from(key_format).to_v1())
+    }
+
+    fn derive_key(
+        &mut self,
+        base_key_name: String,
+        derivation_algorithm: sdk_v2::DerivationAlgorithm,
+        derived_key_algorithm: sdk_v2::DerivedKeyAlgorithm,
+        extractable: bool,
+        usages: Vec<sdk_v2::KeyUsage>,
+    ) -> Result<String, String> {
+        let (derivation_algorithm, derivation_metadata) =
+            v2::DerivationAlgorithm::from(derivation_algorithm).to_v1();
+        let (derived_key_algorithm, derived_key_metadata) =
+            v2::DerivedKeyAlgorithm::from(derived_key_algorithm).to_v1();
+        sdk::Host::derive_key(
+            self,
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            derived_key_algorithm,
+            derived_key_metadata,
+            extractable as i32,
+            usage_ids(usages),
+        )
+    }
+
+    fn derive_bits(
+        &mut self,
+        base_key_name: String,
+        derivation_algorithm: sdk_v2::DerivationAlgorithm,
+        length: u32,
+    ) -> Result<Vec<u8>, String> {
+        let (derivation_algorithm, derivation_metadata) =
+            v2::DerivationAlgorithm::from(derivation_algorithm).to_v1();
+        sdk::Host::derive_bits(
+            self,
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            length as i32,
+        )
+    }
+
+    fn encrypt(
+        &mut self,
+        key_name: String,
+        algorithm: sdk_v2::EncryptionAlgorithm,
+        plain_txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        let (algorithm, metadata) = v2::EncryptionAlgorithm::from(algorithm).to_v1();
+        sdk::Host::encrypt(self, key_name, algorithm, metadata, plain_txt)
+    }
+
+    fn decrypt(
+        &mut self,
+        key_name: String,
+        algorithm: sdk_v2::EncryptionAlgorithm,
+        cipher_txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        let (algorithm, metadata) = v2::EncryptionAlgorithm::from(algorithm).to_v1();
+        sdk::Host::decrypt(self, key_name, algorithm, metadata, cipher_txt)
+    }
+
+    fn sign(
+        &mut self,
+        key_name: String,
+        algorithm: sdk_v2::SigningAlgorithm,
+        txt: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        let (algorithm, metadata) = v2::SigningAlgorithm::from(algorithm).to_v1();
+        sdk::Host::sign(self, key_name, algorithm, metadata, txt)
+    }
+
+    fn verify(
+        &mut self,
+        key_name: String,
+        algorithm: sdk_v2::SigningAlgorithm,
+        txt: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Result<bool, String> {
+        let (algorithm, metadata) = v2::SigningAlgorithm::from(algorithm).to_v1();
+        sdk::Host::verify(self, key_name, algorithm, metadata, txt, signature)
+    }
+
+    fn digest(&mut self, algorithm: sdk_v2::ShaAlgorithm, txt: Vec<u8>) -> Result<Vec<u8>, String> {
+        let (algorithm, metadata) = v2::ShaAlgorithm::from(algorithm).to_v1();
+        sdk::Host::digest(self, algorithm, metadata, txt)
+    }
+
+    fn unwrap_key(
+        &mut self,
+        decrypt_key_name: String,
+        algorithm: sdk_v2::WrappingAlgorithm,
+        key_name_to_import: String,
+        key_format: sdk_v2::KeyFormat,
+        wrapped_key_data: Vec<u8>,
+        key_algorithm: sdk_v2::KeyAlgorithm,
+        extractable: bool,
+        usages: Vec<sdk_v2::KeyUsage>,
+    ) -> Result<String, String> {
+        let (decrypt_algorithm, decrypt_metadata) = v2::WrappingAlgorithm::from(algorithm).to_v1();
+        let (key_algorithm, algo_metadata) = v2::KeyAlgorithm::from(key_algorithm).to_v1();
+        sdk::Host::unwrap_key(
+            self,
+            decrypt_key_name,
+            decrypt_algorithm,
+            decrypt_metadata,
+            key_name_to_import,
+            v2::KeyFormat::from(key_format).to_v1(),
+            wrapped_key_data,
+            key_algorithm,
+            algo_metadata,
+            extractable as i32,
+            usage_ids(usages),
+        )
+    }
+
+    fn wrap_key(
+        &mut self,
+        key_name_to_export: String,
+        key_format: sdk_v2::KeyFormat,
+        encrypt_key_name: String,
+        algorithm: sdk_v2::WrappingAlgorithm,
+    ) -> Result<Vec<u8>, String> {
+        let (encrypt_algorithm, encrypt_metadata) = v2::WrappingAlgorithm::from(algorithm).to_v1();
+        sdk::Host::wrap_key(
+            self,
+            key_name_to_export,
+            v2::KeyFormat::from(key_format).to_v1(),
+            encrypt_key_name,
+            encrypt_algorithm,
+            encrypt_metadata,
+        )
+    }
+}
+
+fn usage_ids(usages: Vec<sdk_v2::KeyUsage>) -> Vec<u8> {
+    usages
+        .into_iter()
+        .map(|usage| {
+            let usage = match usage {
+                sdk_v2::KeyUsage::Encrypt => v2::KeyUsage::Encrypt,
+                sdk_v2::KeyUsage::Decrypt => v2::KeyUsage::Decrypt,
+                sdk_v2::KeyUsage::Sign => v2::KeyUsage::Sign,
+                sdk_v2::KeyUsage::Verify => v2::KeyUsage::Verify,
+                sdk_v2::KeyUsage::DeriveKey => v2::KeyUsage::DeriveKey,
+                sdk_v2::KeyUsage::DeriveBits => v2::KeyUsage::DeriveBits,
+                sdk_v2::KeyUsage::WrapKey => v2::KeyUsage::WrapKey,
+                sdk_v2::KeyUsage::UnwrapKey => v2::KeyUsage::UnwrapKey,
+            };
+            usage.to_v1()
+        })
+        .collect()
+}
+
+impl From<sdk_v2::KeyFormat> for v2::KeyFormat {
+    fn from(format: sdk_v2::KeyFormat) -> Self {
+        match format {
+            sdk_v2::KeyFormat::Raw => v2::KeyFormat::Raw,
+            sdk_v2::KeyFormat::Spki => v2::KeyFormat::Spki,
+            sdk_v2::KeyFormat::Pkcs8 => v2::KeyFormat::Pkcs8,
+            sdk_v2::KeyFormat::Jwk => v2::KeyFormat::Jwk,
+            sdk_v2::KeyFormat::Sec1 => v2::KeyFormat::Sec1,
+            sdk_v2::KeyFormat::Pkcs1 => v2::KeyFormat::Pkcs1,
+        }
+    }
+}
+
+impl From<sdk_v2::ShaSize> for v2::ShaSize {
+    fn from(size: sdk_v2::ShaSize) -> Self {
+        match size {
+            sdk_v2::ShaSize::Bits256 => v2::ShaSize::Bits256,
+            sdk_v2::ShaSize::Bits384 => v2::ShaSize::Bits384,
+            sdk_v2::ShaSize::Bits512 => v2::ShaSize::Bits512,
+        }
+    }
+}
+
+impl From<sdk_v2::ShaAlgorithm> for v2::ShaAlgorithm {
+    fn from(algorithm: sdk_v2::ShaAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::ShaAlgorithm::Sha1 => v2::ShaAlgorithm::Sha1,
+            sdk_v2::ShaAlgorithm::Sha2(size) => v2::ShaAlgorithm::Sha2(size.into()),
+            sdk_v2::ShaAlgorithm::Sha3(size) => v2::ShaAlgorithm::Sha3(size.into()),
+        }
+    }
+}
+
+impl From<sdk_v2::AesKeySize> for v2::AesKeySize {
+    fn from(size: sdk_v2::AesKeySize) -> Self {
+        match size {
+            sdk_v2::AesKeySize::Bits128 => v2::AesKeySize::Bits128,
+            sdk_v2::AesKeySize::Bits192 => v2::AesKeySize::Bits192,
+            sdk_v2::AesKeySize::Bits256 => v2::AesKeySize::Bits256,
+        }
+    }
+}
+
+impl From<sdk_v2::HmacParams> for v2::HmacParams {
+    fn from(params: sdk_v2::HmacParams) -> Self {
+        v2::HmacParams {
+            hash: params.hash.into(),
+            length: params.length,
+        }
+    }
+}
+
+impl From<sdk_v2::KeyAlgorithm> for v2::KeyAlgorithm {
+    fn from(algorithm: sdk_v2::KeyAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::KeyAlgorithm::SecpR1(curve) => v2::KeyAlgorithm::SecpR1(match curve {
+                sdk_v2::SecpR1Curve::P256 => v2::SecpR1Curve::P256,
+                sdk_v2::SecpR1Curve::P384 => v2::SecpR1Curve::P384,
+                sdk_v2::SecpR1Curve::P521 => v2::SecpR1Curve::P521,
+            }),
+            sdk_v2::KeyAlgorithm::SecpK1 => v2::KeyAlgorithm::SecpK1,
+            sdk_v2::KeyAlgorithm::Aes(size) => v2::KeyAlgorithm::Aes(size.into()),
+            sdk_v2::KeyAlgorithm::Rsa(params) => v2::KeyAlgorithm::Rsa(v2::RsaParams {
+                modulus: match params.modulus {
+                    sdk_v2::RsaModulus::Bits2048 => v2::RsaModulus::Bits2048,
+                    sdk_v2::RsaModulus::Bits3072 => v2::RsaModulus::Bits3072,
+                    sdk_v2::RsaModulus::Bits4096 => v2::RsaModulus::Bits4096,
+                },
+                public_exponent: params.public_exponent,
+                hash: params.hash.into(),
+            }),
+            sdk_v2::KeyAlgorithm::Hmac(params) => v2::KeyAlgorithm::Hmac(params.into()),
+        }
+    }
+}
+
+impl From<sdk_v2::AesGcmParams> for v2::AesGcmParams {
+    fn from(params: sdk_v2::AesGcmParams) -> Self {
+        v2::AesGcmParams {
+            iv: params.iv,
+            additional_data: params.additional_data,
+            tag_length: match params.tag_length {
+                sdk_v2::AesTagLength::Bits96 => v2::AesTagLength::Bits96,
+                sdk_v2::AesTagLength::Bits104 => v2::AesTagLength::Bits104,
+                sdk_v2::AesTagLength::Bits112 => v2::AesTagLength::Bits112,
+                sdk_v2::AesTagLength::Bits120 => v2::AesTagLength::Bits120,
+                sdk_v2::AesTagLength::Bits128 => v2::AesTagLength::Bits128,
+            },
+        }
+    }
+}
+
+impl From<sdk_v2::EncryptionAlgorithm> for v2::EncryptionAlgorithm {
+    fn from(algorithm: sdk_v2::EncryptionAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::EncryptionAlgorithm::AesGcm(params) => {
+                v2::EncryptionAlgorithm::AesGcm(params.into())
+            }
+            sdk_v2::EncryptionAlgorithm::RsaOaep(params) => {
+                v2::EncryptionAlgorithm::RsaOaep(v2::RsaOaepParams {
+                    label: params.label,
+                })
+            }
+        }
+    }
+}
+
+impl From<sdk_v2::WrappingAlgorithm> for v2::WrappingAlgorithm {
+    fn from(algorithm: sdk_v2::WrappingAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::WrappingAlgorithm::AesKw(with_padding) => {
+                v2::WrappingAlgorithm::AesKw(with_padding)
+            }
+            sdk_v2::WrappingAlgorithm::AesGcm(params) => {
+                v2::WrappingAlgorithm::AesGcm(params.into())
+            }
+            sdk_v2::WrappingAlgorithm::RsaOaep(params) => {
+                v2::WrappingAlgorithm::RsaOaep(v2::RsaOaepParams {
+                    label: params.label,
+                })
+            }
+        }
+    }
+}
+
+impl From<sdk_v2::SigningAlgorithm> for v2::SigningAlgorithm {
+    fn from(algorithm: sdk_v2::SigningAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::SigningAlgorithm::Ecdsa(hash) => v2::SigningAlgorithm::Ecdsa(hash.into()),
+            sdk_v2::SigningAlgorithm::RsaPss(salt_length) => {
+                v2::SigningAlgorithm::RsaPss(salt_length)
+            }
+            sdk_v2::SigningAlgorithm::Hmac => v2::SigningAlgorithm::Hmac,
+        }
+    }
+}
+
+impl From<sdk_v2::DerivationAlgorithm> for v2::DerivationAlgorithm {
+    fn from(algorithm: sdk_v2::DerivationAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::DerivationAlgorithm::Ecdh(public_key) => {
+                v2::DerivationAlgorithm::Ecdh(public_key)
+            }
+            sdk_v2::DerivationAlgorithm::Hkdf(params) => {
+                v2::DerivationAlgorithm::Hkdf(v2::HkdfParams {
+                    salt: params.salt,
+                    info: params.info,
+                    hash: params.hash.into(),
+                })
+            }
+        }
+    }
+}
+
+impl From<sdk_v2::DerivedKeyAlgorithm> for v2::DerivedKeyAlgorithm {
+    fn from(algorithm: sdk_v2::DerivedKeyAlgorithm) -> Self {
+        match algorithm {
+            sdk_v2::DerivedKeyAlgorithm::Aes(size) => v2::DerivedKeyAlgorithm::Aes(size.into()),
+            sdk_v2::DerivedKeyAlgorithm::Hmac(params) => {
+                v2::DerivedKeyAlgorithm::Hmac(params.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use klave::context;
+    use sdk::Host as _;
+
+    fn session() -> Session {
+        Session {
+            ledger: Ledger::in_memory(),
+            host: MockHost::new(),
+            https: HttpsStubs::default(),
+            context: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn imports_run_on_the_mock_host() {
+        let context = BTreeMap::from([(context::SENDER.to_string(), "alice".to_string())]);
+        let mut state = HostState::new(session(), context);
+        assert_eq!(
+            state.query_context(context::SENDER.to_string()).unwrap(),
+            "alice"
+        );
+        state
+            .write_ledger("table".to_string(), b"key".to_vec(), b"value".to_vec())
+            .unwrap();
+        state.notify("hello".to_string());
+        assert_eq!(
+            state
+                .read_ledger("table".to_string(), b"key".to_vec())
+                .unwrap(),
+            b"value"
+        );
+        // Same errors as in native tests
+        assert_eq!(
+            state.get_quote(Vec::new()).unwrap_err(),
+            MockHost::new().get_quote(&[]).unwrap_err()
+        );
+
+        let session = state.session;
+        assert!(!session.host.end_transaction(false));
+        assert!(session.host.table("table").is_empty());
+        assert_eq!(
+            session.host.take_notifications(),
+            vec![Notification::Message("hello".to_string())]
+        );
+
+        // A new call starts with its own context and no pending notifications
+        let state = HostState::new(session, BTreeMap::new());
+        assert!(state.host().query_context(context::SENDER).is_err());
+        assert!(state.host().notifications().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context as _, Result};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Request as serialized by `klave::https::request`
+#[derive(Deserialize, Debug)]
+struct HttpRequest {
+    method: String,
+    hostname: String,
+    port: i32,
+    path: String,
+    #[serde(default)]
+    headers: Vec<Vec<String>>,
+    #[serde(default)]
+    body: String,
+    /// `utf8` or `base64`
+    #[serde(default)]
+    body_encoding: Option<String>,
+    #[serde(default)]
+    response_body_encoding: Option<String>,
+}
+
+fn is_base64(encoding: &Option<String>) -> bool {
+    encoding.as_deref() == Some("base64")
+}
+
+#[derive(Serialize, Debug)]
+struct HttpResponse<'a> {
+    status_code: i32,
+    headers: &'a [Vec<String>],
+    body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_encoding: Option<&'a str>,
+}
+
+/// Canned response returned for the requests matching `method` and `url`.
+///
+/// `url` is compared with `https://<hostname>[:<port>]<path>`, the port being omitted when it is 443;
+/// a trailing `*` turns it into a prefix match. A missing `method` matches any method.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Stub {
+    #[serde(default)]
+    pub method: Option<String>,
+    pub url: String,
+    #[serde(default = "default_status")]
+    pub status: i32,
+    #[serde(default)]
+    pub headers: Vec<Vec<String>>,
+    #[serde(default)]
+    pub body: String,
+    /// Binary body, base64 encoded, used instead of `body` when set
+    #[serde(default)]
+    pub body_base64: Option<String>,
+}
+
+fn default_status() -> i32 {
+    200
+}
+
+impl Stub {
+    fn matches(&self, method: &str, url: &str) -> bool {
+        if let Some(expected) = &self.method {
+            if !expected.eq_ignore_ascii_case(method) {
+                return false;
+            }
+        }
+        match self.url.strip_suffix('*') {
+            Some(prefix) => url.starts_with(prefix),
+            None => url == self.url,
+        }
+    }
+}
+
+/// Answers `https-query` calls from a list of stubs, first match wins
+#[derive(Default)]
+pub struct HttpsStubs {
+    stubs: Vec<Stub>,
+}
+
+impl HttpsStubs {
+    /// Load the stubs from a JSON file holding an array of [`Stub`]
+    pub fn load(path: &Path) -> Result<HttpsStubs> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read HTTPS stubs {}", path.display()))?;
+        let stubs = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid HTTPS stubs file {}", path.display()))?;
+        Ok(HttpsStubs { stubs })
+    }
+
+    pub fn respond(&self, request: &str) -> Result<String, String> {
+        let request: HttpRequest =
+            serde_json::from_str(request).map_err(|e| format!("Invalid https request: {e}"))?;
+        let url = if request.port == 443 || request.port == 0 {
+            format!("https://{}{}", request.hostname, request.path)
+        } else {
+            format!(
+                "https://{}:{}{}",
+                request.hostname, request.port, request.path
+            )
+        };
+        let Some(stub) = self
+            .stubs
+            .iter()
+            .find(|stub| stub.matches(&request.method, &url))
+        else {
+            return Err(format!("No HTTPS stub matches {} {url}", request.method));
+        };
+        let body_len = if is_base64(&request.body_encoding) {
+            general_purpose::STANDARD
+                .decode(&request.body)
+                .map_err(|e| format!("Invalid base64 https request body: {e}"))?
+                .len()
+        } else {
+            request.body.len()
+        };
+        println!(
+            "[https] {} {url} ({} header(s), {body_len} byte(s)) -> {}",
+            request.method,
+            request.headers.len(),
+            stub.status
+        );
+        let body = match &stub.body_base64 {
+            Some(body) => general_purpose::STANDARD
+                .decode(body)
+                .map_err(|e| format!("Invalid base64 body in HTTPS stub for {}: {e}", stub.url))?,
+            None => stub.body.clone().into_bytes(),
+        };
+        let (body, body_encoding) = if is_base64(&request.response_body_encoding) {
+            (general_purpose::STANDARD.encode(body), Some("base64"))
+        } else {
+            (String::from_utf8_lossy(&body).into_owned(), None)
+        };
+        serde_json::to_string(&HttpResponse {
+            status_code: stub.status,
+            headers: &stub.headers,
+            body,
+            body_encoding,
+        })
+        .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context as _, Result};
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub type Table = BTreeMap<Vec<u8>, Vec<u8>>;
+pub type Tables = BTreeMap<String, Table>;
+
+/// Ledger persisted as a JSON file, keys and values encoded in base64.
+///
+/// Each call works on a copy of the committed tables; only transactions that
+/// complete without being cancelled are committed and written back to disk.
+pub struct Ledger {
+    path: Option<PathBuf>,
+    committed: Tables,
+}
+
+impl Ledger {
+    /// Ledger kept in memory only
+    pub fn in_memory() -> Ledger {
+        Ledger {
+            path: None,
+            committed: Tables::new(),
+        }
+    }
+
+    /// Open the ledger stored at `path`, starting empty if the file does not exist yet
+    pub fn open(path: &Path) -> Result<Ledger> {
+        let committed = if path.exists() {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read ledger {}", path.display()))?;
+            let encoded: BTreeMap<String, BTreeMap<String, String>> =
+                serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid ledger file {}", path.display()))?;
+            let mut tables = Tables::new();
+            for (name, entries) in encoded {
+                let mut table = Table::new();
+                for (key, value) in entries {
+                    table.insert(
+                        general_purpose::STANDARD.decode(key)?,
+                        general_purpose::STANDARD.decode(value)?,
+                    );
+                }
+                tables.insert(name, table);
+            }
+            tables
+        } else {
+            Tables::new()
+        };
+        Ok(Ledger {
+            path: Some(path.to_path_buf()),
+            committed,
+        })
+    }
+
+    /// Copy of the committed tables for a call to work on
+    pub fn snapshot(&self) -> Tables {
+        self.committed.clone()
+    }
+
+    pub fn tables(&self) -> &Tables {
+        &self.committed
+    }
+
+    /// Replace the committed tables and persist them
+    pub fn commit(&mut self, tables: Tables) -> Result<()> {
+        self.committed = tables;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let encoded: BTreeMap<&String, BTreeMap<String, String>> = self
+            .committed
+            .iter()
+            .map(|(name, table)| {
+                let entries = table
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            general_purpose::STANDARD.encode(key),
+                            general_purpose::STANDARD.encode(value),
+                        )
+                    })
+                    .collect();
+                (name, entries)
+            })
+            .collect();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&encoded)?)
+            .with_context(|| format!("Failed to write ledger {}", path.display()))
+    }
+}
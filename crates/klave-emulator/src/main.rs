@@ -0,0 +1,158 @@
+mod host;
+mod https;
+mod ledger;
+mod runtime;
+mod server;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use host::{Notification, Session};
+use https::HttpsStubs;
+use klave::context;
+use klave::host::mock::MockHost;
+use ledger::Ledger;
+use rand_core::{OsRng, RngCore};
+use runtime::{Emulator, Outcome};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Run a Klave component locally against an emulated host
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Component built against wit/sdk/sdk.wit
+    component: PathBuf,
+
+    /// File holding the ledger, created on the first committed transaction
+    #[arg(long, default_value = "klave-ledger.json")]
+    ledger: PathBuf,
+
+    /// Keep the ledger in memory instead of on disk
+    #[arg(long, conflicts_with = "ledger")]
+    in_memory: bool,
+
+    /// Identity of the caller
+    #[arg(long)]
+    sender: Option<String>,
+
+    /// Context value returned to the component, as KEY=VALUE; can be repeated
+    #[arg(long = "context", value_name = "KEY=VALUE", value_parser = parse_context)]
+    context: Vec<(String, String)>,
+
+    /// JSON file listing the canned responses of HTTPS queries
+    #[arg(long)]
+    https_stubs: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the queries and transactions registered by the component
+    Routes,
+    /// Run a query; its ledger writes are discarded
+    Query {
+        name: String,
+        #[arg(default_value = "")]
+        cmd: String,
+    },
+    /// Run a transaction and commit its ledger writes unless it fails or is cancelled
+    Transaction {
+        name: String,
+        #[arg(default_value = "")]
+        cmd: String,
+    },
+    /// Expose the routes over a local HTTP API
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+}
+
+fn parse_context(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{value}'"))
+}
+
+pub(crate) fn print_outcome(kind: &str, outcome: &Outcome) {
+    for notification in &outcome.notifications {
+        match notification {
+            Notification::Message(msg) => println!("[notify] {msg}"),
+            Notification::Error(msg) => println!("[notify-error] {msg}"),
+            Notification::OnSuccess(msg) => println!("[on-success-notify] {msg}"),
+        }
+    }
+    let status = match (&outcome.error, outcome.cancelled, outcome.committed) {
+        (Some(error), _, _) => format!("failed: {error}"),
+        (None, true, _) => "cancelled".to_string(),
+        (None, false, true) => "committed".to_string(),
+        (None, false, false) => "done".to_string(),
+    };
+    eprintln!("{kind} '{}' {status}", outcome.route);
+}
+
+fn run(cli: Cli) -> Result<bool> {
+    let ledger = if cli.in_memory {
+        Ledger::in_memory()
+    } else {
+        Ledger::open(&cli.ledger)?
+    };
+    let https = match &cli.https_stubs {
+        Some(path) => HttpsStubs::load(path)?,
+        None => HttpsStubs::default(),
+    };
+    let mut values = BTreeMap::new();
+    if let Some(sender) = cli.sender {
+        values.insert(context::SENDER.to_string(), sender);
+    }
+    values.extend(cli.context);
+
+    let host = MockHost::with_seed(OsRng.next_u64());
+    host.set_ledger(ledger.snapshot());
+    let session = Session {
+        ledger,
+        host,
+        https,
+        context: values,
+    };
+    let mut emulator = Emulator::load(&cli.component, session)?;
+
+    let (kind, outcome) = match &cli.command {
+        Command::Routes => {
+            for query in emulator.queries() {
+                println!("query       {query}");
+            }
+            for transaction in emulator.transactions() {
+                println!("transaction {transaction}");
+            }
+            return Ok(true);
+        }
+        Command::Query { name, cmd } => ("query", emulator.query(name, cmd)?),
+        Command::Transaction { name, cmd } => ("transaction", emulator.transaction(name, cmd)?),
+        Command::Serve { listen } => {
+            server::serve(&mut emulator, listen)?;
+            return Ok(true);
+        }
+    };
+    print_outcome(kind, &outcome);
+    if outcome.committed && !cli.in_memory {
+        let tables = emulator.session().ledger.tables().len();
+        eprintln!("ledger: {tables} table(s) in {}", cli.ledger.display());
+    }
+    Ok(outcome.success)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("klave-emulator: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::host::{self, CallKind, HostState, Notification, Session};
+use anyhow::{anyhow, bail, Context as _, Result};
+use klave::context;
+use rand_core::{OsRng, RngCore};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wasmtime::component::{Component, Instance, Linker};
+use wasmtime::{Engine, Store};
+
+const REGISTER_ROUTES: &str = "register-routes";
+
+/// Result of running a query or transaction
+#[derive(Serialize, Debug)]
+pub struct Outcome {
+    pub route: String,
+    pub success: bool,
+    /// Whether the ledger writes of the call were persisted
+    pub committed: bool,
+    pub cancelled: bool,
+    pub error: Option<String>,
+    pub notifications: Vec<Notification>,
+}
+
+/// Klave component instantiated against the emulated host, once per call like on Klave
+pub struct Emulator {
+    engine: Engine,
+    component: Component,
+    linker: Linker<HostState>,
+    session: Option<Session>,
+    queries: Vec<String>,
+    transactions: Vec<String>,
+}
+
+impl Emulator {
+    /// Load the component at `path` and collect the routes it registers
+    pub fn load(path: &Path, session: Session) -> Result<Emulator> {
+        let engine = Engine::default();
+        let component = Component::from_file(&engine, path)
+            .map_err(anyhow::Error::from)
+            .with_context(|| format!("Failed to load component {}", path.display()))?;
+        let mut linker = Linker::new(&engine);
+        host::add_to_linker(&mut linker)?;
+
+        let mut emulator = Emulator {
+            engine,
+            component,
+            linker,
+            session: Some(session),
+            queries: Vec::new(),
+            transactions: Vec::new(),
+        };
+        let (HostState { session }, result) =
+            emulator.call(CallKind::Registration, REGISTER_ROUTES, None)?;
+        session.host.end_transaction(false);
+        emulator.queries = session.host.user_queries();
+        emulator.transactions = session.host.user_transactions();
+        emulator.session = Some(session);
+        result.context("Failed to register routes")?;
+        Ok(emulator)
+    }
+
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+
+    pub fn transactions(&self) -> &[String] {
+        &self.transactions
+    }
+
+    pub fn session(&self) -> &Session {
+        self.session
+            .as_ref()
+            .expect("session is only lent to the store during a call")
+    }
+
+    pub fn query(&mut self, name: &str, cmd: &str) -> Result<Outcome> {
+        if !self.queries.iter().any(|query| query == name) {
+            bail!("Unknown query '{name}'");
+        }
+        self.invoke(CallKind::Query, name, cmd)
+    }
+
+    pub fn transaction(&mut self, name: &str, cmd: &str) -> Result<Outcome> {
+        if !self
+            .transactions
+            .iter()
+            .any(|transaction| transaction == name)
+        {
+            bail!("Unknown transaction '{name}'");
+        }
+        self.invoke(CallKind::Transaction, name, cmd)
+    }
+
+    fn invoke(&mut self, kind: CallKind, name: &str, cmd: &str) -> Result<Outcome> {
+        let (HostState { mut session }, result) = self.call(kind, name, Some(cmd))?;
+        let success = result.is_ok();
+        let cancelled = session.host.is_cancelled();
+        let committed = session
+            .host
+            .end_transaction(success && kind == CallKind::Transaction);
+        let notifications = session.host.take_notifications();
+
+        let commit = if committed {
+            session.ledger.commit(session.host.ledger())
+        } else {
+            Ok(())
+        };
+        self.session = Some(session);
+        commit?;
+
+        let delivered = success && (kind == CallKind::Query || committed);
+        Ok(Outcome {
+            route: name.to_string(),
+            success,
+            committed,
+            cancelled,
+            error: result.err().map(|e| format!("{e:#}")),
+            notifications: notifications
+                .into_iter()
+                .filter(|notification| {
+                    delivered || !matches!(notification, Notification::OnSuccess(_))
+                })
+                .collect(),
+        })
+    }
+
+    /// Run `export` in a fresh instance. The outer error is a failure of the emulator itself,
+    /// the inner one a failure of the component.
+    fn call(
+        &mut self,
+        kind: CallKind,
+        export: &str,
+        cmd: Option<&str>,
+    ) -> Result<(HostState, Result<()>)> {
+        let session = self
+            .session
+            .take()
+            .ok_or_else(|| anyhow!("A call is already in progress"))?;
+        let context = call_context(&session, kind);
+        let mut store = Store::new(&self.engine, HostState::new(session, context));
+
+        let result = match self.linker.instantiate(&mut store, &self.component) {
+            Ok(instance) => run(&mut store, &instance, export, cmd),
+            Err(e) => Err(anyhow::Error::from(e).context("Failed to instantiate component")),
+        };
+        Ok((store.into_data(), result))
+    }
+}
+
+fn run(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+    export: &str,
+    cmd: Option<&str>,
+) -> Result<()> {
+    match cmd {
+        None => {
+            let func = instance
+                .get_typed_func::<(), ()>(&mut *store, export)
+                .map_err(anyhow::Error::from)
+                .with_context(|| format!("Component does not export '{export}'"))?;
+            Ok(func.call(&mut *store, ())?)
+        }
+        Some(cmd) => {
+            let func = instance
+                .get_typed_func::<(&str,), ()>(&mut *store, export)
+                .or_else(|_| {
+                    instance.get_typed_func::<(&str,), ()>(&mut *store, export.replace('_', "-"))
+                })
+                .map_err(anyhow::Error::from)
+                .with_context(|| format!("Component does not export '{export}'"))?;
+            Ok(func.call(&mut *store, (cmd,))?)
+        }
+    }
+}
+
+/// Context values of a call: generated ids and the current time,
+/// overridden by the values given on the command line
+fn call_context(session: &Session, kind: CallKind) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    values.insert(context::TRUSTED_TIME.to_string(), now.to_string());
+    values.insert(context::SENDER.to_string(), "emulator".to_string());
+    values.insert(context::APP_ID.to_string(), "emulator-app".to_string());
+    values.insert(
+        context::DEPLOYMENT_ID.to_string(),
+        "emulator-deployment".to_string(),
+    );
+    match kind {
+        CallKind::Query => {
+            values.insert(context::QUERY_ID.to_string(), random_id());
+        }
+        CallKind::Transaction => {
+            values.insert(context::TRANSACTION_ID.to_string(), random_id());
+        }
+        CallKind::Registration => {}
+    }
+    values.extend(session.context.clone());
+    values
+}
+
+fn random_id() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().fold(String::new(), |mut id, byte| {
+        let _ = write!(id, "{byte:02x}");
+        id
+    })
+}
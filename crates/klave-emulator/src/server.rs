@@ -0,0 +1,73 @@
+use crate::runtime::Emulator;
+use anyhow::{anyhow, Result};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Serve the routes of `emulator` over HTTP:
+///
+/// - `GET /routes` lists the registered queries and transactions
+/// - `POST /query/<name>` and `POST /transaction/<name>` run a route with the request body as argument
+///
+/// Calls are handled one at a time, in order of arrival.
+pub fn serve(emulator: &mut Emulator, address: &str) -> Result<()> {
+    let server =
+        Server::http(address).map_err(|e| anyhow!("Failed to listen on {address}: {e}"))?;
+    println!("Listening on http://{}", server.server_addr());
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(emulator, &mut request);
+        let header =
+            Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header);
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to send response: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(emulator: &mut Emulator, request: &mut Request) -> (u16, String) {
+    let url = request.url().to_string();
+    let segments: Vec<&str> = url.trim_matches('/').split('/').collect();
+    match (request.method(), segments.as_slice()) {
+        (Method::Get, ["routes"]) => (
+            200,
+            serde_json::json!({
+                "queries": emulator.queries(),
+                "transactions": emulator.transactions(),
+            })
+            .to_string(),
+        ),
+        (Method::Post, [kind @ ("query" | "transaction"), name]) => {
+            let kind = *kind;
+            let name = name.to_string();
+            let mut cmd = String::new();
+            if let Err(e) = request.as_reader().read_to_string(&mut cmd) {
+                return error(400, format!("Invalid request body: {e}"));
+            }
+            let registered = match kind {
+                "query" => emulator.queries().contains(&name),
+                _ => emulator.transactions().contains(&name),
+            };
+            if !registered {
+                return error(404, format!("Unknown {kind} '{name}'"));
+            }
+            let outcome = match kind {
+                "query" => emulator.query(&name, &cmd),
+                _ => emulator.transaction(&name, &cmd),
+            };
+            match outcome {
+                Ok(outcome) => {
+                    crate::print_outcome(kind, &outcome);
+                    (200, serde_json::to_string(&outcome).unwrap_or_default())
+                }
+                Err(e) => error(500, format!("{e:#}")),
+            }
+        }
+        _ => error(404, format!("No route for {} {url}", request.method())),
+    }
+}
+
+fn error(status: u16, message: String) -> (u16, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
@@ -0,0 +1,417 @@
+mod outcome;
+
+// Compiles and runs the example of the README
+#[doc = include_str!("../README.md")]
+#[cfg(doctest)]
+pub struct ReadmeDoctests;
+
+pub use klave::host::mock::{LedgerSnapshot, MockHost, Notification, TableSnapshot};
+pub use outcome::Outcome;
+
+use klave::context::{self, Timestamp};
+use klave::logging;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Trusted time of the first call of a scenario: 2025-01-01T00:00:00Z
+const DEFAULT_START: u64 = 1_735_689_600_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RouteKind {
+    Query,
+    Transaction,
+}
+
+struct Route {
+    kind: RouteKind,
+    handler: Option<Rc<dyn Fn(String)>>,
+}
+
+/// App under test, running on a [`MockHost`] installed on the current thread.
+///
+/// Routes are those registered by the app's `register_routes`; each needs a handler,
+/// the function exported for it, before it can be called. Every call gets a fresh context:
+/// the caller, a trusted time advancing by [`Scenario::tick`], a query or transaction id, and
+/// the values set on the scenario and the caller. Transactions are rolled back when they are
+/// cancelled or panic, and queries always are.
+pub struct Scenario {
+    host: Rc<MockHost>,
+    routes: HashMap<String, Route>,
+    context: BTreeMap<String, String>,
+    now: Cell<u64>,
+    tick: Duration,
+    calls: Cell<u64>,
+    context_keys: RefCell<Vec<String>>,
+}
+
+impl Scenario {
+    /// Install a new [`MockHost`] and register the app's routes with `register_routes`
+    pub fn new(register_routes: impl FnOnce()) -> Scenario {
+        Scenario::with_host(MockHost::new(), register_routes)
+    }
+
+    /// Same as [`Scenario::new`] with a host prepared beforehand, e.g. seeded or with an HTTPS responder
+    pub fn with_host(host: MockHost, register_routes: impl FnOnce()) -> Scenario {
+        let host = klave::host::mock::install(host);
+        logging::init();
+        register_routes();
+        let mut routes = HashMap::new();
+        for name in host.user_queries() {
+            routes.insert(
+                name,
+                Route {
+                    kind: RouteKind::Query,
+                    handler: None,
+                },
+            );
+        }
+        for name in host.user_transactions() {
+            routes.insert(
+                name,
+                Route {
+                    kind: RouteKind::Transaction,
+                    handler: None,
+                },
+            );
+        }
+        host.take_notifications();
+        Scenario {
+            host,
+            routes,
+            context: BTreeMap::new(),
+            now: Cell::new(DEFAULT_START),
+            tick: Duration::from_millis(1),
+            calls: Cell::new(0),
+            context_keys: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Function run when `route` is called.
+    ///
+    /// Panics if the app did not register `route`.
+    #[track_caller]
+    pub fn handler(mut self, route: &str, handler: impl Fn(String) + 'static) -> Scenario {
+        match self.routes.get_mut(route) {
+            Some(registered) => registered.handler = Some(Rc::new(handler)),
+            None => panic!("Route '{route}' is not registered by the app"),
+        }
+        self
+    }
+
+    /// Context value given to every call, unless overridden by the caller
+    pub fn context(mut self, param: &str, value: &str) -> Scenario {
+        self.context.insert(param.to_string(), value.to_string());
+        self
+    }
+
+    /// Trusted time of the next call
+    pub fn starting_at(self, time: Timestamp) -> Scenario {
+        self.now.set(time.as_nanos());
+        self
+    }
+
+    /// Trusted time elapsed between two calls, 1 ms by default
+    pub fn tick(mut self, tick: Duration) -> Scenario {
+        self.tick = tick;
+        self
+    }
+
+    pub fn host(&self) -> &MockHost {
+        &self.host
+    }
+
+    /// Queries and transactions registered by the app
+    pub fn routes(&self) -> Vec<(String, RouteKind)> {
+        let mut routes: Vec<_> = self
+            .routes
+            .iter()
+            .map(|(name, route)| (name.clone(), route.kind))
+            .collect();
+        routes.sort();
+        routes
+    }
+
+    /// Trusted time of the next call
+    pub fn now(&self) -> Timestamp {
+        Timestamp::from_nanos(self.now.get())
+    }
+
+    /// Move the trusted time forward
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now().saturating_add(duration).as_nanos());
+    }
+
+    /// Caller identified by `sender`
+    pub fn caller(&self, sender: &str) -> Caller<'_> {
+        Caller {
+            scenario: self,
+            sender: sender.to_string(),
+            context: BTreeMap::new(),
+        }
+    }
+
+    pub fn ledger(&self) -> LedgerSnapshot {
+        self.host.ledger()
+    }
+
+    pub fn table(&self, table: &str) -> TableSnapshot {
+        self.host.table(table)
+    }
+
+    /// Value stored under `key` in `table`
+    pub fn get(&self, table: &str, key: &str) -> Option<Vec<u8>> {
+        self.host.table(table).get(key.as_bytes()).cloned()
+    }
+
+    /// JSON value stored under `key` in `table`; panics if it does not deserialize into `T`
+    #[track_caller]
+    pub fn get_json<T: DeserializeOwned>(&self, table: &str, key: &str) -> Option<T> {
+        self.get(table, key)
+            .map(|value| match serde_json::from_slice(&value) {
+                Ok(value) => value,
+                Err(e) => panic!("Invalid JSON under '{key}' in table '{table}': {e}"),
+            })
+    }
+
+    #[track_caller]
+    fn call(&self, caller: &Caller, kind: RouteKind, route: &str, cmd: String) -> Outcome {
+        let handler = match self.routes.get(route) {
+            Some(registered) if registered.kind != kind => panic!(
+                "Route '{route}' is registered as a {:?}, not a {kind:?}",
+                registered.kind
+            ),
+            Some(Route {
+                handler: Some(handler),
+                ..
+            }) => handler.clone(),
+            Some(_) => panic!("No handler set for route '{route}'"),
+            None => panic!("Route '{route}' is not registered by the app"),
+        };
+
+        let calls = self.calls.get() + 1;
+        self.calls.set(calls);
+        let mut values = BTreeMap::new();
+        values.insert(context::SENDER.to_string(), caller.sender.clone());
+        values.insert(
+            context::TRUSTED_TIME.to_string(),
+            self.now.get().to_string(),
+        );
+        values.insert(context::APP_ID.to_string(), "test-app".to_string());
+        values.insert(
+            context::DEPLOYMENT_ID.to_string(),
+            "test-deployment".to_string(),
+        );
+        match kind {
+            RouteKind::Query => {
+                values.insert(context::QUERY_ID.to_string(), format!("query-{calls}"))
+            }
+            RouteKind::Transaction => values.insert(
+                context::TRANSACTION_ID.to_string(),
+                format!("transaction-{calls}"),
+            ),
+        };
+        values.extend(self.context.clone());
+        values.extend(caller.context.clone());
+        for param in self.context_keys.take() {
+            self.host.remove_context(&param);
+        }
+        for (param, value) in &values {
+            self.host.set_context(param, value);
+        }
+        self.context_keys.replace(values.into_keys().collect());
+        self.advance(self.tick);
+
+        let ledger_before = self.host.ledger();
+        self.host.take_notifications();
+        logging::take_records();
+        self.host.begin_transaction();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| handler(cmd)));
+        let panic = result.err().map(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string())
+        });
+        let cancelled = self.host.is_cancelled();
+        let committed = self
+            .host
+            .end_transaction(kind == RouteKind::Transaction && panic.is_none());
+        self.host.reset_cancelled();
+
+        let delivered = panic.is_none() && (kind == RouteKind::Query || committed);
+        let notifications = self
+            .host
+            .take_notifications()
+            .into_iter()
+            .filter(|notification| delivered || !matches!(notification, Notification::OnSuccess(_)))
+            .collect();
+        Outcome {
+            route: route.to_string(),
+            kind,
+            notifications,
+            logs: logging::take_records(),
+            cancelled,
+            committed,
+            panic,
+            ledger_before,
+            ledger_after: self.host.ledger(),
+        }
+    }
+}
+
+/// Caller of the routes of a [`Scenario`]
+pub struct Caller<'a> {
+    scenario: &'a Scenario,
+    sender: String,
+    context: BTreeMap<String, String>,
+}
+
+impl Caller<'_> {
+    /// Context value given to the calls of this caller
+    pub fn context(mut self, param: &str, value: &str) -> Self {
+        self.context.insert(param.to_string(), value.to_string());
+        self
+    }
+
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    #[track_caller]
+    pub fn query(&self, route: &str, cmd: &str) -> Outcome {
+        self.scenario
+            .call(self, RouteKind::Query, route, cmd.to_string())
+    }
+
+    #[track_caller]
+    pub fn transaction(&self, route: &str, cmd: &str) -> Outcome {
+        self.scenario
+            .call(self, RouteKind::Transaction, route, cmd.to_string())
+    }
+
+    /// Query with `input` serialized to JSON
+    #[track_caller]
+    pub fn query_json<T: Serialize>(&self, route: &str, input: &T) -> Outcome {
+        self.query(route, &to_json(input))
+    }
+
+    /// Transaction with `input` serialized to JSON
+    #[track_caller]
+    pub fn transaction_json<T: Serialize>(&self, route: &str, input: &T) -> Outcome {
+        self.transaction(route, &to_json(input))
+    }
+}
+
+#[track_caller]
+fn to_json<T: Serialize>(input: &T) -> String {
+    match serde_json::to_string(input) {
+        Ok(json) => json,
+        Err(e) => panic!("Failed to serialize input: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use klave::{ledger, notifier, router};
+
+    const TABLE: &str = "values";
+
+    fn register_routes() {
+        router::add_user_transaction("store");
+        router::add_user_transaction("store-and-cancel");
+        router::add_user_query("store-in-query");
+        router::add_user_query("whoami");
+    }
+
+    fn store(cmd: String) {
+        ledger::get_table(TABLE).set_string("key", &cmd).unwrap();
+        notifier::send_string("stored");
+        notifier::on_success_notify("committed");
+    }
+
+    fn scenario() -> Scenario {
+        Scenario::new(register_routes)
+            .handler("store", store)
+            .handler("store-and-cancel", |cmd| {
+                store(cmd);
+                router::cancel_transaction();
+            })
+            .handler("store-in-query", store)
+            .handler("whoami", |_| {
+                notifier::send_string(&context::current().sender().unwrap())
+            })
+    }
+
+    #[test]
+    fn transactions_persist_their_writes() {
+        let scenario = scenario();
+        let outcome = scenario.caller("alice").transaction("store", "1");
+        outcome.assert_succeeded().assert_committed();
+        assert_eq!(scenario.get(TABLE, "key"), Some(b"1".to_vec()));
+        assert_eq!(outcome.ledger_after, scenario.ledger());
+    }
+
+    #[test]
+    fn queries_and_cancelled_transactions_roll_back() {
+        let scenario = scenario();
+        let alice = scenario.caller("alice");
+        alice.transaction("store", "1").assert_committed();
+
+        alice.query("store-in-query", "2").assert_rolled_back();
+        alice
+            .transaction("store-and-cancel", "3")
+            .assert_cancelled()
+            .assert_rolled_back();
+        assert_eq!(scenario.get(TABLE, "key"), Some(b"1".to_vec()));
+
+        // The cancellation does not leak into the next transaction
+        alice.transaction("store", "4").assert_committed();
+        assert_eq!(scenario.get(TABLE, "key"), Some(b"4".to_vec()));
+    }
+
+    #[test]
+    fn each_call_runs_as_its_caller() {
+        let scenario = scenario();
+        scenario
+            .caller("alice")
+            .query("whoami", "")
+            .assert_notified("alice");
+        scenario
+            .caller("bob")
+            .query("whoami", "")
+            .assert_notified("bob");
+        scenario
+            .caller("carol")
+            .context(context::SENDER, "dave")
+            .query("whoami", "")
+            .assert_notified("dave");
+    }
+
+    #[test]
+    fn notifications_are_captured_per_call() {
+        let scenario = scenario();
+        let alice = scenario.caller("alice");
+
+        let outcome = alice.transaction("store", "1");
+        assert_eq!(outcome.messages(), ["stored"]);
+        assert_eq!(outcome.on_success(), ["committed"]);
+
+        // Only delivered once committed
+        let outcome = alice.transaction("store-and-cancel", "2");
+        assert_eq!(outcome.messages(), ["stored"]);
+        assert!(outcome.on_success().is_empty());
+
+        let outcome = alice.query("whoami", "");
+        assert_eq!(
+            outcome.notifications,
+            [Notification::Message("alice".to_string())]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{LedgerSnapshot, Notification, RouteKind};
+use klave::logging::{Level, Record};
+use klave::notifier::ErrorPayload;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// What a call did: its notifications, how it ended, and the ledger around it.
+///
+/// `on-success-notify` messages are only kept when the call succeeded and, for transactions,
+/// committed. The assertion methods panic with the notifications in the message and return
+/// `self`, so they can be chained.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub route: String,
+    pub kind: RouteKind,
+    pub notifications: Vec<Notification>,
+    /// Records logged during the call, when the log sink is the buffer
+    pub logs: Vec<Record>,
+    /// Whether `cancel-transaction` was called
+    pub cancelled: bool,
+    /// Whether the ledger writes of the call were kept
+    pub committed: bool,
+    /// Message of the panic that aborted the call
+    pub panic: Option<String>,
+    pub ledger_before: LedgerSnapshot,
+    pub ledger_after: LedgerSnapshot,
+}
+
+impl Outcome {
+    /// Messages sent with `notify`
+    pub fn messages(&self) -> Vec<&str> {
+        self.notifications
+            .iter()
+            .filter_map(|notification| match notification {
+                Notification::Message(msg) => Some(msg.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Messages sent with `notify-error`
+    pub fn errors(&self) -> Vec<&str> {
+        self.notifications
+            .iter()
+            .filter_map(|notification| match notification {
+                Notification::Error(msg) => Some(msg.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Messages sent with `on-success-notify`
+    pub fn on_success(&self) -> Vec<&str> {
+        self.notifications
+            .iter()
+            .filter_map(|notification| match notification {
+                Notification::OnSuccess(msg) => Some(msg.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Messages sent with `notify` that deserialize into `T`
+    pub fn messages_json<T: DeserializeOwned>(&self) -> Vec<T> {
+        self.messages()
+            .into_iter()
+            .filter_map(|msg| serde_json::from_str(msg).ok())
+            .collect()
+    }
+
+    /// Messages sent with `notify-error` that deserialize into an [`ErrorPayload`]
+    pub fn error_payloads(&self) -> Vec<ErrorPayload> {
+        self.errors()
+            .into_iter()
+            .filter_map(|msg| serde_json::from_str(msg).ok())
+            .collect()
+    }
+
+    /// Messages sent with `on-success-notify` that deserialize into `T`
+    pub fn on_success_json<T: DeserializeOwned>(&self) -> Vec<T> {
+        self.on_success()
+            .into_iter()
+            .filter_map(|msg| serde_json::from_str(msg).ok())
+            .collect()
+    }
+
+    #[track_caller]
+    fn check(&self, condition: bool, expectation: impl std::fmt::Display) -> &Self {
+        if !condition {
+            panic!(
+                "{:?} '{}': expected {expectation}\n  notifications: {:#?}\n  cancelled: {}, committed: {}, panic: {:?}",
+                self.kind, self.route, self.notifications, self.cancelled, self.committed, self.panic
+            );
+        }
+        self
+    }
+
+    /// Assert that `expected` was sent with `notify`
+    #[track_caller]
+    pub fn assert_notified(&self, expected: &str) -> &Self {
+        self.check(
+            self.messages().contains(&expected),
+            format_args!("notification {expected:?}"),
+        )
+    }
+
+    /// Assert that a message sent with `notify` is the JSON serialization of `expected`
+    #[track_caller]
+    pub fn assert_notified_json<T: Serialize>(&self, expected: &T) -> &Self {
+        let expected = serde_json::to_value(expected).unwrap_or_default();
+        let found = self
+            .messages()
+            .into_iter()
+            .filter_map(|msg| serde_json::from_str::<serde_json::Value>(msg).ok())
+            .any(|value| value == expected);
+        self.check(found, format_args!("JSON notification {expected}"))
+    }
+
+    /// Assert that a message sent with `notify-error` contains `expected`
+    #[track_caller]
+    pub fn assert_error(&self, expected: &str) -> &Self {
+        self.check(
+            self.errors().iter().any(|msg| msg.contains(expected)),
+            format_args!("an error containing {expected:?}"),
+        )
+    }
+
+    /// Assert that an [`ErrorPayload`] with `code` was sent with `notify-error`
+    #[track_caller]
+    pub fn assert_error_code(&self, code: &str) -> &Self {
+        self.check(
+            self.error_payloads()
+                .iter()
+                .any(|payload| payload.code == code),
+            format_args!("an error with code {code:?}"),
+        )
+    }
+
+    /// Assert that a record of `level` containing `expected` was logged
+    #[track_caller]
+    pub fn assert_logged(&self, level: Level, expected: &str) -> &Self {
+        self.check(
+            self.logs
+                .iter()
+                .any(|record| record.level == level && record.to_string().contains(expected)),
+            format_args!(
+                "a {level} log containing {expected:?}\n  logs: {:#?}",
+                self.logs
+            ),
+        )
+    }
+
+    /// Assert that nothing was sent with `notify-error`
+    #[track_caller]
+    pub fn assert_no_error(&self) -> &Self {
+        self.check(self.errors().is_empty(), "no error")
+    }
+
+    /// Assert that the call did not panic nor cancel the transaction
+    #[track_caller]
+    pub fn assert_succeeded(&self) -> &Self {
+        self.check(self.panic.is_none() && !self.cancelled, "success")
+    }
+
+    /// Assert that the call panicked with a message containing `expected`
+    #[track_caller]
+    pub fn assert_panicked(&self, expected: &str) -> &Self {
+        self.check(
+            self.panic
+                .as_deref()
+                .is_some_and(|panic| panic.contains(expected)),
+            format_args!("a panic containing {expected:?}"),
+        )
+    }
+
+    #[track_caller]
+    pub fn assert_cancelled(&self) -> &Self {
+        self.check(self.cancelled, "a cancelled transaction")
+    }
+
+    #[track_caller]
+    pub fn assert_committed(&self) -> &Self {
+        self.check(self.committed, "a committed transaction")
+    }
+
+    /// Assert that the writes of the call were discarded, leaving the ledger as it was before it
+    #[track_caller]
+    pub fn assert_rolled_back(&self) -> &Self {
+        self.check(
+            !self.committed && self.ledger_before == self.ledger_after,
+            "the ledger to be rolled back",
+        )
+    }
+}
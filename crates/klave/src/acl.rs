@@ -0,0 +1,368 @@
+use crate::context;
+use crate::error::{Error, ErrorKind, Result, Subsystem};
+use crate::ledger;
+use crate::util::read_or_default;
+use serde::{Deserialize, Serialize};
+
+const ACL_TABLE: &str = "__klave_acl";
+const ACL_AUDIT_TABLE: &str = "__klave_acl_audit";
+const OWNER_KEY: &str = "owner";
+const AUDIT_SEQUENCE_KEY: &str = "sequence";
+
+/// Role held by the identity that bootstrapped the ACL. It passes every role and permission check.
+pub const OWNER_ROLE: &str = "owner";
+/// Role allowed to grant and revoke roles and permissions besides the owner.
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Serialize, Deserialize, Default)]
+struct Member {
+    roles: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Role {
+    permissions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Bootstrap,
+    GrantRole,
+    RevokeRole,
+    GrantPermission,
+    RevokePermission,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub subject: String,
+    pub value: String,
+}
+
+fn member_key(identity: &str) -> String {
+    format!("member:{identity}")
+}
+
+fn role_key(role: &str) -> String {
+    format!("role:{role}")
+}
+
+fn audit(actor: &str, action: AuditAction, subject: &str, value: &str) -> Result<()> {
+    let table = ledger::get_table(ACL_AUDIT_TABLE);
+    let sequence = read_or_default::<u64>(&table, AUDIT_SEQUENCE_KEY)? + 1;
+    let entry = AuditEntry {
+        sequence,
+        actor: actor.to_string(),
+        action,
+        subject: subject.to_string(),
+        value: value.to_string(),
+    };
+    table.set_json(&format!("{sequence:020}"), &entry)?;
+    table.set_json(AUDIT_SEQUENCE_KEY, &sequence)
+}
+
+/// Identity of the caller of the current query or transaction
+pub fn caller() -> Result<String> {
+    context::current().sender()
+}
+
+/// Identity of the owner, if the ACL has been bootstrapped
+pub fn owner() -> Result<Option<String>> {
+    let table = ledger::get_table(ACL_TABLE);
+    if table.exists(OWNER_KEY)? {
+        Ok(Some(table.get_string(OWNER_KEY)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Make `owner` the owner of the application. The caller must be `owner`.
+///
+/// `owner` is the identity of the deployer, configured in the app, e.g. with
+/// `bootstrap(env!("APP_OWNER"))`: otherwise whoever sends the first transaction after
+/// deployment would take the app. Only the first call succeeds; it must be made from a transaction.
+pub fn bootstrap(owner: &str) -> Result<String> {
+    if owner.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid owner: cannot be empty",
+        ));
+    }
+    require_transaction()?;
+    if let Some(current) = self::owner()? {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::AlreadyExists,
+            format!("ACL already bootstrapped: owner is {current}"),
+        ));
+    }
+    let caller = caller()?;
+    if caller != owner {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            format!("Access denied: {caller} is not the configured owner"),
+        ));
+    }
+    let table = ledger::get_table(ACL_TABLE);
+    table.set_string(OWNER_KEY, &caller)?;
+    table.set_json(
+        &member_key(&caller),
+        &Member {
+            roles: vec![OWNER_ROLE.to_string()],
+        },
+    )?;
+    audit(&caller, AuditAction::Bootstrap, &caller, OWNER_ROLE)?;
+    Ok(caller)
+}
+
+/// List the roles granted to an identity
+pub fn roles_of(identity: &str) -> Result<Vec<String>> {
+    let member: Member = read_or_default(&ledger::get_table(ACL_TABLE), &member_key(identity))?;
+    Ok(member.roles)
+}
+
+/// List the permissions attached to a role
+pub fn permissions_of(role: &str) -> Result<Vec<String>> {
+    let role: Role = read_or_default(&ledger::get_table(ACL_TABLE), &role_key(role))?;
+    Ok(role.permissions)
+}
+
+fn is_owner(identity: &str) -> Result<bool> {
+    Ok(owner()?.as_deref() == Some(identity))
+}
+
+/// Check whether an identity holds a role
+pub fn has_role(identity: &str, role: &str) -> Result<bool> {
+    if is_owner(identity)? {
+        return Ok(true);
+    }
+    Ok(roles_of(identity)?.iter().any(|r| r == role))
+}
+
+/// Check whether any role of an identity grants a permission
+pub fn has_permission(identity: &str, permission: &str) -> Result<bool> {
+    if is_owner(identity)? {
+        return Ok(true);
+    }
+    for role in roles_of(identity)? {
+        if permissions_of(&role)?.iter().any(|p| p == permission) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Fail unless the caller holds the given role
+pub fn require_role(role: &str) -> Result<()> {
+    let caller = caller()?;
+    if has_role(&caller, role)? {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            format!("Access denied: {caller} does not hold role {role}"),
+        ))
+    }
+}
+
+/// Fail unless the caller holds a role granting the given permission
+pub fn require_permission(permission: &str) -> Result<()> {
+    let caller = caller()?;
+    if has_permission(&caller, permission)? {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            format!("Access denied: {caller} does not have permission {permission}"),
+        ))
+    }
+}
+
+fn require_transaction() -> Result<()> {
+    context::current().require_transaction(Subsystem::Access, "Changing the ACL")
+}
+
+fn require_admin() -> Result<String> {
+    let caller = caller()?;
+    if has_role(&caller, ADMIN_ROLE)? {
+        Ok(caller)
+    } else {
+        Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            format!("Access denied: {caller} is neither owner nor {ADMIN_ROLE}"),
+        ))
+    }
+}
+
+/// Grant a role to an identity. The caller must be the owner or an admin, in a transaction.
+pub fn grant_role(identity: &str, role: &str) -> Result<()> {
+    if identity.is_empty() || role.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid identity or role: cannot be empty",
+        ));
+    }
+    if role == OWNER_ROLE {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid role: owner role cannot be granted",
+        ));
+    }
+    require_transaction()?;
+    let caller = require_admin()?;
+    let table = ledger::get_table(ACL_TABLE);
+    let mut member: Member = read_or_default(&table, &member_key(identity))?;
+    if !member.roles.iter().any(|r| r == role) {
+        member.roles.push(role.to_string());
+        table.set_json(&member_key(identity), &member)?;
+    }
+    audit(&caller, AuditAction::GrantRole, identity, role)
+}
+
+/// Revoke a role from an identity. The caller must be the owner or an admin, in a transaction.
+pub fn revoke_role(identity: &str, role: &str) -> Result<()> {
+    if role == OWNER_ROLE {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid role: owner role cannot be revoked",
+        ));
+    }
+    require_transaction()?;
+    let caller = require_admin()?;
+    let table = ledger::get_table(ACL_TABLE);
+    let mut member: Member = read_or_default(&table, &member_key(identity))?;
+    member.roles.retain(|r| r != role);
+    table.set_json(&member_key(identity), &member)?;
+    audit(&caller, AuditAction::RevokeRole, identity, role)
+}
+
+/// Attach a permission to a role. The caller must be the owner or an admin, in a transaction.
+pub fn grant_permission(role: &str, permission: &str) -> Result<()> {
+    if role.is_empty() || permission.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid role or permission: cannot be empty",
+        ));
+    }
+    require_transaction()?;
+    let caller = require_admin()?;
+    let table = ledger::get_table(ACL_TABLE);
+    let mut entry: Role = read_or_default(&table, &role_key(role))?;
+    if !entry.permissions.iter().any(|p| p == permission) {
+        entry.permissions.push(permission.to_string());
+        table.set_json(&role_key(role), &entry)?;
+    }
+    audit(&caller, AuditAction::GrantPermission, role, permission)
+}
+
+/// Detach a permission from a role. The caller must be the owner or an admin, in a transaction.
+pub fn revoke_permission(role: &str, permission: &str) -> Result<()> {
+    require_transaction()?;
+    let caller = require_admin()?;
+    let table = ledger::get_table(ACL_TABLE);
+    let mut entry: Role = read_or_default(&table, &role_key(role))?;
+    entry.permissions.retain(|p| p != permission);
+    table.set_json(&role_key(role), &entry)?;
+    audit(&caller, AuditAction::RevokePermission, role, permission)
+}
+
+/// Retrieve the audit trail of ACL changes, oldest first
+pub fn audit_log() -> Result<Vec<AuditEntry>> {
+    let table = ledger::get_table(ACL_AUDIT_TABLE);
+    let mut keys = table.list_keys()?;
+    keys.retain(|key| key != AUDIT_SEQUENCE_KEY);
+    keys.sort();
+    keys.iter().map(|key| table.get_json(key)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    #[test]
+    fn only_the_configured_owner_can_bootstrap() {
+        let host = install(MockHost::new());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host.set_context(context::SENDER, "mallory");
+        let err = bootstrap("alice").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(owner().unwrap(), None);
+
+        host.set_context(context::SENDER, "alice");
+        assert_eq!(bootstrap("alice").unwrap(), "alice");
+        assert_eq!(owner().unwrap().as_deref(), Some("alice"));
+        assert!(has_role("alice", ADMIN_ROLE).unwrap());
+        assert_eq!(
+            bootstrap("alice").unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+    }
+
+    #[test]
+    fn admins_manage_roles_and_permissions() {
+        let host = install(MockHost::new());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host.set_context(context::SENDER, "alice");
+        bootstrap("alice").unwrap();
+        grant_role("bob", ADMIN_ROLE).unwrap();
+        grant_permission("reader", "read").unwrap();
+
+        host.set_context(context::SENDER, "bob");
+        grant_role("carol", "reader").unwrap();
+        assert!(has_permission("carol", "read").unwrap());
+        revoke_permission("reader", "read").unwrap();
+        assert!(!has_permission("carol", "read").unwrap());
+        revoke_role("carol", "reader").unwrap();
+        assert!(roles_of("carol").unwrap().is_empty());
+
+        host.set_context(context::SENDER, "carol");
+        assert_eq!(
+            grant_role("carol", ADMIN_ROLE).unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(audit_log().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn queries_cannot_change_the_acl() {
+        let host = install(MockHost::new());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host.set_context(context::SENDER, "alice");
+        bootstrap("alice").unwrap();
+
+        host.remove_context(context::TRANSACTION_ID);
+        host.set_context(context::QUERY_ID, "q1");
+        for err in [
+            grant_role("bob", ADMIN_ROLE).unwrap_err(),
+            revoke_role("bob", ADMIN_ROLE).unwrap_err(),
+            grant_permission("reader", "read").unwrap_err(),
+            revoke_permission("reader", "read").unwrap_err(),
+        ] {
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+        }
+        assert!(roles_of("bob").unwrap().is_empty());
+        assert_eq!(audit_log().unwrap().len(), 1);
+
+        let host = install(MockHost::new());
+        host.set_context(context::SENDER, "alice");
+        host.set_context(context::QUERY_ID, "q1");
+        assert_eq!(
+            bootstrap("alice").unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
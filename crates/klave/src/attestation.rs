@@ -1,3 +1,4 @@
+use crate::error::{Error, ErrorKind, ResultExt, Subsystem};
 use crate::sdk;
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -229,45 +230,58 @@ pub enum Quote {
     V4(Quote4),
 }
 
-pub fn get_quote(challenge: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let quote_bytes =
-        sdk::get_quote(challenge).map_err(|e| -> String { format!("Failed to get quote: {e}") })?;
+pub fn get_quote(challenge: &[u8]) -> Result<Vec<u8>, Error> {
+    let quote_bytes = sdk::get_quote(challenge)
+        .map_err(|e| Error::host(Subsystem::Attestation, "Failed to get quote", e))?;
 
     Ok(quote_bytes)
 }
 
-pub fn verify_quote(
-    quote: &[u8],
-    current_time: i64,
-) -> Result<VerifyQuoteResponse, Box<dyn std::error::Error>> {
+pub fn verify_quote(quote: &[u8], current_time: i64) -> Result<VerifyQuoteResponse, Error> {
     let response_str = sdk::verify_quote(current_time, quote)
-        .map_err(|e| -> String { format!("Failed to verify quote: {e}") })?;
+        .map_err(|e| Error::host(Subsystem::Attestation, "Failed to verify quote", e))?;
 
     //Deserialize the response into a VerifyQuoteResponse
-    let response: VerifyQuoteResponse = serde_json::from_str(&response_str)
-        .map_err(|e| -> String { format!("Failed to deserialize response: {e}") })?;
+    let response: VerifyQuoteResponse = serde_json::from_str(&response_str).or_error(
+        Subsystem::Attestation,
+        ErrorKind::Serialization,
+        "Failed to deserialize response",
+    )?;
 
     Ok(response)
 }
 
-pub fn parse_quote(quote: &[u8]) -> Result<Quote, Box<dyn std::error::Error>> {
-    let version = quote.first().ok_or("Quote is empty")?;
+pub fn parse_quote(quote: &[u8]) -> Result<Quote, Error> {
+    let version = quote
+        .first()
+        .ok_or_else(|| Error::invalid_input(Subsystem::Attestation, "Quote is empty"))?;
 
-    let quote_parsed_string =
-        sdk::parse_quote(quote).map_err(|e| format!("Failed to parse quote: {e}"))?;
+    let quote_parsed_string = sdk::parse_quote(quote)
+        .map_err(|e| Error::host(Subsystem::Attestation, "Failed to parse quote", e))?;
 
     let parsed: Quote = match version {
         3 => {
-            let q3 = serde_json::from_str::<Quote3>(&quote_parsed_string)
-                .map_err(|e| format!("Failed to parse Quote3: {e}"))?;
+            let q3 = serde_json::from_str::<Quote3>(&quote_parsed_string).or_error(
+                Subsystem::Attestation,
+                ErrorKind::Serialization,
+                "Failed to parse Quote3",
+            )?;
             Quote::V3(q3)
         }
         4 => {
-            let q4 = serde_json::from_str::<Quote4>(&quote_parsed_string)
-                .map_err(|e| format!("Failed to parse Quote4: {e}"))?;
+            let q4 = serde_json::from_str::<Quote4>(&quote_parsed_string).or_error(
+                Subsystem::Attestation,
+                ErrorKind::Serialization,
+                "Failed to parse Quote4",
+            )?;
             Quote::V4(q4)
         }
-        _ => return Err("Unsupported quote version".into()),
+        _ => {
+            return Err(Error::invalid_input(
+                Subsystem::Attestation,
+                "Unsupported quote version",
+            ))
+        }
     };
 
     Ok(parsed)
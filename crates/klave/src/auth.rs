@@ -0,0 +1,300 @@
+use crate::context::{self, Timestamp};
+use crate::crypto::ecc;
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::ledger;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::Duration;
+
+const NONCE_TABLE: &str = "__klave_auth_nonces";
+const ENVELOPE_DOMAIN: &str = "klave-signed-request-v1";
+
+/// Payload signed by an end user's key
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedRequest {
+    /// Serialized (JSON) payload of the request
+    pub payload: String,
+    /// Unique value chosen by the client, never reused with the same key
+    pub nonce: String,
+    /// Client time of signature, in nanoseconds since the Unix epoch
+    pub timestamp: Timestamp,
+    /// SPKI-encoded public key of the signer
+    pub public_key: Vec<u8>,
+    /// ECDSA signature of [`SignedRequest::canonical_bytes`]
+    pub signature: Vec<u8>,
+}
+
+impl SignedRequest {
+    /// Bytes covered by the signature: the domain tag, nonce, timestamp, public key and payload,
+    /// each prefixed by its length as a big-endian u32
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        length_prefixed(&[
+            ENVELOPE_DOMAIN.as_bytes(),
+            self.nonce.as_bytes(),
+            &self.timestamp.as_nanos().to_be_bytes(),
+            &self.public_key,
+            self.payload.as_bytes(),
+        ])
+    }
+}
+
+/// Concatenation of `fields`, each prefixed by its length as a big-endian u32, so that
+/// no two lists of fields give the same bytes
+pub(crate) fn length_prefixed(fields: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for field in fields {
+        bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(field);
+    }
+    bytes
+}
+
+/// Rules applied when verifying a [`SignedRequest`]
+pub struct Policy {
+    /// Maximum distance between the request timestamp and the trusted time
+    pub window: Duration,
+    pub named_curve: String,
+    pub hash: String,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            window: Duration::from_secs(5 * 60),
+            named_curve: "P-256".to_string(),
+            hash: "SHA2-256".to_string(),
+        }
+    }
+}
+
+/// Payload of a request whose signature and freshness have been checked
+#[derive(Debug, Clone)]
+pub struct Verified<T> {
+    pub payload: T,
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UsedNonce {
+    timestamp: Timestamp,
+}
+
+fn nonce_key(public_key: &[u8], nonce: &str) -> String {
+    format!(
+        "{}:{nonce}",
+        general_purpose::STANDARD_NO_PAD.encode(public_key)
+    )
+}
+
+/// Verify the signature of a request, reject it if it is outside the time window
+/// or if its nonce was already used, then record the nonce.
+///
+/// Nonces are stored in the ledger, whose writes are dropped at the end of a query, so it fails
+/// with [`ErrorKind::Unsupported`] outside a transaction rather than accept replays.
+pub fn verify(request: &SignedRequest, policy: &Policy) -> Result<Verified<String>> {
+    context::current().require_transaction(Subsystem::Access, "Signed request verification")?;
+    if request.nonce.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid signed request: nonce cannot be empty",
+        ));
+    }
+    if request.public_key.is_empty() || request.signature.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid signed request: missing public key or signature",
+        ));
+    }
+
+    let now = context::current().trusted_time()?;
+    let skew = if now > request.timestamp {
+        now.duration_since(request.timestamp)
+    } else {
+        request.timestamp.duration_since(now)
+    };
+    if skew > policy.window {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::Expired,
+            format!(
+                "Invalid signed request: timestamp {} is outside the {}s window around {now}",
+                request.timestamp,
+                policy.window.as_secs()
+            ),
+        ));
+    }
+
+    let result = ecc::verify_ecdsa(
+        &policy.named_curve,
+        &policy.hash,
+        &request.public_key,
+        &request.canonical_bytes(),
+        &request.signature,
+    )?;
+    if !result.is_valid() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            "Invalid signed request: signature verification failed",
+        ));
+    }
+
+    let table = ledger::get_table(NONCE_TABLE);
+    let key = nonce_key(&request.public_key, &request.nonce);
+    if table.exists(&key)? {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::AlreadyExists,
+            format!(
+                "Replayed signed request: nonce {} already used",
+                request.nonce
+            ),
+        ));
+    }
+    table.set_json(
+        &key,
+        &UsedNonce {
+            timestamp: request.timestamp,
+        },
+    )?;
+
+    Ok(Verified {
+        payload: request.payload.clone(),
+        public_key: request.public_key.clone(),
+    })
+}
+
+/// Verify a request and deserialize its JSON payload
+pub fn verify_json<T: DeserializeOwned>(
+    request: &SignedRequest,
+    policy: &Policy,
+) -> Result<Verified<T>> {
+    let verified = verify(request, policy)?;
+    Ok(Verified {
+        payload: serde_json::from_str(&verified.payload).or_error(
+            Subsystem::Access,
+            ErrorKind::Serialization,
+            "Failed to deserialize value",
+        )?,
+        public_key: verified.public_key,
+    })
+}
+
+/// Forget the nonces that fell out of the policy window and return how many were removed
+pub fn purge_nonces(policy: &Policy) -> Result<usize> {
+    let table = ledger::get_table(NONCE_TABLE);
+    let now = context::current().trusted_time()?;
+    let mut removed = 0;
+    for key in table.list_keys()? {
+        let used: UsedNonce = table.get_json(&key)?;
+        if now.duration_since(used.timestamp) > policy.window {
+            table.remove(&key)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::subtle::{self, CryptoKey, EcKeyGenParams, EcdsaParams};
+    use crate::crypto::subtle::{KeyGenAlgorithm, SignAlgorithm};
+    use crate::host::mock::{install, MockHost};
+    use std::rc::Rc;
+
+    const NOW: u64 = 1_000_000_000_000;
+
+    fn setup() -> (Rc<MockHost>, CryptoKey, Vec<u8>) {
+        let host = install(MockHost::new());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host.set_context(context::TRUSTED_TIME, &NOW.to_string());
+        let key = subtle::generate_key(
+            &KeyGenAlgorithm::Ecc(EcKeyGenParams {
+                named_curve: "P-256".to_string(),
+            }),
+            true,
+            &["sign", "verify"],
+        )
+        .unwrap();
+        let spki = subtle::export_key("spki", &subtle::get_public_key(&key).unwrap()).unwrap();
+        (host, key, spki)
+    }
+
+    fn signed(key: &CryptoKey, spki: &[u8], nonce: &str, timestamp: u64) -> SignedRequest {
+        let mut request = SignedRequest {
+            payload: r#"{"amount":10}"#.to_string(),
+            nonce: nonce.to_string(),
+            timestamp: Timestamp::from_nanos(timestamp),
+            public_key: spki.to_vec(),
+            signature: vec![],
+        };
+        request.signature = subtle::sign(
+            &SignAlgorithm::Ecdsa(EcdsaParams::default()),
+            key,
+            &request.canonical_bytes(),
+        )
+        .unwrap();
+        request
+    }
+
+    #[test]
+    fn replayed_nonces_are_rejected() {
+        let (_host, key, spki) = setup();
+        let verified =
+            verify_json::<serde_json::Value>(&signed(&key, &spki, "n1", NOW), &Policy::default())
+                .unwrap();
+        assert_eq!(verified.payload["amount"], 10);
+        assert_eq!(verified.public_key, spki);
+
+        let replay = signed(&key, &spki, "n1", NOW + 1);
+        assert_eq!(
+            verify(&replay, &Policy::default()).unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+        verify(&signed(&key, &spki, "n2", NOW), &Policy::default()).unwrap();
+    }
+
+    #[test]
+    fn timestamps_outside_the_window_are_rejected() {
+        let (_host, key, spki) = setup();
+        let window = Policy::default().window.as_nanos() as u64;
+        for timestamp in [NOW - window - 1, NOW + window + 1] {
+            assert_eq!(
+                verify(&signed(&key, &spki, "n1", timestamp), &Policy::default())
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::Expired
+            );
+        }
+        verify(&signed(&key, &spki, "n1", NOW - window), &Policy::default()).unwrap();
+    }
+
+    #[test]
+    fn bad_signatures_are_rejected() {
+        let (_host, key, spki) = setup();
+        let mut request = signed(&key, &spki, "n1", NOW);
+        request.payload = r#"{"amount":1000}"#.to_string();
+        assert_eq!(
+            verify(&request, &Policy::default()).unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        // The nonce of a rejected request stays available
+        verify(&signed(&key, &spki, "n1", NOW), &Policy::default()).unwrap();
+    }
+
+    #[test]
+    fn queries_are_refused() {
+        let (host, key, spki) = setup();
+        host.remove_context(context::TRANSACTION_ID);
+        host.set_context(context::QUERY_ID, "q1");
+        assert_eq!(
+            verify(&signed(&key, &spki, "n1", NOW), &Policy::default())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
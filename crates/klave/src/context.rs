@@ -1,8 +1,219 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
 use crate::sdk;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn get(param: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub const SENDER: &str = "sender";
+pub const TRANSACTION_ID: &str = "transaction_id";
+pub const QUERY_ID: &str = "query_id";
+pub const TRUSTED_TIME: &str = "trusted_time";
+pub const APP_ID: &str = "app_id";
+pub const DEPLOYMENT_ID: &str = "deployment_id";
+
+pub fn get(param: &str) -> Result<String> {
     match sdk::query_context(param) {
         Ok(result) => Ok(result),
-        Err(err) => Err(err.into()),
+        Err(err) => Err(Error::host(
+            Subsystem::Context,
+            format!("Failed to read context value '{param}'"),
+            err,
+        )),
+    }
+}
+
+/// Point in time given by the host's trusted clock, in nanoseconds since the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub fn from_nanos(nanos: u64) -> Timestamp {
+        Timestamp(nanos)
+    }
+
+    pub fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    pub fn as_millis(&self) -> u64 {
+        self.0 / 1_000_000
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.0 / 1_000_000_000
+    }
+
+    /// Timestamp `duration` later, saturating at the end of representable time
+    pub fn saturating_add(&self, duration: Duration) -> Timestamp {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        Timestamp(self.0.saturating_add(nanos))
+    }
+
+    /// Time elapsed since `earlier`, or zero if `earlier` is later than `self`
+    pub fn duration_since(&self, earlier: Timestamp) -> Duration {
+        Duration::from_nanos(self.0.saturating_sub(earlier.0))
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.0)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:09}",
+            self.0 / 1_000_000_000,
+            self.0 % 1_000_000_000
+        )
+    }
+}
+
+/// Typed view over the values the host exposes for the current query or transaction
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Context;
+
+impl Context {
+    pub fn current() -> Context {
+        Context
+    }
+
+    fn require(&self, param: &str) -> Result<String> {
+        let value = get(param)?;
+        if value.is_empty() {
+            return Err(Error::not_found(
+                Subsystem::Context,
+                format!("Context value '{param}' is not provided by the host"),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Identity of the caller
+    pub fn sender(&self) -> Result<String> {
+        self.require(SENDER)
+    }
+
+    /// Id of the current transaction; only available in transactions
+    pub fn transaction_id(&self) -> Result<String> {
+        self.require(TRANSACTION_ID)
+    }
+
+    /// Whether the current call is a transaction, whose ledger writes are kept
+    pub fn is_transaction(&self) -> bool {
+        get(TRANSACTION_ID).is_ok_and(|id| !id.is_empty())
+    }
+
+    /// Fails with [`ErrorKind::Unsupported`] outside a transaction: `what` writes to the ledger,
+    /// and the writes of queries are dropped
+    #[cfg(feature = "ledger")]
+    pub(crate) fn require_transaction(&self, subsystem: Subsystem, what: &str) -> Result<()> {
+        if self.is_transaction() {
+            return Ok(());
+        }
+        Err(Error::new(
+            subsystem,
+            ErrorKind::Unsupported,
+            format!("{what} needs a transaction: the ledger writes of queries are not kept"),
+        ))
+    }
+
+    /// Id of the current query; only available in queries
+    pub fn query_id(&self) -> Result<String> {
+        self.require(QUERY_ID)
+    }
+
+    /// Trusted time of the current query or transaction
+    pub fn trusted_time(&self) -> Result<Timestamp> {
+        let time = self.require(TRUSTED_TIME)?;
+        let nanos = time.parse::<u64>().map_err(|e| {
+            Error::new(
+                Subsystem::Context,
+                ErrorKind::Serialization,
+                format!("Invalid context value '{TRUSTED_TIME}': '{time}'"),
+            )
+            .with_source(e)
+        })?;
+        Ok(Timestamp(nanos))
+    }
+
+    /// Id of the application
+    pub fn app_id(&self) -> Result<String> {
+        self.require(APP_ID)
+    }
+
+    /// Id of the deployment running the application
+    pub fn deployment_id(&self) -> Result<String> {
+        self.require(DEPLOYMENT_ID)
+    }
+}
+
+/// Retrieve the context of the current query or transaction
+pub fn current() -> Context {
+    Context::current()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    #[test]
+    fn empty_transaction_ids_are_queries() {
+        let host = install(MockHost::new());
+        assert!(!current().is_transaction());
+
+        host.set_context(TRANSACTION_ID, "");
+        assert!(!current().is_transaction());
+        assert_eq!(
+            current().transaction_id().unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+
+        host.set_context(TRANSACTION_ID, "t1");
+        assert!(current().is_transaction());
+        assert_eq!(current().transaction_id().unwrap(), "t1");
+    }
+
+    #[test]
+    fn missing_values_are_host_errors() {
+        let _host = install(MockHost::new());
+        let err = current().sender().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Host);
+        assert_eq!(err.subsystem(), Subsystem::Context);
+    }
+
+    #[test]
+    fn trusted_time_is_in_nanoseconds() {
+        let host = install(MockHost::new());
+        host.set_context(TRUSTED_TIME, "1700000000123456789");
+        let time = current().trusted_time().unwrap();
+        assert_eq!(time.as_nanos(), 1_700_000_000_123_456_789);
+        assert_eq!(time.as_millis(), 1_700_000_000_123);
+        assert_eq!(time.as_secs(), 1_700_000_000);
+        assert_eq!(time.to_string(), "1700000000.123456789");
+        assert_eq!(
+            time.to_system_time(),
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789)
+        );
+        assert_eq!(
+            time.saturating_add(Duration::from_millis(1)).as_nanos(),
+            1_700_000_000_124_456_789
+        );
+        assert_eq!(
+            time.duration_since(Timestamp::from_nanos(1_700_000_000_000_000_000)),
+            Duration::from_nanos(123_456_789)
+        );
+        assert_eq!(
+            Timestamp::from_nanos(1).duration_since(time),
+            Duration::ZERO
+        );
+
+        host.set_context(TRUSTED_TIME, "1700000000.5");
+        assert_eq!(
+            current().trusted_time().unwrap_err().kind(),
+            ErrorKind::Serialization
+        );
     }
 }
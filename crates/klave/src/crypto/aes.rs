@@ -1,13 +1,15 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 use std::fmt::Display;
 
 use super::random;
 use super::sdk_wrapper::CryptoImpl;
 use super::sdk_wrapper::Key;
-use super::subtle::{AesKeyGenParams, CryptoKey};
+use super::subtle::CryptoKey;
 use super::subtle_idl_v1::AesGcmEncryptionMetadata;
-use super::subtle_idl_v1_enums::{AesTagLength, EncryptionAlgorithm, KeyAlgorithm};
+use super::subtle_idl_v1_enums::AesTagLength;
+use super::subtle_idl_v2::{AesKeySize, EncryptionAlgorithm, KeyAlgorithm};
+use super::util;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyAES {
@@ -44,7 +46,7 @@ impl KeyAES {
         }
     }
 
-    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
         let iv = random::get_random_bytes(12)?;
         let aes_gcm_params = AesGcmEncryptionMetadata {
             iv: iv.clone(),
@@ -54,8 +56,7 @@ impl KeyAES {
 
         match CryptoImpl::encrypt(
             &self.key.name(),
-            EncryptionAlgorithm::AesGcm as u32,
-            &serde_json::to_string(&aes_gcm_params)?,
+            &EncryptionAlgorithm::AesGcm((&aes_gcm_params).into()),
             data,
         ) {
             Ok(result) => {
@@ -67,9 +68,14 @@ impl KeyAES {
         }
     }
 
-    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
-        let iv = &data[0..12];
-        let data = &data[12..];
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < 12 {
+            return Err(Error::invalid_input(
+                Subsystem::Crypto,
+                "Invalid ciphertext: shorter than its 12 byte IV",
+            ));
+        }
+        let (iv, data) = data.split_at(12);
         let aes_gcm_params = AesGcmEncryptionMetadata {
             iv: iv.to_vec(),
             additional_data: vec![],
@@ -78,8 +84,7 @@ impl KeyAES {
 
         match CryptoImpl::decrypt(
             &self.key.name(),
-            EncryptionAlgorithm::AesGcm as u32,
-            &serde_json::to_string(&aes_gcm_params)?,
+            &EncryptionAlgorithm::AesGcm((&aes_gcm_params).into()),
             data,
         ) {
             Ok(result) => Ok(result),
@@ -88,32 +93,37 @@ impl KeyAES {
     }
 }
 
-pub fn get_key(name: &str) -> Result<KeyAES, Box<dyn Error>> {
+pub fn get_key(name: &str) -> Result<KeyAES> {
     match CryptoImpl::key_exists(name) {
         Ok(_) => Ok(KeyAES::new(name, 256)),
         Err(err) => Err(err),
     }
 }
 
-pub fn generate_key(name: &str) -> Result<KeyAES, Box<dyn Error>> {
+pub fn generate_key(name: &str) -> Result<KeyAES> {
     if name.is_empty() {
-        return Err("Invalid key name: key name cannot be empty".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: key name cannot be empty",
+        ));
     }
 
     match CryptoImpl::key_exists(name) {
         Ok(exists) => {
             if exists {
-                return Err(format!("Invalid key name: key name {name} already exists").into());
+                return Err(Error::new(
+                    Subsystem::Crypto,
+                    ErrorKind::AlreadyExists,
+                    format!("Invalid key name: key name {name} already exists"),
+                ));
             }
         }
         Err(e) => return Err(e),
     }
 
-    let metadata = AesKeyGenParams { length: 256 };
     let key = CryptoImpl::generate_key(
         name,
-        KeyAlgorithm::Aes as u32,
-        &serde_json::to_string(&metadata)?,
+        &KeyAlgorithm::Aes(AesKeySize::Bits256),
         true,
         &["encrypt", "decrypt"],
     )?;
@@ -123,9 +133,9 @@ pub fn generate_key(name: &str) -> Result<KeyAES, Box<dyn Error>> {
         Err(e) => return Err(e),
     };
 
-    match serde_json::from_str::<CryptoKey>(&String::from_utf8(key)?) {
+    match util::from_json::<CryptoKey>(&util::from_utf8(key)?) {
         Ok(_) => (),
-        Err(e) => return Err(e.into()),
+        Err(e) => return Err(e),
     };
 
     Ok(KeyAES::new(name, 256))
@@ -1,14 +1,25 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Display;
 
 use super::keys::PublicKey;
 use super::sdk_wrapper::{CryptoImpl, Key, VerifySignResult};
-use super::subtle::CryptoKey;
+use super::subtle::{self, CryptoKey, EcKeyGenParams, EcdsaParams, KeyGenAlgorithm, SignAlgorithm};
 use super::subtle_idl_v1::{EcdsaSignatureMetadata, SecpR1Metadata, ShaMetadata};
-use super::subtle_idl_v1_enums::{
-    KeyAlgorithm, SecpR1KeyBitsize, ShaAlgorithm, ShaDigestBitsize, SigningAlgorithm,
-};
+use super::subtle_idl_v1_enums::{SecpR1KeyBitsize, ShaAlgorithm, ShaDigestBitsize};
+use super::subtle_idl_v2::{KeyAlgorithm, SigningAlgorithm};
+use super::util;
+
+/// Imported public keys kept at most, so that new keys sent by callers cannot grow the cache without bound
+const IMPORTED_PUBLIC_KEYS_MAX: usize = 64;
+
+thread_local! {
+    /// Public keys imported for verification, by curve and SPKI bytes
+    static IMPORTED_PUBLIC_KEYS: RefCell<HashMap<(String, Vec<u8>), CryptoKey>> =
+        RefCell::new(HashMap::new());
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyECC {
@@ -45,7 +56,7 @@ impl KeyECC {
         }
     }
 
-    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
         let hash_algo = ShaMetadata {
             algo_id: ShaAlgorithm::Sha2,
             length: ShaDigestBitsize::Sha256,
@@ -55,8 +66,7 @@ impl KeyECC {
         };
         match CryptoImpl::sign(
             &self.key.name(),
-            SigningAlgorithm::Ecdsa as u32,
-            &serde_json::to_string(&signature_metadata)?,
+            &SigningAlgorithm::try_from(&signature_metadata)?,
             data,
         ) {
             Ok(result) => Ok(result),
@@ -64,11 +74,7 @@ impl KeyECC {
         }
     }
 
-    pub fn verify(
-        &self,
-        data: &[u8],
-        signature: &[u8],
-    ) -> Result<VerifySignResult, Box<dyn Error>> {
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<VerifySignResult> {
         let hash_algo = ShaMetadata {
             algo_id: ShaAlgorithm::Sha2,
             length: ShaDigestBitsize::Sha256,
@@ -78,8 +84,7 @@ impl KeyECC {
         };
         match CryptoImpl::verify(
             &self.key.name(),
-            SigningAlgorithm::Ecdsa as u32,
-            &serde_json::to_string(&signature_metadata)?,
+            &SigningAlgorithm::try_from(&signature_metadata)?,
             data,
             signature,
         ) {
@@ -88,7 +93,7 @@ impl KeyECC {
         }
     }
 
-    pub fn get_public_key(&self) -> Result<PublicKey, Box<dyn Error>> {
+    pub fn get_public_key(&self) -> Result<PublicKey> {
         match CryptoImpl::get_public_key(&self.key.name()) {
             Ok(result) => Ok(PublicKey::new(&result)),
             Err(err) => Err(err),
@@ -96,34 +101,91 @@ impl KeyECC {
     }
 }
 
-pub fn get_key(name: &str) -> Result<KeyECC, Box<dyn Error>> {
+pub fn get_key(name: &str) -> Result<KeyECC> {
     match CryptoImpl::key_exists(name) {
         Ok(_) => Ok(KeyECC::new(name, &String::from("P-256"))),
         Err(err) => Err(err),
     }
 }
 
-pub fn generate_key(name: &str) -> Result<KeyECC, Box<dyn Error>> {
+/// Verify an ECDSA (SHA-256) signature against a SPKI-encoded public key on `named_curve`,
+/// e.g. `P-256` or `P-384`
+pub fn verify_with_public_key(
+    named_curve: &str,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<VerifySignResult> {
+    verify_ecdsa(named_curve, "SHA2-256", public_key, data, signature)
+}
+
+/// Verify an ECDSA signature hashed with `hash` against a SPKI-encoded public key on `named_curve`.
+///
+/// The key is imported once and reused by later verifications, or imported again if the host dropped it.
+pub(crate) fn verify_ecdsa(
+    named_curve: &str,
+    hash: &str,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<VerifySignResult> {
+    let algorithm = SignAlgorithm::Ecdsa(EcdsaParams {
+        hash: hash.to_string(),
+    });
+    let cache_key = (named_curve.to_string(), public_key.to_vec());
+    let cached = IMPORTED_PUBLIC_KEYS.with(|keys| keys.borrow().get(&cache_key).cloned());
+    if let Some(key) = cached {
+        if let Ok(result) = subtle::verify(&algorithm, &key, data, signature) {
+            return Ok(result);
+        }
+    }
+    let key = subtle::import_key(
+        "spki",
+        public_key,
+        &KeyGenAlgorithm::Ecc(EcKeyGenParams {
+            named_curve: named_curve.to_string(),
+        }),
+        false,
+        &["verify"],
+    )?;
+    let result = subtle::verify(&algorithm, &key, data, signature)?;
+    IMPORTED_PUBLIC_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        if keys.len() >= IMPORTED_PUBLIC_KEYS_MAX {
+            keys.clear();
+        }
+        keys.insert(cache_key, key);
+    });
+    Ok(result)
+}
+
+pub fn generate_key(name: &str) -> Result<KeyECC> {
     if name.is_empty() {
-        return Err("Invalid key name: key name cannot be empty".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: key name cannot be empty",
+        ));
     }
 
     match CryptoImpl::key_exists(name) {
         Ok(exists) => {
             if exists {
-                return Err(format!("Invalid key name: key name {name} already exists").into());
+                return Err(Error::new(
+                    Subsystem::Crypto,
+                    ErrorKind::AlreadyExists,
+                    format!("Invalid key name: key name {name} already exists"),
+                ));
             }
         }
         Err(e) => return Err(e),
     }
 
-    let metadata = serde_json::to_string(&SecpR1Metadata {
+    let metadata = SecpR1Metadata {
         length: SecpR1KeyBitsize::SecpR1256,
-    })?;
+    };
     let key: Vec<u8> = CryptoImpl::generate_key(
         name,
-        KeyAlgorithm::SecpR1 as u32,
-        &metadata,
+        &KeyAlgorithm::SecpR1((&metadata).into()),
         true,
         &["sign"],
     )?;
@@ -133,10 +195,62 @@ pub fn generate_key(name: &str) -> Result<KeyECC, Box<dyn Error>> {
         Err(e) => return Err(e),
     };
 
-    match serde_json::from_str::<CryptoKey>(&String::from_utf8(key)?) {
+    match util::from_json::<CryptoKey>(&util::from_utf8(key)?) {
         Ok(_) => (),
-        Err(e) => return Err(e.into()),
+        Err(e) => return Err(e),
     };
 
     Ok(KeyECC::new(name, "P-256"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    fn signed_message() -> (Vec<u8>, Vec<u8>) {
+        let key = subtle::generate_key(
+            &KeyGenAlgorithm::Ecc(EcKeyGenParams {
+                named_curve: "P-256".to_string(),
+            }),
+            true,
+            &["sign", "verify"],
+        )
+        .unwrap();
+        let spki = subtle::export_key("spki", &subtle::get_public_key(&key).unwrap()).unwrap();
+        let signature = subtle::sign(
+            &SignAlgorithm::Ecdsa(EcdsaParams::default()),
+            &key,
+            b"message",
+        )
+        .unwrap();
+        (spki, signature)
+    }
+
+    #[test]
+    fn public_keys_are_imported_once() {
+        install(MockHost::new());
+        let (spki, signature) = signed_message();
+        for _ in 0..3 {
+            assert!(
+                verify_with_public_key("P-256", &spki, b"message", &signature)
+                    .unwrap()
+                    .is_valid()
+            );
+        }
+        assert!(
+            !verify_with_public_key("P-256", &spki, b"other", &signature)
+                .unwrap()
+                .is_valid()
+        );
+        assert_eq!(IMPORTED_PUBLIC_KEYS.with(|keys| keys.borrow().len()), 1);
+
+        // A host that does not know the cached key gets it imported again
+        install(MockHost::new());
+        assert!(
+            verify_with_public_key("P-256", &spki, b"message", &signature)
+                .unwrap()
+                .is_valid()
+        );
+    }
+}
@@ -1,7 +1,10 @@
 mod keys;
 mod sdk_wrapper;
+#[allow(dead_code)]
 mod subtle_idl_v1;
-mod subtle_idl_v1_enums;
+#[allow(dead_code)]
+pub(crate) mod subtle_idl_v1_enums;
+pub mod subtle_idl_v2;
 mod util;
 
 pub mod aes;
@@ -9,6 +12,7 @@ pub mod ecc;
 pub mod random;
 pub mod rsa;
 pub mod sha;
+pub mod simple;
 pub mod subtle;
 
 pub use keys::{PrivateKey, PublicKey};
@@ -1,8 +1,13 @@
+use crate::error::{Error, Result, Subsystem};
 use crate::sdk;
 
-pub fn get_random_bytes(size: i32) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+pub fn get_random_bytes(size: i32) -> Result<Vec<u8>> {
     match sdk::get_random_bytes(size) {
         Ok(result) => Ok(result),
-        Err(err) => Err(err.into()),
+        Err(err) => Err(Error::host(
+            Subsystem::Crypto,
+            "Failed to get random bytes",
+            err,
+        )),
     }
 }
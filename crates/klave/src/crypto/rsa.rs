@@ -1,5 +1,5 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 use std::fmt::Display;
 
 use super::sdk_wrapper::CryptoImpl;
@@ -7,9 +7,8 @@ use super::sdk_wrapper::Key;
 use super::sdk_wrapper::VerifySignResult;
 use super::subtle::CryptoKey;
 use super::subtle_idl_v1::{RsaMetadata, RsaOaepEncryptionMetadata, RsaPssSignatureMetadata};
-use super::subtle_idl_v1_enums::{
-    EncryptionAlgorithm, KeyAlgorithm, RsaKeyBitsize, SigningAlgorithm,
-};
+use super::subtle_idl_v1_enums::RsaKeyBitsize;
+use super::subtle_idl_v2::{EncryptionAlgorithm, KeyAlgorithm, RsaParams, SigningAlgorithm};
 use super::util;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,12 +46,11 @@ impl KeyRSA {
         }
     }
 
-    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
         let rsa_oaep_encryption_metadata = RsaOaepEncryptionMetadata { label: vec![] };
         match CryptoImpl::encrypt(
             &self.key.name(),
-            EncryptionAlgorithm::RsaOaep as u32,
-            &serde_json::to_string(&rsa_oaep_encryption_metadata)?,
+            &EncryptionAlgorithm::RsaOaep((&rsa_oaep_encryption_metadata).into()),
             data,
         ) {
             Ok(result) => Ok(result),
@@ -60,12 +58,11 @@ impl KeyRSA {
         }
     }
 
-    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
         let rsa_oaep_encryption_metadata = RsaOaepEncryptionMetadata { label: vec![] };
         match CryptoImpl::decrypt(
             &self.key.name(),
-            EncryptionAlgorithm::RsaOaep as u32,
-            &serde_json::to_string(&rsa_oaep_encryption_metadata)?,
+            &EncryptionAlgorithm::RsaOaep((&rsa_oaep_encryption_metadata).into()),
             data,
         ) {
             Ok(result) => Ok(result),
@@ -73,13 +70,12 @@ impl KeyRSA {
         }
     }
 
-    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
         let salt_length = 32;
         let signature_metadata = RsaPssSignatureMetadata { salt_length };
         match CryptoImpl::sign(
             &self.key.name(),
-            SigningAlgorithm::RsaPss as u32,
-            &serde_json::to_string(&signature_metadata)?,
+            &SigningAlgorithm::from(&signature_metadata),
             data,
         ) {
             Ok(result) => Ok(result),
@@ -87,17 +83,12 @@ impl KeyRSA {
         }
     }
 
-    pub fn verify(
-        &self,
-        data: &[u8],
-        signature: &[u8],
-    ) -> Result<VerifySignResult, Box<dyn Error>> {
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<VerifySignResult> {
         let salt_length = 32;
         let signature_metadata = RsaPssSignatureMetadata { salt_length };
         match CryptoImpl::verify(
             &self.key.name(),
-            SigningAlgorithm::RsaPss as u32,
-            &serde_json::to_string(&signature_metadata)?,
+            &SigningAlgorithm::from(&signature_metadata),
             data,
             signature,
         ) {
@@ -106,7 +97,7 @@ impl KeyRSA {
         }
     }
 
-    pub fn get_public_key(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn get_public_key(&self) -> Result<Vec<u8>> {
         match CryptoImpl::get_public_key(&self.key.name()) {
             Ok(result) => Ok(result),
             Err(err) => Err(err),
@@ -114,22 +105,29 @@ impl KeyRSA {
     }
 }
 
-pub fn get_key(name: &str) -> Result<KeyRSA, Box<dyn Error>> {
+pub fn get_key(name: &str) -> Result<KeyRSA> {
     match CryptoImpl::key_exists(name) {
         Ok(_) => Ok(KeyRSA::new(name, 2048)),
         Err(err) => Err(err),
     }
 }
 
-pub fn generate_key(name: &str) -> Result<KeyRSA, Box<dyn Error>> {
+pub fn generate_key(name: &str) -> Result<KeyRSA> {
     if name.is_empty() {
-        return Err("Invalid key name: key name cannot be empty".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: key name cannot be empty",
+        ));
     }
 
     match CryptoImpl::key_exists(name) {
         Ok(exists) => {
             if exists {
-                return Err(format!("Invalid key name: key name {name} already exists").into());
+                return Err(Error::new(
+                    Subsystem::Crypto,
+                    ErrorKind::AlreadyExists,
+                    format!("Invalid key name: key name {name} already exists"),
+                ));
             }
         }
         Err(e) => return Err(e),
@@ -144,8 +142,7 @@ pub fn generate_key(name: &str) -> Result<KeyRSA, Box<dyn Error>> {
     };
     let key = CryptoImpl::generate_key(
         name,
-        KeyAlgorithm::Rsa as u32,
-        &serde_json::to_string(&metadata)?,
+        &KeyAlgorithm::Rsa(RsaParams::try_from(&metadata)?),
         true,
         &["sign", "decrypt"],
     )?;
@@ -155,9 +152,9 @@ pub fn generate_key(name: &str) -> Result<KeyRSA, Box<dyn Error>> {
         Err(e) => return Err(e),
     };
 
-    match serde_json::from_str::<CryptoKey>(&String::from_utf8(key)?) {
+    match util::from_json::<CryptoKey>(&util::from_utf8(key)?) {
         Ok(_) => (),
-        Err(e) => return Err(e.into()),
+        Err(e) => return Err(e),
     };
 
     Ok(KeyRSA::new(name, 2048))
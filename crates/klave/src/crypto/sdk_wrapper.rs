@@ -1,8 +1,13 @@
-use std::error::Error;
 use std::fmt::Display;
 
 use super::random;
+use super::subtle_idl_v2::{
+    DerivationAlgorithm, DerivedKeyAlgorithm, EncryptionAlgorithm, KeyAlgorithm, KeyFormat,
+    KeyUsage, ShaAlgorithm, SigningAlgorithm, WrappingAlgorithm,
+};
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
 use crate::sdk;
+use crate::util::to_hex;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,11 +28,7 @@ impl Key {
                 };
             };
             Key {
-                name: rand.iter().fold(String::new(), |mut acc, b| {
-                    use std::fmt::Write;
-                    write!(acc, "{b:02x}").unwrap();
-                    acc
-                }),
+                name: to_hex(&rand),
             }
         }
     }
@@ -58,270 +59,309 @@ impl Display for VerifySignResult {
 pub struct CryptoImpl;
 
 impl CryptoImpl {
-    fn usage(input: &str) -> u8 {
-        match input {
-            "encrypt" => 0,
-            "decrypt" => 1,
-            "sign" => 2,
-            "verify" => 3,
-            "derive_key" => 4,
-            "deriveKey" => 4,
-            "derive_bits" => 5,
-            "deriveBits" => 5,
-            "wrap_key" => 6,
-            "wrapKey" => 6,
-            "unwrap_key" => 7,
-            "unwrapKey" => 7,
-            _ => u8::MAX,
-        }
-    }
-
-    fn process_usages(usages: &[&str]) -> Vec<u8> {
-        let mut local_usages = Vec::with_capacity(usages.len());
-        for usage in usages {
-            local_usages.push(CryptoImpl::usage(usage));
-        }
-        local_usages
+    fn process_usages(usages: &[&str]) -> Result<Vec<KeyUsage>> {
+        usages.iter().map(|usage| usage.parse()).collect()
     }
 
-    pub fn key_exists(key_name: &str) -> Result<bool, Box<dyn Error>> {
+    pub fn key_exists(key_name: &str) -> Result<bool> {
         match sdk::key_exists(key_name) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to check key", err)),
         }
     }
 
     pub fn generate_key(
         key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &KeyAlgorithm,
         extractable: bool,
         usages: &[&str],
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::generate_key(
+    ) -> Result<Vec<u8>> {
+        match sdk::generate_key_v2(
             key_name,
-            algorithm as i32,
-            algo_metadata,
-            extractable as i32,
-            &CryptoImpl::process_usages(usages),
+            algorithm,
+            extractable,
+            &CryptoImpl::process_usages(usages)?,
         ) {
             Ok(result) => Ok(result.into_bytes()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to generate key",
+                err,
+            )),
         }
     }
 
     pub fn encrypt(
         key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &EncryptionAlgorithm,
         clear_text: &[u8],
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::encrypt(key_name, algorithm as i32, algo_metadata, clear_text) {
+    ) -> Result<Vec<u8>> {
+        match sdk::encrypt_v2(key_name, algorithm, clear_text) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to encrypt", err)),
         }
     }
 
     pub fn decrypt(
         key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &EncryptionAlgorithm,
         cipher_text: &[u8],
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::decrypt(key_name, algorithm as i32, algo_metadata, cipher_text) {
+    ) -> Result<Vec<u8>> {
+        match sdk::decrypt_v2(key_name, algorithm, cipher_text) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to decrypt", err)),
         }
     }
 
-    pub fn sign(
-        key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
-        data: &[u8],
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::sign(key_name, algorithm as i32, algo_metadata, data) {
+    pub fn sign(key_name: &str, algorithm: &SigningAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
+        match sdk::sign_v2(key_name, algorithm, data) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to sign", err)),
         }
     }
 
     pub fn verify(
         key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &SigningAlgorithm,
         data: &[u8],
         signature: &[u8],
-    ) -> Result<VerifySignResult, Box<dyn Error>> {
-        match sdk::verify(key_name, algorithm as i32, algo_metadata, data, signature) {
+    ) -> Result<VerifySignResult> {
+        match sdk::verify_v2(key_name, algorithm, data, signature) {
             Ok(result) => Ok(VerifySignResult { is_valid: result }),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to verify signature",
+                err,
+            )),
         }
     }
 
-    pub fn digest(algorithm: u32, hash_info: &str, text: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::digest(algorithm as i32, hash_info, text) {
+    pub fn digest(algorithm: ShaAlgorithm, text: &[u8]) -> Result<Vec<u8>> {
+        match sdk::digest_v2(algorithm, text) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to compute digest",
+                err,
+            )),
         }
     }
 
     pub fn import_key(
-        format: u32,
+        format: KeyFormat,
         key_data: &[u8],
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &KeyAlgorithm,
         extractable: bool,
         usages: &[&str],
         key_name: &str,
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::import_key(
+    ) -> Result<Vec<u8>> {
+        match sdk::import_key_v2(
             key_name,
-            format as i32,
+            format,
             key_data,
-            algorithm as i32,
-            algo_metadata,
-            extractable as i32,
-            &CryptoImpl::process_usages(usages),
+            algorithm,
+            extractable,
+            &CryptoImpl::process_usages(usages)?,
         ) {
             Ok(result) => Ok(result.into_bytes()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to import key", err)),
         }
     }
 
-    pub fn export_key(key_name: &str, format: u32) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::export_key(key_name, format as i32) {
+    pub fn export_key(key_name: &str, format: KeyFormat) -> Result<Vec<u8>> {
+        match sdk::export_key_v2(key_name, format) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to export key", err)),
         }
     }
 
     pub fn unwrap_key(
         decryption_key_name: &str,
-        unwrap_algo_id: u32,
-        unwrap_metadata: &str,
-        format: u32,
+        unwrap_algorithm: &WrappingAlgorithm,
+        format: KeyFormat,
         wrapped_key: &[u8],
-        key_gen_algorithm: u32,
-        key_gen_algo_metadata: &str,
+        key_gen_algorithm: &KeyAlgorithm,
         extractable: bool,
         usages: &[&str],
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::unwrap_key(
+    ) -> Result<Vec<u8>> {
+        match sdk::unwrap_key_v2(
             decryption_key_name,
-            unwrap_algo_id as i32,
-            unwrap_metadata,
+            unwrap_algorithm,
             "",
-            format as i32,
+            format,
             wrapped_key,
-            key_gen_algorithm as i32,
-            key_gen_algo_metadata,
-            extractable as i32,
-            &CryptoImpl::process_usages(usages),
+            key_gen_algorithm,
+            extractable,
+            &CryptoImpl::process_usages(usages)?,
         ) {
             Ok(result) => Ok(result.into_bytes()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to unwrap key", err)),
         }
     }
 
     pub fn wrap_key(
         encryption_key_name: &str,
-        algorithm: u32,
-        algo_metadata: &str,
+        algorithm: &WrappingAlgorithm,
         key_name: &str,
-        format: u32,
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::wrap_key(
-            key_name,
-            format as i32,
-            encryption_key_name,
-            algorithm as i32,
-            algo_metadata,
-        ) {
+        format: KeyFormat,
+    ) -> Result<Vec<u8>> {
+        match sdk::wrap_key_v2(key_name, format, encryption_key_name, algorithm) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to wrap key", err)),
         }
     }
 
-    pub fn get_public_key(key_name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    pub fn get_public_key(key_name: &str) -> Result<Vec<u8>> {
         match sdk::get_public_key(key_name) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to get public key",
+                err,
+            )),
         }
     }
 
-    pub fn get_public_key_as_crypto_key(key_name: &str) -> Result<String, Box<dyn Error>> {
+    pub fn get_public_key_as_crypto_key(key_name: &str) -> Result<String> {
         match sdk::get_public_key_as_cryptokey(key_name) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to get public key",
+                err,
+            )),
         }
     }
 
     pub fn derive_key(
-        derivation_algorithm: u32,
-        derivation_metadata: &str,
-        derived_key_algorithm: u32,
-        derived_key_metadata: &str,
+        derivation_algorithm: &DerivationAlgorithm,
+        derived_key_algorithm: &DerivedKeyAlgorithm,
         extractable: bool,
         usages: &[&str],
         key_name: &str,
-    ) -> Result<String, Box<dyn Error>> {
-        match sdk::derive_key(
+    ) -> Result<String> {
+        match sdk::derive_key_v2(
             key_name,
-            derivation_algorithm as i32,
-            derivation_metadata,
-            derived_key_algorithm as i32,
-            derived_key_metadata,
-            extractable as i32,
-            &CryptoImpl::process_usages(usages),
+            derivation_algorithm,
+            derived_key_algorithm,
+            extractable,
+            &CryptoImpl::process_usages(usages)?,
         ) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to derive key", err)),
         }
     }
 
     pub fn derive_bits(
         key_name: &str,
-        derivation_algorithm: u32,
-        derivation_metadata: &str,
+        derivation_algorithm: &DerivationAlgorithm,
         length: u32,
-    ) -> Result<Vec<u8>, Box<dyn Error>> {
-        match sdk::derive_bits(
-            key_name,
-            derivation_algorithm as i32,
-            derivation_metadata,
-            length as i32,
-        ) {
+    ) -> Result<Vec<u8>> {
+        match sdk::derive_bits_v2(key_name, derivation_algorithm, length) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to derive bits", err)),
+        }
+    }
+
+    pub fn generate_simple_encryption_key(key_name: &str) -> Result<()> {
+        match sdk::generate_simple_encryption_key(key_name) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to generate simple encryption key",
+                err,
+            )),
+        }
+    }
+
+    pub fn generate_simple_signing_key(key_name: &str) -> Result<()> {
+        match sdk::generate_simple_signing_key(key_name) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to generate simple signing key",
+                err,
+            )),
+        }
+    }
+
+    pub fn simple_encrypt(key_name: &str, clear_text: &[u8]) -> Result<Vec<u8>> {
+        match sdk::simple_encrypt(key_name, clear_text) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to encrypt", err)),
+        }
+    }
+
+    pub fn simple_decrypt(key_name: &str, cipher_text: &[u8]) -> Result<Vec<u8>> {
+        match sdk::simple_decrypt(key_name, cipher_text) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to decrypt", err)),
+        }
+    }
+
+    pub fn simple_sign(key_name: &str, data: &[u8]) -> Result<Vec<u8>> {
+        match sdk::simple_sign(key_name, data) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to sign", err)),
+        }
+    }
+
+    pub fn simple_verify(
+        key_name: &str,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<VerifySignResult> {
+        match sdk::simple_verify(key_name, data, signature) {
+            Ok(result) => Ok(VerifySignResult { is_valid: result }),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to verify signature",
+                err,
+            )),
+        }
+    }
+
+    pub fn simple_digest(data: &[u8]) -> Result<Vec<u8>> {
+        match sdk::simple_digest(data) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(
+                Subsystem::Crypto,
+                "Failed to compute digest",
+                err,
+            )),
         }
     }
 
-    pub fn save_key(key_name: &str) -> Result<(), Box<dyn Error>> {
+    pub fn save_key(key_name: &str) -> Result<()> {
         match sdk::save_key(key_name) {
             Ok(_) => Ok(()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to save key", err)),
         }
     }
 
-    pub fn persist_key(key_persist_data: &[u8]) -> Result<(), Box<dyn Error>> {
-        match sdk::persist_key(&String::from_utf8(key_persist_data.to_vec())?) {
+    pub fn persist_key(key_persist_data: &[u8]) -> Result<()> {
+        let key_persist_params = String::from_utf8(key_persist_data.to_vec()).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Key persist parameters are not valid UTF-8",
+        )?;
+        match sdk::persist_key(&key_persist_params) {
             Ok(_) => Ok(()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to persist key", err)),
         }
     }
 
-    pub fn load_key(key_name: &str) -> Result<String, Box<dyn Error>> {
+    pub fn load_key(key_name: &str) -> Result<String> {
         match sdk::load_key(key_name) {
             Ok(result) => Ok(result),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to load key", err)),
         }
     }
 
-    pub fn delete_key(key_name: &str) -> Result<(), Box<dyn Error>> {
+    pub fn delete_key(key_name: &str) -> Result<()> {
         match sdk::delete_key(key_name) {
             Ok(_) => Ok(()),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(Error::host(Subsystem::Crypto, "Failed to delete key", err)),
         }
     }
 }
@@ -1,9 +1,21 @@
-use super::subtle;
-use std::error::Error;
+use super::subtle::{self, HmacKeyGenParams, KeyGenAlgorithm, SignAlgorithm};
+use crate::error::Result;
 
-pub fn digest(algorithm: &str, text: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
-    match subtle::digest(algorithm, text) {
-        Ok(result) => Ok(result),
-        Err(err) => Err(err),
-    }
+pub fn digest(algorithm: &str, text: &[u8]) -> Result<Vec<u8>> {
+    subtle::digest(algorithm, text)
+}
+
+/// HMAC-SHA256 of `data` under the raw `key`
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let key = subtle::import_key(
+        "raw",
+        key,
+        &KeyGenAlgorithm::Hmac(HmacKeyGenParams {
+            hash: "SHA2-256".to_string(),
+            length: key.len() as u32 * 8,
+        }),
+        false,
+        &["sign"],
+    )?;
+    subtle::sign(&SignAlgorithm::Hmac(), &key, data)
 }
@@ -0,0 +1,238 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt::Display;
+
+use super::sdk_wrapper::{CryptoImpl, Key, VerifySignResult};
+
+/// Encryption key generated by the host, with the algorithm the host recommends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionKey {
+    key: Key,
+}
+
+impl Display for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "EncryptionKey: name: {}", self.key.name())
+    }
+}
+
+impl EncryptionKey {
+    /// Generates a new key named `name`, failing if a key with that name exists
+    pub fn generate(name: &str) -> Result<EncryptionKey> {
+        check_new_key(name)?;
+        CryptoImpl::generate_simple_encryption_key(name)?;
+        Ok(EncryptionKey {
+            key: Key::new(name),
+        })
+    }
+
+    /// Handle on the existing key `name`
+    pub fn get(name: &str) -> Result<EncryptionKey> {
+        check_existing_key(name)?;
+        Ok(EncryptionKey {
+            key: Key::new(name),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.key.name()
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        CryptoImpl::simple_encrypt(&self.key.name(), data)
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        CryptoImpl::simple_decrypt(&self.key.name(), data)
+    }
+
+    /// Serializes `value` to JSON and encrypts it
+    pub fn seal<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(value).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Failed to serialize value to seal",
+        )?;
+        self.encrypt(&json)
+    }
+
+    /// Decrypts a box made by [`EncryptionKey::seal`] and deserializes its content
+    pub fn open<T: DeserializeOwned>(&self, sealed: &[u8]) -> Result<T> {
+        let json = self.decrypt(sealed)?;
+        serde_json::from_slice(&json).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Failed to deserialize sealed value",
+        )
+    }
+}
+
+/// Signing key generated by the host, with the algorithm the host recommends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningKey {
+    key: Key,
+}
+
+impl Display for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SigningKey: name: {}", self.key.name())
+    }
+}
+
+impl SigningKey {
+    /// Generates a new key named `name`, failing if a key with that name exists
+    pub fn generate(name: &str) -> Result<SigningKey> {
+        check_new_key(name)?;
+        CryptoImpl::generate_simple_signing_key(name)?;
+        Ok(SigningKey {
+            key: Key::new(name),
+        })
+    }
+
+    /// Handle on the existing key `name`
+    pub fn get(name: &str) -> Result<SigningKey> {
+        check_existing_key(name)?;
+        Ok(SigningKey {
+            key: Key::new(name),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.key.name()
+    }
+
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        CryptoImpl::simple_sign(&self.key.name(), data)
+    }
+
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<VerifySignResult> {
+        CryptoImpl::simple_verify(&self.key.name(), data, signature)
+    }
+
+    /// Serializes `value` to JSON and signs it
+    pub fn seal<T: Serialize>(&self, value: &T) -> Result<SignedMessage> {
+        let data = serde_json::to_vec(value).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Failed to serialize value to sign",
+        )?;
+        let signature = self.sign(&data)?;
+        Ok(SignedMessage { data, signature })
+    }
+
+    /// Checks the signature of a message made by [`SigningKey::seal`] and deserializes its content.
+    ///
+    /// Fails with [`ErrorKind::PermissionDenied`] if the signature does not match, like the other
+    /// signature checks of the SDK.
+    pub fn open<T: DeserializeOwned>(&self, message: &SignedMessage) -> Result<T> {
+        if !self.verify(&message.data, &message.signature)?.is_valid() {
+            return Err(Error::new(
+                Subsystem::Crypto,
+                ErrorKind::PermissionDenied,
+                "Invalid signature: message was not signed by this key",
+            ));
+        }
+        serde_json::from_slice(&message.data).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Failed to deserialize signed value",
+        )
+    }
+}
+
+/// JSON value along with its signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedMessage {
+    pub data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Digest of `data` with the hash the host recommends
+pub fn digest(data: &[u8]) -> Result<Vec<u8>> {
+    CryptoImpl::simple_digest(data)
+}
+
+fn check_new_key(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: key name cannot be empty",
+        ));
+    }
+    if CryptoImpl::key_exists(name)? {
+        return Err(Error::new(
+            Subsystem::Crypto,
+            ErrorKind::AlreadyExists,
+            format!("Invalid key name: key name {name} already exists"),
+        ));
+    }
+    Ok(())
+}
+
+fn check_existing_key(name: &str) -> Result<()> {
+    if !CryptoImpl::key_exists(name)? {
+        return Err(Error::not_found(
+            Subsystem::Crypto,
+            format!("Key {name} does not exist"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+    use serde_json::json;
+
+    #[test]
+    fn encryption_keys_seal_and_open() {
+        install(MockHost::new());
+        let key = EncryptionKey::generate("box").unwrap();
+        let sealed = key.seal(&json!({ "secret": 42 })).unwrap();
+        assert_eq!(
+            EncryptionKey::get("box")
+                .unwrap()
+                .open::<serde_json::Value>(&sealed)
+                .unwrap(),
+            json!({ "secret": 42 })
+        );
+        // A fresh IV for every message
+        assert_ne!(key.encrypt(b"same").unwrap(), key.encrypt(b"same").unwrap());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.open::<serde_json::Value>(&tampered).is_err());
+        assert_eq!(
+            EncryptionKey::generate("box").unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+    }
+
+    #[test]
+    fn signing_keys_seal_and_open() {
+        install(MockHost::new());
+        let key = SigningKey::generate("signer").unwrap();
+        let mut message = key.seal(&json!(["a", "b"])).unwrap();
+        assert_eq!(key.open::<Vec<String>>(&message).unwrap(), ["a", "b"]);
+
+        message.data = serde_json::to_vec(&json!(["a", "c"])).unwrap();
+        assert_eq!(
+            key.open::<Vec<String>>(&message).unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            SigningKey::get("missing").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn digest_is_sha256() {
+        install(MockHost::new());
+        assert_eq!(
+            digest(b"abc").unwrap(),
+            crate::crypto::sha::digest("SHA-256", b"abc").unwrap()
+        );
+    }
+}
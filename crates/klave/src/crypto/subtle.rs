@@ -1,3 +1,4 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
 use serde::{Deserialize, Serialize};
 /**
  * Environment definitions for compiling Klave Trustless Applications.
@@ -12,10 +13,7 @@ use super::subtle_idl_v1::{
     AesGcmEncryptionMetadata, AesKwWrappingMetadata, EcdsaSignatureMetadata,
     RsaOaepEncryptionMetadata, RsaPssSignatureMetadata,
 };
-use super::subtle_idl_v1_enums::{
-    DerivationAlgorithm, DerivedKeyUsageAlgorithm, EncryptionAlgorithm, HashAlgorithm,
-    KeyAlgorithm, SigningAlgorithm, WrappingAlgorithm,
-};
+use super::subtle_idl_v2 as v2;
 use super::util;
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
@@ -222,305 +220,200 @@ pub enum DerivedKeyAlgorithm {
     Hmac(HmacKeyGenParams),
 }
 
-pub fn generate_key(
-    algorithm: &KeyGenAlgorithm,
-    extractable: bool,
-    usages: &[&str],
-) -> Result<CryptoKey, Box<dyn std::error::Error>> {
+fn get_key_algorithm(algorithm: &KeyGenAlgorithm) -> Result<v2::KeyAlgorithm> {
     match algorithm {
         KeyGenAlgorithm::Rsa(params) => {
             let rsa_metadata = util::get_rsa_metadata(params)?;
-
-            let key = CryptoImpl::generate_key(
-                "",
-                KeyAlgorithm::Rsa as u32,
-                &serde_json::to_string(&rsa_metadata)?,
-                extractable,
-                usages,
-            )?;
-
-            let crypto_key_json = String::from_utf8(key)?;
-            let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
-            Ok(crypto_key)
-        }
-        KeyGenAlgorithm::Ecc(params) => {
-            let named_curve = params.named_curve.as_str();
-            match named_curve {
-                "P-256" | "P-384" | "P-521" => {
-                    let secpr1_metadata = util::get_secpr1_metadata(params)?;
-                    let str_secpr1_metadata = match serde_json::to_string(&secpr1_metadata) {
-                        Ok(str_secpr1_metadata) => str_secpr1_metadata,
-                        Err(e) => {
-                            return Err(e.into());
-                        }
-                    };
-                    let key = CryptoImpl::generate_key(
-                        "",
-                        KeyAlgorithm::SecpR1 as u32,
-                        &str_secpr1_metadata,
-                        extractable,
-                        usages,
-                    )?;
-                    let crypto_key_json = String::from_utf8(key)?;
-                    let crypto_key: CryptoKey = match serde_json::from_str(&crypto_key_json) {
-                        Ok(crypto_key) => crypto_key,
-                        Err(e) => return Err(e.into()),
-                    };
-                    Ok(crypto_key)
-                }
-                "secp256k1" | "SECP256K1" => {
-                    let secpk1_metadata = match util::get_secpk1_metadata(params) {
-                        Ok(secpk1_metadata) => secpk1_metadata,
-                        Err(e) => {
-                            return Err(e);
-                        }
-                    };
-                    let str_secpk1_metadata = match serde_json::to_string(&secpk1_metadata) {
-                        Ok(str_secpk1_metadata) => str_secpk1_metadata,
-                        Err(e) => {
-                            return Err(e.into());
-                        }
-                    };
-                    let key = CryptoImpl::generate_key(
-                        "",
-                        KeyAlgorithm::SecpK1 as u32,
-                        &str_secpk1_metadata,
-                        extractable,
-                        usages,
-                    )?;
-                    let crypto_key_json = String::from_utf8(key)?;
-                    let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
-                    Ok(crypto_key)
-                }
-                _ => Err("Invalid curve name".into()),
+            Ok(v2::KeyAlgorithm::Rsa(v2::RsaParams::try_from(
+                &rsa_metadata,
+            )?))
+        }
+        KeyGenAlgorithm::Ecc(params) => match params.named_curve.as_str() {
+            "P-256" | "P-384" | "P-521" => {
+                let secpr1_metadata = util::get_secpr1_metadata(params)?;
+                Ok(v2::KeyAlgorithm::SecpR1((&secpr1_metadata).into()))
             }
-        }
+            "secp256k1" | "SECP256K1" => {
+                let secpk1_metadata = util::get_secpk1_metadata(params)?;
+                Ok((&secpk1_metadata).into())
+            }
+            _ => Err(Error::invalid_input(
+                Subsystem::Crypto,
+                "Invalid curve name",
+            )),
+        },
         KeyGenAlgorithm::Aes(params) => {
-            let aes_metadata = match util::get_aes_metadata(params) {
-                Ok(aes_metadata) => aes_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            let str_aes_metadata = match serde_json::to_string(&aes_metadata) {
-                Ok(str_aes_metadata) => str_aes_metadata,
-                Err(e) => {
-                    return Err(e.into());
-                }
-            };
-            let key = CryptoImpl::generate_key(
-                "",
-                KeyAlgorithm::Aes as u32,
-                &str_aes_metadata,
-                extractable,
-                usages,
-            )?;
-            let crypto_key_json = String::from_utf8(key)?;
-            let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
-            Ok(crypto_key)
+            let aes_metadata = util::get_aes_metadata(params)?;
+            Ok(v2::KeyAlgorithm::Aes((&aes_metadata).into()))
         }
         KeyGenAlgorithm::Hmac(params) => {
-            let hmac_metadata = match util::get_hmac_metadata(params) {
-                Ok(hmac_metadata) => hmac_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            let str_hmac_metadata = match serde_json::to_string(&hmac_metadata) {
-                Ok(str_hmac_metadata) => str_hmac_metadata,
-                Err(e) => {
-                    return Err(e.into());
-                }
-            };
-            let key = CryptoImpl::generate_key(
-                "",
-                KeyAlgorithm::Hmac as u32,
-                &str_hmac_metadata,
-                extractable,
-                usages,
-            )?;
-            let crypto_key_json = String::from_utf8(key)?;
-            let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
-            Ok(crypto_key)
+            let hmac_metadata = util::get_hmac_metadata(params)?;
+            Ok(v2::KeyAlgorithm::Hmac(v2::HmacParams::try_from(
+                &hmac_metadata,
+            )?))
         }
     }
 }
 
-pub fn encrypt(
-    algorithm: &EncryptAlgorithm,
-    key: &CryptoKey,
-    clear_text: &[u8],
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    if clear_text.is_empty() {
-        return Err("Invalid clear text".into());
-    }
-    let key_name = &key.id;
-    match algorithm {
-        EncryptAlgorithm::RsaOaep(params) => {
-            let metadata = RsaOaepEncryptionMetadata {
-                label: params.label.clone(),
-            };
-            let result = CryptoImpl::encrypt(
-                key_name,
-                EncryptionAlgorithm::RsaOaep as u32,
-                &serde_json::to_string(&metadata)?,
-                clear_text,
-            )?;
-            Ok(result)
-        }
-        EncryptAlgorithm::AesGcm(params) => {
-            let tag_length = util::get_aes_tag_length(&params.tag_length)?;
-            let metadata = AesGcmEncryptionMetadata {
-                iv: params.iv.clone(),
-                additional_data: params.additional_data.clone(),
-                tag_length,
-            };
-            let result = CryptoImpl::encrypt(
-                key_name,
-                EncryptionAlgorithm::AesGcm as u32,
-                &serde_json::to_string(&metadata)?,
-                clear_text,
-            )?;
-            Ok(result)
-        }
-    }
+fn get_aes_gcm_params(params: &AesGcmParams) -> Result<v2::AesGcmParams> {
+    let tag_length = util::get_aes_tag_length(&params.tag_length)?;
+    let metadata = AesGcmEncryptionMetadata {
+        iv: params.iv.clone(),
+        additional_data: params.additional_data.clone(),
+        tag_length,
+    };
+    Ok((&metadata).into())
 }
 
-pub fn decrypt(
-    algorithm: &EncryptAlgorithm,
-    key: &CryptoKey,
-    cipher_text: &[u8],
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    if cipher_text.is_empty() {
-        return Err("Invalid cipher_text text".into());
-    }
-    let key_name = &key.id;
+fn get_rsa_oaep_params(params: &RsaOaepParams) -> v2::RsaOaepParams {
+    let metadata = RsaOaepEncryptionMetadata {
+        label: params.label.clone(),
+    };
+    (&metadata).into()
+}
+
+fn get_encryption_algorithm(algorithm: &EncryptAlgorithm) -> Result<v2::EncryptionAlgorithm> {
     match algorithm {
-        EncryptAlgorithm::RsaOaep(params) => {
-            let metadata = RsaOaepEncryptionMetadata {
-                label: params.label.clone(),
-            };
-            let result = CryptoImpl::decrypt(
-                key_name,
-                EncryptionAlgorithm::RsaOaep as u32,
-                &serde_json::to_string(&metadata)?,
-                cipher_text,
-            )?;
-            Ok(result)
-        }
+        EncryptAlgorithm::RsaOaep(params) => Ok(v2::EncryptionAlgorithm::RsaOaep(
+            get_rsa_oaep_params(params),
+        )),
         EncryptAlgorithm::AesGcm(params) => {
-            let tag_length = util::get_aes_tag_length(&params.tag_length)?;
-            let metadata = AesGcmEncryptionMetadata {
-                iv: params.iv.clone(),
-                additional_data: params.additional_data.clone(),
-                tag_length,
-            };
-            let result = CryptoImpl::decrypt(
-                key_name,
-                EncryptionAlgorithm::AesGcm as u32,
-                &serde_json::to_string(&metadata)?,
-                cipher_text,
-            )?;
-            Ok(result)
+            Ok(v2::EncryptionAlgorithm::AesGcm(get_aes_gcm_params(params)?))
         }
     }
 }
 
-pub fn sign(
-    algorithm: &SignAlgorithm,
-    key: &CryptoKey,
-    data: &[u8],
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    if data.is_empty() {
-        return Err("Invalid data".into());
-    }
-    let key_name = &key.id;
+fn get_signing_algorithm(algorithm: &SignAlgorithm) -> Result<v2::SigningAlgorithm> {
     match algorithm {
         SignAlgorithm::RsaPss(params) => {
             let metadata = RsaPssSignatureMetadata {
                 salt_length: params.salt_length as u64,
             };
-            let result = CryptoImpl::sign(
-                key_name,
-                SigningAlgorithm::RsaPss as u32,
-                &serde_json::to_string(&metadata)?,
-                data,
-            )?;
-            Ok(result)
+            Ok((&metadata).into())
         }
         SignAlgorithm::Ecdsa(params) => {
             let sha_metadata = util::get_sha_metadata(&params.hash)?;
             let metadata = EcdsaSignatureMetadata { sha_metadata };
-            let result = CryptoImpl::sign(
-                key_name,
-                SigningAlgorithm::Ecdsa as u32,
-                &serde_json::to_string(&metadata)?,
-                data,
-            )?;
-            Ok(result)
+            v2::SigningAlgorithm::try_from(&metadata)
+        }
+        SignAlgorithm::Hmac() => Ok(v2::SigningAlgorithm::Hmac),
+    }
+}
+
+fn get_wrapping_algorithm(algorithm: &KeyWrapAlgorithm) -> Result<v2::WrappingAlgorithm> {
+    match algorithm {
+        KeyWrapAlgorithm::RsaOaep(params) => {
+            Ok(v2::WrappingAlgorithm::RsaOaep(get_rsa_oaep_params(params)))
         }
-        SignAlgorithm::Hmac() => {
-            let result = CryptoImpl::sign(key_name, SigningAlgorithm::Hmac as u32, "", data)?;
-            Ok(result)
+        KeyWrapAlgorithm::AesGcm(params) => {
+            Ok(v2::WrappingAlgorithm::AesGcm(get_aes_gcm_params(params)?))
+        }
+        KeyWrapAlgorithm::AesKw => {
+            let metadata = AesKwWrappingMetadata { with_padding: true };
+            Ok((&metadata).into())
+        }
+    }
+}
+
+fn get_derivation_algorithm(algorithm: &KeyDerivationAlgorithm) -> Result<v2::DerivationAlgorithm> {
+    match algorithm {
+        KeyDerivationAlgorithm::Ecdh(params) => {
+            let metadata = util::get_ecdh_metadata(params)?;
+            Ok((&metadata).into())
+        }
+        KeyDerivationAlgorithm::Hkdf(params) => {
+            let metadata = util::get_hkdf_metadata(params)?;
+            v2::DerivationAlgorithm::try_from(&metadata)
         }
     }
 }
 
+fn get_derived_key_algorithm(algorithm: &DerivedKeyAlgorithm) -> Result<v2::DerivedKeyAlgorithm> {
+    match algorithm {
+        DerivedKeyAlgorithm::Aes(params) => {
+            let aes_metadata = util::get_aes_metadata(params)?;
+            Ok(v2::DerivedKeyAlgorithm::Aes((&aes_metadata).into()))
+        }
+        DerivedKeyAlgorithm::Hmac(params) => {
+            let hmac_metadata = util::get_hmac_metadata(params)?;
+            Ok(v2::DerivedKeyAlgorithm::Hmac(v2::HmacParams::try_from(
+                &hmac_metadata,
+            )?))
+        }
+    }
+}
+
+fn get_key_format(format: &str) -> Result<v2::KeyFormat> {
+    Ok((&util::get_key_format(format)?).into())
+}
+
+pub fn generate_key(
+    algorithm: &KeyGenAlgorithm,
+    extractable: bool,
+    usages: &[&str],
+) -> Result<CryptoKey> {
+    let key = CryptoImpl::generate_key("", &get_key_algorithm(algorithm)?, extractable, usages)?;
+    let crypto_key_json = util::from_utf8(key)?;
+    let crypto_key: CryptoKey = util::from_json(&crypto_key_json)?;
+    Ok(crypto_key)
+}
+
+pub fn encrypt(
+    algorithm: &EncryptAlgorithm,
+    key: &CryptoKey,
+    clear_text: &[u8],
+) -> Result<Vec<u8>> {
+    if clear_text.is_empty() {
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid clear text",
+        ));
+    }
+    CryptoImpl::encrypt(&key.id, &get_encryption_algorithm(algorithm)?, clear_text)
+}
+
+pub fn decrypt(
+    algorithm: &EncryptAlgorithm,
+    key: &CryptoKey,
+    cipher_text: &[u8],
+) -> Result<Vec<u8>> {
+    if cipher_text.is_empty() {
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid cipher_text text",
+        ));
+    }
+    CryptoImpl::decrypt(&key.id, &get_encryption_algorithm(algorithm)?, cipher_text)
+}
+
+pub fn sign(algorithm: &SignAlgorithm, key: &CryptoKey, data: &[u8]) -> Result<Vec<u8>> {
+    if data.is_empty() {
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid data"));
+    }
+    CryptoImpl::sign(&key.id, &get_signing_algorithm(algorithm)?, data)
+}
+
 pub fn verify(
     algorithm: &SignAlgorithm,
     key: &CryptoKey,
     data: &[u8],
     signature: &[u8],
-) -> Result<VerifySignResult, Box<dyn std::error::Error>> {
+) -> Result<VerifySignResult> {
     if signature.is_empty() || data.is_empty() {
-        return Err("Invalid signature or data".into());
-    }
-    let key_name = &key.id;
-    match algorithm {
-        SignAlgorithm::RsaPss(params) => {
-            let metadata = RsaPssSignatureMetadata {
-                salt_length: params.salt_length as u64,
-            };
-            let result = CryptoImpl::verify(
-                key_name,
-                SigningAlgorithm::RsaPss as u32,
-                &serde_json::to_string(&metadata)?,
-                data,
-                signature,
-            )?;
-            Ok(result)
-        }
-        SignAlgorithm::Ecdsa(params) => {
-            let sha_metadata = util::get_sha_metadata(&params.hash)?;
-            let metadata = EcdsaSignatureMetadata { sha_metadata };
-            let result = CryptoImpl::verify(
-                key_name,
-                SigningAlgorithm::Ecdsa as u32,
-                &serde_json::to_string(&metadata)?,
-                data,
-                signature,
-            )?;
-            Ok(result)
-        }
-        SignAlgorithm::Hmac() => {
-            let result =
-                CryptoImpl::verify(key_name, SigningAlgorithm::Hmac as u32, "", data, signature)?;
-            Ok(result)
-        }
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid signature or data",
+        ));
     }
+    CryptoImpl::verify(&key.id, &get_signing_algorithm(algorithm)?, data, signature)
 }
 
-pub fn digest(algorithm: &str, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+pub fn digest(algorithm: &str, data: &[u8]) -> Result<Vec<u8>> {
     if data.is_empty() {
-        return Err("Invalid data".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid data"));
     }
     let sha_metadata = util::get_sha_metadata(algorithm)?;
-    let result = CryptoImpl::digest(
-        HashAlgorithm::Sha as u32,
-        &serde_json::to_string(&sha_metadata)?,
-        data,
-    )?;
-    Ok(result)
+    CryptoImpl::digest(v2::ShaAlgorithm::try_from(&sha_metadata)?, data)
 }
 
 pub fn import_key(
@@ -529,72 +422,21 @@ pub fn import_key(
     algorithm: &KeyGenAlgorithm,
     extractable: bool,
     usages: &[&str],
-) -> Result<CryptoKey, Box<dyn std::error::Error>> {
-    let algo_metadata: String;
-    let algo_id: KeyAlgorithm;
-
-    let key_format = util::get_key_format(format)?;
-
+) -> Result<CryptoKey> {
+    let key_format = get_key_format(format)?;
     if key_data.is_empty() {
-        return Err("Invalid key data".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key data"));
     }
-
-    match algorithm {
-        KeyGenAlgorithm::Rsa(params) => {
-            let rsa_metadata = util::get_rsa_metadata(params)?;
-            algo_metadata = serde_json::to_string(&rsa_metadata)?;
-            algo_id = KeyAlgorithm::Rsa;
-        }
-        KeyGenAlgorithm::Ecc(params) => {
-            let named_curve = params.named_curve.as_str();
-            match named_curve {
-                "P-256" | "P-384" | "P-521" => {
-                    let secpr1_metadata = util::get_secpr1_metadata(params)?;
-                    algo_metadata = serde_json::to_string(&secpr1_metadata)?;
-                    algo_id = KeyAlgorithm::SecpR1;
-                }
-                "secp256k1" | "SECP256K1" => {
-                    let secpk1_metadata = util::get_secpk1_metadata(params)?;
-                    algo_metadata = serde_json::to_string(&secpk1_metadata)?;
-                    algo_id = KeyAlgorithm::SecpK1;
-                }
-                _ => return Err("Invalid curve name".into()),
-            }
-        }
-        KeyGenAlgorithm::Aes(params) => {
-            let aes_metadata = match util::get_aes_metadata(params) {
-                Ok(aes_metadata) => aes_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            algo_metadata = serde_json::to_string(&aes_metadata)?;
-            algo_id = KeyAlgorithm::Aes;
-        }
-        KeyGenAlgorithm::Hmac(params) => {
-            let hmac_metadata = match util::get_hmac_metadata(params) {
-                Ok(hmac_metadata) => hmac_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            algo_metadata = serde_json::to_string(&hmac_metadata)?;
-            algo_id = KeyAlgorithm::Hmac;
-        }
-    }
-
     let result = CryptoImpl::import_key(
-        key_format as u32,
+        key_format,
         key_data,
-        algo_id as u32,
-        &algo_metadata,
+        &get_key_algorithm(algorithm)?,
         extractable,
         usages,
         "",
     )?;
-
-    let crypto_key_json = String::from_utf8(result)?;
-    let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
+    let crypto_key_json = util::from_utf8(result)?;
+    let crypto_key: CryptoKey = util::from_json(&crypto_key_json)?;
     Ok(crypto_key)
 }
 
@@ -603,57 +445,19 @@ pub fn wrap_key(
     key: &CryptoKey,
     wrapping_key: &CryptoKey,
     algorithm: &KeyWrapAlgorithm,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let key_format = util::get_key_format(format)?;
+) -> Result<Vec<u8>> {
+    let key_format = get_key_format(format)?;
     let key_name = &key.id;
     let wrapping_key_name = &wrapping_key.id;
-
     if key_name.is_empty() || wrapping_key_name.is_empty() {
-        return Err("Invalid key".into());
-    }
-
-    match algorithm {
-        KeyWrapAlgorithm::RsaOaep(params) => {
-            let metadata = RsaOaepEncryptionMetadata {
-                label: params.label.clone(),
-            };
-            let result = CryptoImpl::wrap_key(
-                wrapping_key_name,
-                EncryptionAlgorithm::RsaOaep as u32,
-                &serde_json::to_string(&metadata)?,
-                key_name,
-                key_format as u32,
-            )?;
-            Ok(result)
-        }
-        KeyWrapAlgorithm::AesGcm(params) => {
-            let tag_length = util::get_aes_tag_length(&params.tag_length)?;
-            let metadata = AesGcmEncryptionMetadata {
-                iv: params.iv.clone(),
-                additional_data: params.additional_data.clone(),
-                tag_length,
-            };
-            let result = CryptoImpl::wrap_key(
-                wrapping_key_name,
-                EncryptionAlgorithm::AesGcm as u32,
-                &serde_json::to_string(&metadata)?,
-                key_name,
-                key_format as u32,
-            )?;
-            Ok(result)
-        }
-        KeyWrapAlgorithm::AesKw => {
-            let metadata = AesKwWrappingMetadata { with_padding: true };
-            let result = CryptoImpl::wrap_key(
-                wrapping_key_name,
-                WrappingAlgorithm::AesKw as u32,
-                &serde_json::to_string(&metadata)?,
-                key_name,
-                key_format as u32,
-            )?;
-            Ok(result)
-        }
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
+    CryptoImpl::wrap_key(
+        wrapping_key_name,
+        &get_wrapping_algorithm(algorithm)?,
+        key_name,
+        key_format,
+    )
 }
 
 pub fn unwrap_key(
@@ -664,140 +468,60 @@ pub fn unwrap_key(
     unwrapped_key_algorithm: &KeyGenAlgorithm,
     extractable: bool,
     usages: &[&str],
-) -> Result<CryptoKey, Box<dyn std::error::Error>> {
-    let key_format = util::get_key_format(format)?;
-
+) -> Result<CryptoKey> {
+    let key_format = get_key_format(format)?;
     if wrapped_key.is_empty() {
-        return Err("Invalid wrapped key".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid wrapped key",
+        ));
     }
-
     let unwrapping_key_name = &unwrapping_key.id;
     if unwrapping_key_name.is_empty() {
-        return Err("Invalid key".into());
-    }
-
-    let wrapping_algo_metadata: String;
-    let wrapping_algo_id: WrappingAlgorithm;
-
-    match unwrap_algorithm {
-        KeyWrapAlgorithm::RsaOaep(params) => {
-            let metadata = RsaOaepEncryptionMetadata {
-                label: params.label.clone(),
-            };
-            wrapping_algo_metadata = serde_json::to_string(&metadata)?;
-            wrapping_algo_id = WrappingAlgorithm::RsaOaep;
-        }
-        KeyWrapAlgorithm::AesGcm(params) => {
-            let tag_length = util::get_aes_tag_length(&params.tag_length)?;
-            let metadata = AesGcmEncryptionMetadata {
-                iv: params.iv.clone(),
-                additional_data: params.additional_data.clone(),
-                tag_length,
-            };
-            wrapping_algo_metadata = serde_json::to_string(&metadata)?;
-            wrapping_algo_id = WrappingAlgorithm::AesGcm;
-        }
-        KeyWrapAlgorithm::AesKw => {
-            let metadata = AesKwWrappingMetadata { with_padding: true };
-            wrapping_algo_metadata = serde_json::to_string(&metadata)?;
-            wrapping_algo_id = WrappingAlgorithm::AesKw;
-        }
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
-
-    let key_gen_algo_metadata: String;
-    let key_gen_algo_id: KeyAlgorithm;
-
-    match unwrapped_key_algorithm {
-        KeyGenAlgorithm::Rsa(params) => {
-            let rsa_metadata = util::get_rsa_metadata(params)?;
-            key_gen_algo_metadata = serde_json::to_string(&rsa_metadata)?;
-            key_gen_algo_id = KeyAlgorithm::Rsa;
-        }
-        KeyGenAlgorithm::Ecc(params) => {
-            let named_curve = params.named_curve.as_str();
-            match named_curve {
-                "P-256" | "P-384" | "P-521" => {
-                    let secpr1_metadata = util::get_secpr1_metadata(params)?;
-                    key_gen_algo_metadata = serde_json::to_string(&secpr1_metadata)?;
-                    key_gen_algo_id = KeyAlgorithm::SecpR1;
-                }
-                "secp256k1" | "SECP256K1" => {
-                    let secpk1_metadata = util::get_secpk1_metadata(params)?;
-                    key_gen_algo_metadata = serde_json::to_string(&secpk1_metadata)?;
-                    key_gen_algo_id = KeyAlgorithm::SecpK1;
-                }
-                _ => return Err("Invalid curve name".into()),
-            }
-        }
-        KeyGenAlgorithm::Aes(params) => {
-            let aes_metadata = match util::get_aes_metadata(params) {
-                Ok(aes_metadata) => aes_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            key_gen_algo_metadata = serde_json::to_string(&aes_metadata)?;
-            key_gen_algo_id = KeyAlgorithm::Aes;
-        }
-        KeyGenAlgorithm::Hmac(params) => {
-            let hmac_metadata = match util::get_hmac_metadata(params) {
-                Ok(hmac_metadata) => hmac_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            key_gen_algo_metadata = serde_json::to_string(&hmac_metadata)?;
-            key_gen_algo_id = KeyAlgorithm::Hmac;
-        }
-    }
-
     let result = CryptoImpl::unwrap_key(
         unwrapping_key_name,
-        wrapping_algo_id as u32,
-        &wrapping_algo_metadata,
-        key_format as u32,
+        &get_wrapping_algorithm(unwrap_algorithm)?,
+        key_format,
         wrapped_key,
-        key_gen_algo_id as u32,
-        &key_gen_algo_metadata,
+        &get_key_algorithm(unwrapped_key_algorithm)?,
         extractable,
         usages,
     )?;
-    let crypto_key_json = String::from_utf8(result)?;
-    let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
+    let crypto_key_json = util::from_utf8(result)?;
+    let crypto_key: CryptoKey = util::from_json(&crypto_key_json)?;
     Ok(crypto_key)
 }
 
-pub fn export_key(format: &str, key: &CryptoKey) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+pub fn export_key(format: &str, key: &CryptoKey) -> Result<Vec<u8>> {
     if key.id.is_empty() {
-        return Err("Invalid key".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
-    let key_name = &key.id;
-    let key_format = util::get_key_format(format)?;
-    let result = CryptoImpl::export_key(key_name, key_format as u32)?;
-    Ok(result)
+    CryptoImpl::export_key(&key.id, get_key_format(format)?)
 }
 
-pub fn get_public_key(key: &CryptoKey) -> Result<CryptoKey, Box<dyn std::error::Error>> {
+pub fn get_public_key(key: &CryptoKey) -> Result<CryptoKey> {
     if key.id.is_empty() {
-        return Err("Invalid key".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
     let key_name = &key.id;
 
     let key_type = key.key_type.as_str();
     match key_type {
         "secret" | "aes" => {
-            return Err("Invalid key type: AES symmetric key cannot have public key".into())
+            return Err(Error::invalid_input(
+                Subsystem::Crypto,
+                "Invalid key type: AES symmetric key cannot have public key",
+            ))
         }
-        "public" => return Err("Invalid key type".into()),
+        "public" => return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key type")),
         _ => (),
     };
 
     let crypto_key_json = CryptoImpl::get_public_key_as_crypto_key(key_name)?;
 
-    let crypto_key: CryptoKey = match serde_json::from_str(&crypto_key_json) {
-        Ok(crypto_key) => crypto_key,
-        Err(e) => return Err(e.into()),
-    };
+    let crypto_key: CryptoKey = util::from_json(&crypto_key_json)?;
 
     Ok(crypto_key)
 }
@@ -808,78 +532,18 @@ pub fn derive_key(
     derived_key_algorithm: &DerivedKeyAlgorithm,
     extractable: bool,
     usages: &[&str],
-) -> Result<CryptoKey, Box<dyn std::error::Error>> {
+) -> Result<CryptoKey> {
     if base_key.id.is_empty() {
-        return Err("Invalid key".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
-    let base_key_name = &base_key.id;
-
-    let derivation_algo_metadata: String;
-    let derivation_algo_id: DerivationAlgorithm;
-
-    match derivation_algorithm {
-        KeyDerivationAlgorithm::Ecdh(params) => {
-            derivation_algo_metadata = match util::get_ecdh_metadata(params) {
-                Ok(result) => serde_json::to_string(&result)?,
-                Err(e) => return Err(e),
-            };
-            derivation_algo_id = DerivationAlgorithm::Ecdh;
-        }
-        KeyDerivationAlgorithm::Hkdf(params) => {
-            derivation_algo_metadata = match util::get_hkdf_metadata(params) {
-                Ok(result) => serde_json::to_string(&result)?,
-                Err(e) => return Err(e),
-            };
-            derivation_algo_id = DerivationAlgorithm::Hkdf;
-        }
-    }
-
-    let derived_key_algo_metadata: String;
-    let derived_key_algo_id: DerivedKeyUsageAlgorithm;
-    match derived_key_algorithm {
-        DerivedKeyAlgorithm::Aes(params) => {
-            let aes_metadata = match util::get_aes_metadata(params) {
-                Ok(aes_metadata) => aes_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            derived_key_algo_metadata = match serde_json::to_string(&aes_metadata) {
-                Ok(result) => result,
-                Err(e) => {
-                    return Err(e.into());
-                }
-            };
-            derived_key_algo_id = DerivedKeyUsageAlgorithm::Aes;
-        }
-        DerivedKeyAlgorithm::Hmac(params) => {
-            let hmac_metadata = match util::get_hmac_metadata(params) {
-                Ok(hmac_metadata) => hmac_metadata,
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            derived_key_algo_metadata = match serde_json::to_string(&hmac_metadata) {
-                Ok(result) => result,
-                Err(e) => {
-                    return Err(e.into());
-                }
-            };
-            derived_key_algo_id = DerivedKeyUsageAlgorithm::Hmac;
-        }
-    }
-
     let crypto_key_json = CryptoImpl::derive_key(
-        derivation_algo_id as u32,
-        &derivation_algo_metadata,
-        derived_key_algo_id as u32,
-        &derived_key_algo_metadata,
+        &get_derivation_algorithm(derivation_algorithm)?,
+        &get_derived_key_algorithm(derived_key_algorithm)?,
         extractable,
         usages,
-        base_key_name,
+        &base_key.id,
     )?;
-
-    let crypto_key: CryptoKey = serde_json::from_str(&crypto_key_json)?;
+    let crypto_key: CryptoKey = util::from_json(&crypto_key_json)?;
     Ok(crypto_key)
 }
 
@@ -887,61 +551,38 @@ pub fn derive_bits(
     derivation_algorithm: &KeyDerivationAlgorithm,
     base_key: &CryptoKey,
     length: u32,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+) -> Result<Vec<u8>> {
     if base_key.id.is_empty() {
-        return Err("Invalid key".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
     if length == 0 {
-        return Err("Invalid Length".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid Length"));
     }
-    let base_key_name = &base_key.id;
-
-    let derivation_algo_metadata: String;
-    let derivation_algo_id: DerivationAlgorithm;
-
-    match derivation_algorithm {
-        KeyDerivationAlgorithm::Ecdh(params) => {
-            derivation_algo_metadata = match util::get_ecdh_metadata(params) {
-                Ok(result) => serde_json::to_string(&result)?,
-                Err(e) => return Err(e),
-            };
-            derivation_algo_id = DerivationAlgorithm::Ecdh;
-        }
-        KeyDerivationAlgorithm::Hkdf(params) => {
-            derivation_algo_metadata = match util::get_hkdf_metadata(params) {
-                Ok(result) => serde_json::to_string(&result)?,
-                Err(e) => return Err(e),
-            };
-            derivation_algo_id = DerivationAlgorithm::Hkdf;
-        }
-    }
-
-    let derived_bits = CryptoImpl::derive_bits(
-        base_key_name,
-        derivation_algo_id as u32,
-        &derivation_algo_metadata,
+    CryptoImpl::derive_bits(
+        &base_key.id,
+        &get_derivation_algorithm(derivation_algorithm)?,
         length,
-    )?;
-
-    Ok(derived_bits)
+    )
 }
 
-pub fn save_key(
-    key: &CryptoKey,
-    key_persisted_name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_key(key: &CryptoKey, key_persisted_name: &str) -> Result<()> {
     if key.id.is_empty() {
-        return Err("Invalid key".into());
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid key"));
     }
     if key_persisted_name.is_empty() {
-        return Err("Invalid key name: cannot be null or empty".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: cannot be null or empty",
+        ));
     }
     let existing_key = CryptoImpl::key_exists(key_persisted_name)?;
 
     if existing_key {
-        return Err(
-            format!("Invalid key name: key name {key_persisted_name} already exists").into(),
-        );
+        return Err(Error::new(
+            Subsystem::Crypto,
+            ErrorKind::AlreadyExists,
+            format!("Invalid key name: key name {key_persisted_name} already exists"),
+        ));
     }
 
     let params = KeyPersistParams {
@@ -949,26 +590,32 @@ pub fn save_key(
         key_name: key_persisted_name.to_string(),
         key_type: key.key_type.clone(),
     };
-    match CryptoImpl::persist_key(&serde_json::to_string(&params)?.into_bytes()) {
+    match CryptoImpl::persist_key(&util::to_json(&params)?.into_bytes()) {
         Ok(_) => (),
         Err(e) => return Err(e),
     };
     Ok(())
 }
 
-pub fn load_key(key_name: &str) -> Result<CryptoKey, Box<dyn std::error::Error>> {
+pub fn load_key(key_name: &str) -> Result<CryptoKey> {
     if key_name.is_empty() {
-        return Err("Invalid key name: cannot be null or empty".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: cannot be null or empty",
+        ));
     }
     let key_json = CryptoImpl::load_key(key_name)?;
-    let crypto_key: CryptoKey = serde_json::from_str(&key_json)?;
+    let crypto_key: CryptoKey = util::from_json(&key_json)?;
     Ok(crypto_key)
 }
 
-pub fn delete_key(key: &CryptoKey) -> Result<(), Box<dyn std::error::Error>> {
+pub fn delete_key(key: &CryptoKey) -> Result<()> {
     if let Some(alias) = &key.alias {
         if alias.is_empty() {
-            return Err("Invalid key name: cannot be null or empty".into());
+            return Err(Error::invalid_input(
+                Subsystem::Crypto,
+                "Invalid key name: cannot be null or empty",
+            ));
         }
         match CryptoImpl::delete_key(alias) {
             Ok(_) => (),
@@ -976,6 +623,9 @@ pub fn delete_key(key: &CryptoKey) -> Result<(), Box<dyn std::error::Error>> {
         };
         Ok(())
     } else {
-        Err("Invalid key name: cannot be null or empty".into())
+        Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key name: cannot be null or empty",
+        ))
     }
 }
@@ -0,0 +1,580 @@
+use super::subtle_idl_v1::{
+    AesGcmEncryptionMetadata, AesKwWrappingMetadata, AesMetadata, EcdhMetadata,
+    EcdsaSignatureMetadata, HkdfMetadata, HmacMetadata, RsaMetadata, RsaOaepEncryptionMetadata,
+    RsaPssSignatureMetadata, SecpK1Metadata, SecpR1Metadata, ShaMetadata,
+};
+use super::subtle_idl_v1_enums as v1;
+use crate::error::{Error, Result, Subsystem};
+use serde::Serialize;
+use std::str::FromStr;
+
+// Typed parameters of the `klave:sdk/sdk-v2` crypto calls.
+// They are built from the `subtle_idl_v1` metadata, and turned back into algorithm ids and
+// JSON metadata (`to_v1`) for hosts that only provide the string-based `klave:sdk/sdk` calls.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    Raw,
+    Spki,
+    Pkcs8,
+    Jwk,
+    Sec1,
+    Pkcs1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyUsage {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    DeriveKey,
+    DeriveBits,
+    WrapKey,
+    UnwrapKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaSize {
+    Bits256,
+    Bits384,
+    Bits512,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaAlgorithm {
+    Sha1,
+    Sha2(ShaSize),
+    Sha3(ShaSize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecpR1Curve {
+    P256,
+    P384,
+    P521,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesKeySize {
+    Bits128,
+    Bits192,
+    Bits256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsaModulus {
+    Bits2048,
+    Bits3072,
+    Bits4096,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesTagLength {
+    Bits96,
+    Bits104,
+    Bits112,
+    Bits120,
+    Bits128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaParams {
+    pub modulus: RsaModulus,
+    pub public_exponent: u32,
+    pub hash: ShaAlgorithm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HmacParams {
+    pub hash: ShaAlgorithm,
+    /// Key length in bits, 0 for the block size of the hash
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    SecpR1(SecpR1Curve),
+    SecpK1,
+    Aes(AesKeySize),
+    Rsa(RsaParams),
+    Hmac(HmacParams),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AesGcmParams {
+    pub iv: Vec<u8>,
+    pub additional_data: Vec<u8>,
+    pub tag_length: AesTagLength,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaOaepParams {
+    pub label: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionAlgorithm {
+    AesGcm(AesGcmParams),
+    RsaOaep(RsaOaepParams),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WrappingAlgorithm {
+    /// With padding when true
+    AesKw(bool),
+    AesGcm(AesGcmParams),
+    RsaOaep(RsaOaepParams),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    Ecdsa(ShaAlgorithm),
+    /// Salt length in bytes
+    RsaPss(u64),
+    Hmac,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HkdfParams {
+    pub salt: Vec<u8>,
+    pub info: Vec<u8>,
+    pub hash: ShaAlgorithm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivationAlgorithm {
+    /// Name of the public key of the other party
+    Ecdh(String),
+    Hkdf(HkdfParams),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivedKeyAlgorithm {
+    Aes(AesKeySize),
+    Hmac(HmacParams),
+}
+
+// Metadata are plain structs of numbers, bytes and strings: serializing them cannot fail
+fn json<T: Serialize>(metadata: &T) -> String {
+    serde_json::to_string(metadata).unwrap_or_default()
+}
+
+impl FromStr for KeyUsage {
+    type Err = Error;
+
+    fn from_str(usage: &str) -> Result<KeyUsage> {
+        match usage {
+            "encrypt" => Ok(KeyUsage::Encrypt),
+            "decrypt" => Ok(KeyUsage::Decrypt),
+            "sign" => Ok(KeyUsage::Sign),
+            "verify" => Ok(KeyUsage::Verify),
+            "derive_key" | "deriveKey" => Ok(KeyUsage::DeriveKey),
+            "derive_bits" | "deriveBits" => Ok(KeyUsage::DeriveBits),
+            "wrap_key" | "wrapKey" => Ok(KeyUsage::WrapKey),
+            "unwrap_key" | "unwrapKey" => Ok(KeyUsage::UnwrapKey),
+            _ => Err(Error::invalid_input(
+                Subsystem::Crypto,
+                format!("Invalid key usage: {usage}"),
+            )),
+        }
+    }
+}
+
+impl KeyUsage {
+    pub fn to_v1(self) -> u8 {
+        let usage = match self {
+            KeyUsage::Encrypt => v1::SubtleKeyUsage::Encrypt,
+            KeyUsage::Decrypt => v1::SubtleKeyUsage::Decrypt,
+            KeyUsage::Sign => v1::SubtleKeyUsage::Sign,
+            KeyUsage::Verify => v1::SubtleKeyUsage::Verify,
+            KeyUsage::DeriveKey => v1::SubtleKeyUsage::DeriveKey,
+            KeyUsage::DeriveBits => v1::SubtleKeyUsage::DeriveBits,
+            KeyUsage::WrapKey => v1::SubtleKeyUsage::WrapKey,
+            KeyUsage::UnwrapKey => v1::SubtleKeyUsage::UnwrapKey,
+        };
+        usage as u8
+    }
+}
+
+impl From<&v1::KeyFormat> for KeyFormat {
+    fn from(format: &v1::KeyFormat) -> Self {
+        match format {
+            v1::KeyFormat::Raw => KeyFormat::Raw,
+            v1::KeyFormat::Spki => KeyFormat::Spki,
+            v1::KeyFormat::Pkcs8 => KeyFormat::Pkcs8,
+            v1::KeyFormat::Jwk => KeyFormat::Jwk,
+            v1::KeyFormat::Sec1 => KeyFormat::Sec1,
+            v1::KeyFormat::Pkcs1 => KeyFormat::Pkcs1,
+        }
+    }
+}
+
+impl KeyFormat {
+    pub fn to_v1(self) -> i32 {
+        let format = match self {
+            KeyFormat::Raw => v1::KeyFormat::Raw,
+            KeyFormat::Spki => v1::KeyFormat::Spki,
+            KeyFormat::Pkcs8 => v1::KeyFormat::Pkcs8,
+            KeyFormat::Jwk => v1::KeyFormat::Jwk,
+            KeyFormat::Sec1 => v1::KeyFormat::Sec1,
+            KeyFormat::Pkcs1 => v1::KeyFormat::Pkcs1,
+        };
+        format as i32
+    }
+}
+
+impl TryFrom<&ShaMetadata> for ShaAlgorithm {
+    type Error = Error;
+
+    fn try_from(metadata: &ShaMetadata) -> Result<ShaAlgorithm> {
+        let size = match metadata.length {
+            v1::ShaDigestBitsize::Sha1 => None,
+            v1::ShaDigestBitsize::Sha256 => Some(ShaSize::Bits256),
+            v1::ShaDigestBitsize::Sha384 => Some(ShaSize::Bits384),
+            v1::ShaDigestBitsize::Sha512 => Some(ShaSize::Bits512),
+        };
+        match (&metadata.algo_id, size) {
+            (v1::ShaAlgorithm::Sha1 | v1::ShaAlgorithm::Sha2, None) => Ok(ShaAlgorithm::Sha1),
+            (v1::ShaAlgorithm::Sha2, Some(size)) => Ok(ShaAlgorithm::Sha2(size)),
+            (v1::ShaAlgorithm::Sha3, Some(size)) => Ok(ShaAlgorithm::Sha3(size)),
+            _ => Err(Error::invalid_input(
+                Subsystem::Crypto,
+                "Invalid hash algorithm",
+            )),
+        }
+    }
+}
+
+impl ShaAlgorithm {
+    fn metadata(self) -> ShaMetadata {
+        let length = |size| match size {
+            ShaSize::Bits256 => v1::ShaDigestBitsize::Sha256,
+            ShaSize::Bits384 => v1::ShaDigestBitsize::Sha384,
+            ShaSize::Bits512 => v1::ShaDigestBitsize::Sha512,
+        };
+        match self {
+            // Same ids as `util::get_sha_metadata`
+            ShaAlgorithm::Sha1 => ShaMetadata {
+                algo_id: v1::ShaAlgorithm::Sha2,
+                length: v1::ShaDigestBitsize::Sha1,
+            },
+            ShaAlgorithm::Sha2(size) => ShaMetadata {
+                algo_id: v1::ShaAlgorithm::Sha2,
+                length: length(size),
+            },
+            ShaAlgorithm::Sha3(size) => ShaMetadata {
+                algo_id: v1::ShaAlgorithm::Sha3,
+                length: length(size),
+            },
+        }
+    }
+
+    /// Hash algorithm id and metadata of the `digest` call of `klave:sdk/sdk`
+    pub fn to_v1(self) -> (i32, String) {
+        (v1::HashAlgorithm::Sha as i32, json(&self.metadata()))
+    }
+}
+
+impl From<&SecpR1Metadata> for SecpR1Curve {
+    fn from(metadata: &SecpR1Metadata) -> Self {
+        match metadata.length {
+            v1::SecpR1KeyBitsize::SecpR1256 => SecpR1Curve::P256,
+            v1::SecpR1KeyBitsize::SecpR1384 => SecpR1Curve::P384,
+            v1::SecpR1KeyBitsize::SecpR1521 => SecpR1Curve::P521,
+        }
+    }
+}
+
+impl From<&AesMetadata> for AesKeySize {
+    fn from(metadata: &AesMetadata) -> Self {
+        match metadata.length {
+            v1::AesKeyBitsize::Aes128 => AesKeySize::Bits128,
+            v1::AesKeyBitsize::Aes192 => AesKeySize::Bits192,
+            v1::AesKeyBitsize::Aes256 => AesKeySize::Bits256,
+        }
+    }
+}
+
+impl AesKeySize {
+    fn metadata(self) -> AesMetadata {
+        let length = match self {
+            AesKeySize::Bits128 => v1::AesKeyBitsize::Aes128,
+            AesKeySize::Bits192 => v1::AesKeyBitsize::Aes192,
+            AesKeySize::Bits256 => v1::AesKeyBitsize::Aes256,
+        };
+        AesMetadata { length }
+    }
+}
+
+impl TryFrom<&RsaMetadata> for RsaParams {
+    type Error = Error;
+
+    fn try_from(metadata: &RsaMetadata) -> Result<RsaParams> {
+        let modulus = match metadata.modulus {
+            v1::RsaKeyBitsize::Rsa2048 => RsaModulus::Bits2048,
+            v1::RsaKeyBitsize::Rsa3072 => RsaModulus::Bits3072,
+            v1::RsaKeyBitsize::Rsa4096 => RsaModulus::Bits4096,
+        };
+        Ok(RsaParams {
+            modulus,
+            public_exponent: metadata.public_exponent,
+            hash: ShaAlgorithm::try_from(&metadata.sha_metadata)?,
+        })
+    }
+}
+
+impl TryFrom<&HmacMetadata> for HmacParams {
+    type Error = Error;
+
+    fn try_from(metadata: &HmacMetadata) -> Result<HmacParams> {
+        Ok(HmacParams {
+            hash: ShaAlgorithm::try_from(&metadata.sha_metadata)?,
+            length: metadata.length,
+        })
+    }
+}
+
+impl HmacParams {
+    fn metadata(&self) -> HmacMetadata {
+        HmacMetadata {
+            sha_metadata: self.hash.metadata(),
+            length: self.length,
+        }
+    }
+}
+
+impl From<&SecpK1Metadata> for KeyAlgorithm {
+    fn from(metadata: &SecpK1Metadata) -> Self {
+        match metadata.length {
+            v1::SecpK1KeyBitsize::SecpK1256 => KeyAlgorithm::SecpK1,
+        }
+    }
+}
+
+impl KeyAlgorithm {
+    /// Key algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            KeyAlgorithm::SecpR1(curve) => {
+                let length = match curve {
+                    SecpR1Curve::P256 => v1::SecpR1KeyBitsize::SecpR1256,
+                    SecpR1Curve::P384 => v1::SecpR1KeyBitsize::SecpR1384,
+                    SecpR1Curve::P521 => v1::SecpR1KeyBitsize::SecpR1521,
+                };
+                (
+                    v1::KeyAlgorithm::SecpR1 as i32,
+                    json(&SecpR1Metadata { length }),
+                )
+            }
+            KeyAlgorithm::SecpK1 => (
+                v1::KeyAlgorithm::SecpK1 as i32,
+                json(&SecpK1Metadata {
+                    length: v1::SecpK1KeyBitsize::SecpK1256,
+                }),
+            ),
+            KeyAlgorithm::Aes(size) => (v1::KeyAlgorithm::Aes as i32, json(&size.metadata())),
+            KeyAlgorithm::Rsa(params) => {
+                let modulus = match params.modulus {
+                    RsaModulus::Bits2048 => v1::RsaKeyBitsize::Rsa2048,
+                    RsaModulus::Bits3072 => v1::RsaKeyBitsize::Rsa3072,
+                    RsaModulus::Bits4096 => v1::RsaKeyBitsize::Rsa4096,
+                };
+                let metadata = RsaMetadata {
+                    modulus,
+                    public_exponent: params.public_exponent,
+                    sha_metadata: params.hash.metadata(),
+                };
+                (v1::KeyAlgorithm::Rsa as i32, json(&metadata))
+            }
+            KeyAlgorithm::Hmac(params) => (v1::KeyAlgorithm::Hmac as i32, json(&params.metadata())),
+        }
+    }
+}
+
+impl From<&AesGcmEncryptionMetadata> for AesGcmParams {
+    fn from(metadata: &AesGcmEncryptionMetadata) -> Self {
+        let tag_length = match metadata.tag_length {
+            v1::AesTagLength::Tag96 => AesTagLength::Bits96,
+            v1::AesTagLength::Tag104 => AesTagLength::Bits104,
+            v1::AesTagLength::Tag112 => AesTagLength::Bits112,
+            v1::AesTagLength::Tag120 => AesTagLength::Bits120,
+            v1::AesTagLength::Tag128 => AesTagLength::Bits128,
+        };
+        AesGcmParams {
+            iv: metadata.iv.clone(),
+            additional_data: metadata.additional_data.clone(),
+            tag_length,
+        }
+    }
+}
+
+impl AesGcmParams {
+    fn metadata(&self) -> AesGcmEncryptionMetadata {
+        let tag_length = match self.tag_length {
+            AesTagLength::Bits96 => v1::AesTagLength::Tag96,
+            AesTagLength::Bits104 => v1::AesTagLength::Tag104,
+            AesTagLength::Bits112 => v1::AesTagLength::Tag112,
+            AesTagLength::Bits120 => v1::AesTagLength::Tag120,
+            AesTagLength::Bits128 => v1::AesTagLength::Tag128,
+        };
+        AesGcmEncryptionMetadata {
+            iv: self.iv.clone(),
+            additional_data: self.additional_data.clone(),
+            tag_length,
+        }
+    }
+}
+
+impl From<&RsaOaepEncryptionMetadata> for RsaOaepParams {
+    fn from(metadata: &RsaOaepEncryptionMetadata) -> Self {
+        RsaOaepParams {
+            label: metadata.label.clone(),
+        }
+    }
+}
+
+impl RsaOaepParams {
+    fn metadata(&self) -> RsaOaepEncryptionMetadata {
+        RsaOaepEncryptionMetadata {
+            label: self.label.clone(),
+        }
+    }
+}
+
+impl EncryptionAlgorithm {
+    /// Encryption algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            EncryptionAlgorithm::AesGcm(params) => (
+                v1::EncryptionAlgorithm::AesGcm as i32,
+                json(&params.metadata()),
+            ),
+            EncryptionAlgorithm::RsaOaep(params) => (
+                v1::EncryptionAlgorithm::RsaOaep as i32,
+                json(&params.metadata()),
+            ),
+        }
+    }
+}
+
+impl From<&AesKwWrappingMetadata> for WrappingAlgorithm {
+    fn from(metadata: &AesKwWrappingMetadata) -> Self {
+        WrappingAlgorithm::AesKw(metadata.with_padding)
+    }
+}
+
+impl WrappingAlgorithm {
+    /// Wrapping algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            WrappingAlgorithm::AesKw(with_padding) => (
+                v1::WrappingAlgorithm::AesKw as i32,
+                json(&AesKwWrappingMetadata {
+                    with_padding: *with_padding,
+                }),
+            ),
+            WrappingAlgorithm::AesGcm(params) => (
+                v1::WrappingAlgorithm::AesGcm as i32,
+                json(&params.metadata()),
+            ),
+            WrappingAlgorithm::RsaOaep(params) => (
+                v1::WrappingAlgorithm::RsaOaep as i32,
+                json(&params.metadata()),
+            ),
+        }
+    }
+}
+
+impl TryFrom<&EcdsaSignatureMetadata> for SigningAlgorithm {
+    type Error = Error;
+
+    fn try_from(metadata: &EcdsaSignatureMetadata) -> Result<SigningAlgorithm> {
+        Ok(SigningAlgorithm::Ecdsa(ShaAlgorithm::try_from(
+            &metadata.sha_metadata,
+        )?))
+    }
+}
+
+impl From<&RsaPssSignatureMetadata> for SigningAlgorithm {
+    fn from(metadata: &RsaPssSignatureMetadata) -> Self {
+        SigningAlgorithm::RsaPss(metadata.salt_length)
+    }
+}
+
+impl SigningAlgorithm {
+    /// Signing algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            SigningAlgorithm::Ecdsa(hash) => (
+                v1::SigningAlgorithm::Ecdsa as i32,
+                json(&EcdsaSignatureMetadata {
+                    sha_metadata: hash.metadata(),
+                }),
+            ),
+            SigningAlgorithm::RsaPss(salt_length) => (
+                v1::SigningAlgorithm::RsaPss as i32,
+                json(&RsaPssSignatureMetadata {
+                    salt_length: *salt_length,
+                }),
+            ),
+            SigningAlgorithm::Hmac => (v1::SigningAlgorithm::Hmac as i32, String::new()),
+        }
+    }
+}
+
+impl From<&EcdhMetadata> for DerivationAlgorithm {
+    fn from(metadata: &EcdhMetadata) -> Self {
+        DerivationAlgorithm::Ecdh(metadata.public_key.clone())
+    }
+}
+
+impl TryFrom<&HkdfMetadata> for DerivationAlgorithm {
+    type Error = Error;
+
+    fn try_from(metadata: &HkdfMetadata) -> Result<DerivationAlgorithm> {
+        Ok(DerivationAlgorithm::Hkdf(HkdfParams {
+            salt: metadata.salt.clone(),
+            info: metadata.info.clone(),
+            hash: ShaAlgorithm::try_from(&metadata.hash_info)?,
+        }))
+    }
+}
+
+impl DerivationAlgorithm {
+    /// Derivation algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            DerivationAlgorithm::Ecdh(public_key) => (
+                v1::DerivationAlgorithm::Ecdh as i32,
+                json(&EcdhMetadata {
+                    public_key: public_key.clone(),
+                }),
+            ),
+            DerivationAlgorithm::Hkdf(params) => (
+                v1::DerivationAlgorithm::Hkdf as i32,
+                json(&HkdfMetadata {
+                    salt: params.salt.clone(),
+                    info: params.info.clone(),
+                    hash_info: params.hash.metadata(),
+                }),
+            ),
+        }
+    }
+}
+
+impl DerivedKeyAlgorithm {
+    /// Derived key algorithm id and metadata of the `klave:sdk/sdk` calls
+    pub fn to_v1(&self) -> (i32, String) {
+        match self {
+            DerivedKeyAlgorithm::Aes(size) => (
+                v1::DerivedKeyUsageAlgorithm::Aes as i32,
+                json(&size.metadata()),
+            ),
+            DerivedKeyAlgorithm::Hmac(params) => (
+                v1::DerivedKeyUsageAlgorithm::Hmac as i32,
+                json(&params.metadata()),
+            ),
+        }
+    }
+}
@@ -10,12 +10,31 @@ use super::subtle_idl_v1_enums::{
     AesKeyBitsize, AesTagLength, KeyFormat, RsaKeyBitsize, SecpK1KeyBitsize, SecpR1KeyBitsize,
     ShaAlgorithm, ShaDigestBitsize,
 };
-use serde::{Deserialize, Serialize};
-use std::error::Error;
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use serde::{de::DeserializeOwned, Serialize};
 
-#[derive(Deserialize, Serialize)]
-struct KeyFormatWrapper {
-    pub format: KeyFormat,
+pub fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).or_error(
+        Subsystem::Crypto,
+        ErrorKind::Serialization,
+        "Failed to serialize crypto metadata",
+    )
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json).or_error(
+        Subsystem::Crypto,
+        ErrorKind::Serialization,
+        "Failed to deserialize host response",
+    )
+}
+
+pub fn from_utf8(bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes).or_error(
+        Subsystem::Crypto,
+        ErrorKind::Serialization,
+        "Host response is not valid UTF-8",
+    )
 }
 
 pub fn is_valid_hash_algorithm(algorithm: &str) -> bool {
@@ -56,7 +75,7 @@ pub fn digest_size(algorithm: &str) -> usize {
     }
 }
 
-pub fn get_sha_metadata(algorithm: &str) -> Result<ShaMetadata, Box<dyn Error>> {
+pub fn get_sha_metadata(algorithm: &str) -> Result<ShaMetadata> {
     match algorithm {
         "sha1" | "SHA1" | "sha1-160" | "SHA1-160" => Ok(ShaMetadata {
             algo_id: ShaAlgorithm::Sha2,
@@ -86,36 +105,54 @@ pub fn get_sha_metadata(algorithm: &str) -> Result<ShaMetadata, Box<dyn Error>>
             algo_id: ShaAlgorithm::Sha3,
             length: ShaDigestBitsize::Sha512,
         }),
-        _ => Err("Invalid hash algorithm".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid hash algorithm",
+        )),
     }
 }
 
-pub fn rsa_bitsize(modulus_length: u32) -> Result<RsaKeyBitsize, Box<dyn Error>> {
+pub fn rsa_bitsize(modulus_length: u32) -> Result<RsaKeyBitsize> {
     match modulus_length {
         2048 => Ok(RsaKeyBitsize::Rsa2048),
         3072 => Ok(RsaKeyBitsize::Rsa3072),
         4096 => Ok(RsaKeyBitsize::Rsa4096),
-        _ => Err("Invalid RSA modulus length".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid RSA modulus length",
+        )),
     }
 }
 
-pub fn get_rsa_metadata(params: &RsaHashedKeyGenParams) -> Result<RsaMetadata, Box<dyn Error>> {
+pub fn get_rsa_metadata(params: &RsaHashedKeyGenParams) -> Result<RsaMetadata> {
     let hash = params.hash.as_str();
     if !is_valid_hash_algorithm(hash) {
-        return Err("Invalid hash algorithm".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid hash algorithm",
+        ));
     }
 
     let digest_size = digest_size(hash);
     if digest_size == 0 {
-        return Err("Invalid hash algorithm".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid hash algorithm",
+        ));
     }
 
     let Ok(rsa_bitsize) = rsa_bitsize(params.modulus_length) else {
-        return Err("Invalid RSA modulus length".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid RSA modulus length",
+        ));
     };
 
     let Ok(sha_metadata) = get_sha_metadata(hash) else {
-        return Err("Invalid hash algorithm".into());
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid hash algorithm",
+        ));
     };
 
     Ok(RsaMetadata {
@@ -125,7 +162,7 @@ pub fn get_rsa_metadata(params: &RsaHashedKeyGenParams) -> Result<RsaMetadata, B
     })
 }
 
-pub fn get_secpr1_metadata(params: &EcKeyGenParams) -> Result<SecpR1Metadata, Box<dyn Error>> {
+pub fn get_secpr1_metadata(params: &EcKeyGenParams) -> Result<SecpR1Metadata> {
     match params.named_curve.as_str() {
         "P-256" => Ok(SecpR1Metadata {
             length: SecpR1KeyBitsize::SecpR1256,
@@ -136,20 +173,26 @@ pub fn get_secpr1_metadata(params: &EcKeyGenParams) -> Result<SecpR1Metadata, Bo
         "P-521" => Ok(SecpR1Metadata {
             length: SecpR1KeyBitsize::SecpR1521,
         }),
-        _ => Err("Invalid curve name".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid curve name",
+        )),
     }
 }
 
-pub fn get_secpk1_metadata(params: &EcKeyGenParams) -> Result<SecpK1Metadata, Box<dyn Error>> {
+pub fn get_secpk1_metadata(params: &EcKeyGenParams) -> Result<SecpK1Metadata> {
     match params.named_curve.as_str() {
         "secp256k1" | "SECP256K1" => Ok(SecpK1Metadata {
             length: SecpK1KeyBitsize::SecpK1256,
         }),
-        _ => Err("Invalid curve name".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid curve name",
+        )),
     }
 }
 
-pub fn get_aes_metadata(params: &AesKeyGenParams) -> Result<AesMetadata, Box<dyn Error>> {
+pub fn get_aes_metadata(params: &AesKeyGenParams) -> Result<AesMetadata> {
     match params.length {
         128 => Ok(AesMetadata {
             length: AesKeyBitsize::Aes128,
@@ -160,28 +203,34 @@ pub fn get_aes_metadata(params: &AesKeyGenParams) -> Result<AesMetadata, Box<dyn
         256 => Ok(AesMetadata {
             length: AesKeyBitsize::Aes256,
         }),
-        _ => Err("Invalid AES key length".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid AES key length",
+        )),
     }
 }
 
-pub fn get_hmac_metadata(params: &HmacKeyGenParams) -> Result<HmacMetadata, Box<dyn Error>> {
+pub fn get_hmac_metadata(params: &HmacKeyGenParams) -> Result<HmacMetadata> {
     let hash: &str = params.hash.as_str();
     match get_sha_metadata(hash) {
         Ok(sha_metadata) => Ok(HmacMetadata {
             sha_metadata,
             length: params.length,
         }),
-        Err(_) => Err("Invalid hash algorithm".into()),
+        Err(_) => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid hash algorithm",
+        )),
     }
 }
 
-pub fn get_ecdh_metadata(params: &EcdhDerivParams) -> Result<EcdhMetadata, Box<dyn Error>> {
+pub fn get_ecdh_metadata(params: &EcdhDerivParams) -> Result<EcdhMetadata> {
     Ok(EcdhMetadata {
         public_key: params.public.id.clone(),
     })
 }
 
-pub fn get_hkdf_metadata(params: &HkdfDerivParams) -> Result<HkdfMetadata, Box<dyn Error>> {
+pub fn get_hkdf_metadata(params: &HkdfDerivParams) -> Result<HkdfMetadata> {
     Ok(HkdfMetadata {
         salt: params.salt.clone(),
         info: params.info.clone(),
@@ -189,24 +238,30 @@ pub fn get_hkdf_metadata(params: &HkdfDerivParams) -> Result<HkdfMetadata, Box<d
     })
 }
 
-pub fn get_key_format(format: &str) -> Result<KeyFormat, Box<dyn Error>> {
+pub fn get_key_format(format: &str) -> Result<KeyFormat> {
     match format {
         "raw" | "RAW" => Ok(KeyFormat::Raw),
         "pkcs8" | "PKCS8" => Ok(KeyFormat::Pkcs8),
         "spki" | "SPKI" => Ok(KeyFormat::Spki),
         "sec1" | "SEC1" => Ok(KeyFormat::Sec1),
         "pkcs1" | "PKCS1" => Ok(KeyFormat::Pkcs1),
-        _ => Err("Invalid key format".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid key format",
+        )),
     }
 }
 
-pub fn get_aes_tag_length(tag_length: &u32) -> Result<AesTagLength, Box<dyn Error>> {
+pub fn get_aes_tag_length(tag_length: &u32) -> Result<AesTagLength> {
     match tag_length {
         96 => Ok(AesTagLength::Tag96),
         104 => Ok(AesTagLength::Tag104),
         112 => Ok(AesTagLength::Tag112),
         120 => Ok(AesTagLength::Tag120),
         128 => Ok(AesTagLength::Tag128),
-        _ => Err("Invalid tag length".into()),
+        _ => Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid tag length",
+        )),
     }
 }
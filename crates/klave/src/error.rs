@@ -0,0 +1,289 @@
+use std::fmt::Display;
+
+type Source = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// What went wrong, independently of the subsystem that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The requested key, table entry or object does not exist
+    NotFound,
+    /// An argument was rejected before reaching the host
+    InvalidInput,
+    /// The object to create already exists or the operation was already performed
+    AlreadyExists,
+    /// The caller is not allowed to perform the operation
+    PermissionDenied,
+    /// A deadline or validity window has passed
+    Expired,
+    /// The caller exceeded a rate limit
+    RateLimited,
+    /// The host reported a failure; its message is kept as the source
+    Host,
+    /// A value could not be serialized or deserialized
+    Serialization,
+    /// The operation cannot run in the current call, e.g. a ledger write from a query
+    Unsupported,
+}
+
+impl ErrorKind {
+    /// Snake case name of the kind, as used in error codes sent to clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::InvalidInput => "invalid_input",
+            ErrorKind::AlreadyExists => "already_exists",
+            ErrorKind::PermissionDenied => "permission_denied",
+            ErrorKind::Expired => "expired",
+            ErrorKind::RateLimited => "rate_limited",
+            ErrorKind::Host => "host",
+            ErrorKind::Serialization => "serialization",
+            ErrorKind::Unsupported => "unsupported",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind = match self {
+            ErrorKind::NotFound => "not found",
+            ErrorKind::InvalidInput => "invalid input",
+            ErrorKind::AlreadyExists => "already exists",
+            ErrorKind::PermissionDenied => "permission denied",
+            ErrorKind::Expired => "expired",
+            ErrorKind::RateLimited => "rate limited",
+            ErrorKind::Host => "host error",
+            ErrorKind::Serialization => "serialization error",
+            ErrorKind::Unsupported => "unsupported",
+        };
+        f.write_str(kind)
+    }
+}
+
+/// Error message reported by a host call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostError(pub String);
+
+impl Display for HostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for HostError {}
+
+macro_rules! subsystem_errors {
+    ($($(#[$attr:meta])* $variant:ident => $name:literal),*) => {
+        /// Subsystem an [`Error`] originates from
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Subsystem {
+            $($variant),*
+        }
+
+        impl Display for Subsystem {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    $(Subsystem::$variant => f.write_str($name)),*
+                }
+            }
+        }
+
+        /// Error returned by every fallible function of the SDK
+        #[derive(Debug)]
+        pub enum Error {
+            $(
+                $(#[$attr])*
+                $variant {
+                    kind: ErrorKind,
+                    message: String,
+                    source: Option<Source>,
+                }
+            ),*
+        }
+
+        impl Error {
+            pub fn new(subsystem: Subsystem, kind: ErrorKind, message: impl Into<String>) -> Error {
+                let message = message.into();
+                match subsystem {
+                    $(Subsystem::$variant => Error::$variant { kind, message, source: None }),*
+                }
+            }
+
+            pub fn subsystem(&self) -> Subsystem {
+                match self {
+                    $(Error::$variant { .. } => Subsystem::$variant),*
+                }
+            }
+
+            fn parts(&self) -> (ErrorKind, &str, Option<&Source>) {
+                match self {
+                    $(Error::$variant { kind, message, source } => (*kind, message, source.as_ref())),*
+                }
+            }
+
+            fn source_mut(&mut self) -> &mut Option<Source> {
+                match self {
+                    $(Error::$variant { source, .. } => source),*
+                }
+            }
+        }
+    };
+}
+
+subsystem_errors!(
+    Ledger => "ledger",
+    Crypto => "crypto",
+    Https => "https",
+    Sql => "sql",
+    Attestation => "attestation",
+    Ml => "ml",
+    Context => "context",
+    Notifier => "notifier",
+    /// Access control, request authentication, quorum and rate limiting
+    Access => "access"
+);
+
+impl Error {
+    /// Failure reported by the host, keeping its message as the source
+    pub fn host(subsystem: Subsystem, message: impl Into<String>, host_error: String) -> Error {
+        Error::new(subsystem, ErrorKind::Host, message).with_source(HostError(host_error))
+    }
+
+    pub fn invalid_input(subsystem: Subsystem, message: impl Into<String>) -> Error {
+        Error::new(subsystem, ErrorKind::InvalidInput, message)
+    }
+
+    pub fn not_found(subsystem: Subsystem, message: impl Into<String>) -> Error {
+        Error::new(subsystem, ErrorKind::NotFound, message)
+    }
+
+    pub fn with_source(mut self, error: impl std::error::Error + Send + Sync + 'static) -> Error {
+        *self.source_mut() = Some(Box::new(error));
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.parts().0
+    }
+
+    pub fn message(&self) -> &str {
+        self.parts().1
+    }
+
+    /// Message reported by the host, if this error comes from a host call
+    pub fn host_error(&self) -> Option<&str> {
+        self.parts()
+            .2
+            .and_then(|source| source.downcast_ref::<HostError>())
+            .map(|host_error| host_error.0.as_str())
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.kind() == ErrorKind::NotFound
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (kind, message, source) = self.parts();
+        write!(f, "{} {kind}: {message}", self.subsystem())?;
+        if let Some(source) = source {
+            write!(f, ": {source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.parts()
+            .2
+            .map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Conversion of foreign errors into an [`Error`] of a given subsystem and kind
+pub(crate) trait ResultExt<T> {
+    fn or_error(self, subsystem: Subsystem, kind: ErrorKind, message: &str) -> Result<T>;
+}
+
+impl<T, E: std::error::Error + Send + Sync + 'static> ResultExt<T> for std::result::Result<T, E> {
+    fn or_error(self, subsystem: Subsystem, kind: ErrorKind, message: &str) -> Result<T> {
+        self.map_err(|e| Error::new(subsystem, kind, message).with_source(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn errors_keep_their_subsystem_and_kind() {
+        for subsystem in [Subsystem::Ledger, Subsystem::Crypto, Subsystem::Access] {
+            let error = Error::new(subsystem, ErrorKind::Expired, "Too late");
+            assert_eq!(error.subsystem(), subsystem);
+            assert_eq!(error.kind(), ErrorKind::Expired);
+            assert_eq!(error.message(), "Too late");
+            assert!(error.source().is_none());
+        }
+        assert!(Error::not_found(Subsystem::Ledger, "Missing").is_not_found());
+        assert_eq!(
+            Error::invalid_input(Subsystem::Https, "Bad").kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn display_includes_the_host_error() {
+        let error = Error::host(
+            Subsystem::Ledger,
+            "Failed to read key 'k'",
+            "table is locked".to_string(),
+        );
+        assert_eq!(error.kind(), ErrorKind::Host);
+        assert_eq!(error.host_error(), Some("table is locked"));
+        assert_eq!(
+            error.to_string(),
+            "ledger host error: Failed to read key 'k': table is locked"
+        );
+        assert_eq!(error.source().unwrap().to_string(), "table is locked");
+
+        let error = Error::new(Subsystem::Access, ErrorKind::RateLimited, "Slow down");
+        assert_eq!(error.to_string(), "access rate limited: Slow down");
+        assert_eq!(error.host_error(), None);
+    }
+
+    #[test]
+    fn or_error_wraps_foreign_errors() {
+        let result: std::result::Result<u32, _> = "x".parse::<u32>();
+        let error = result
+            .or_error(
+                Subsystem::Context,
+                ErrorKind::Serialization,
+                "Invalid number",
+            )
+            .unwrap_err();
+        assert_eq!(error.subsystem(), Subsystem::Context);
+        assert_eq!(error.kind(), ErrorKind::Serialization);
+        assert_eq!(error.message(), "Invalid number");
+        assert_eq!(error.host_error(), None);
+        assert_eq!(
+            error.to_string(),
+            "context serialization error: Invalid number: invalid digit found in string"
+        );
+        assert!(error
+            .source()
+            .unwrap()
+            .downcast_ref::<std::num::ParseIntError>()
+            .is_some());
+
+        let ok: std::result::Result<u32, std::num::ParseIntError> = Ok(1);
+        assert_eq!(
+            ok.or_error(Subsystem::Context, ErrorKind::Serialization, "Unused")
+                .unwrap(),
+            1
+        );
+    }
+}
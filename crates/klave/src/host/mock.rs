@@ -0,0 +1,760 @@
+#[cfg(feature = "crypto")]
+use super::software_crypto::SoftwareCrypto;
+use super::Host;
+#[cfg(feature = "https")]
+use crate::https::{Body, BodyEncoding, HttpRequest, HttpResponse};
+#[cfg(feature = "https")]
+use http::{Request, Response};
+#[cfg(feature = "crypto")]
+use rand_core::{CryptoRng, RngCore};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+
+/// Entries of a ledger table, ordered by key
+pub type TableSnapshot = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Every ledger table, by name
+pub type LedgerSnapshot = BTreeMap<String, TableSnapshot>;
+
+#[cfg(feature = "https")]
+type HttpsResponder = dyn Fn(&Request<Vec<u8>>) -> Result<Response<Vec<u8>>, String>;
+
+/// Message sent to the client through one of the notification imports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "message", rename_all = "snake_case")]
+pub enum Notification {
+    /// Sent with `notify`
+    Message(String),
+    /// Sent with `notify-error`
+    Error(String),
+    /// Sent with `on-success-notify`, once the transaction is committed
+    OnSuccess(String),
+}
+
+/// In-memory host for native tests.
+///
+/// Ledger, context, notifications, HTTPS and random bytes are simulated, and crypto calls,
+/// simple ones included, run on a software backend seeded by the same generator as the random bytes;
+/// ML, SQL and attestation calls fail with an "unsupported" error,
+/// as do HTTPS and crypto calls when the `https` or `crypto` feature is disabled.
+pub struct MockHost {
+    ledger: RefCell<LedgerSnapshot>,
+    checkpoint: RefCell<Option<LedgerSnapshot>>,
+    context: RefCell<HashMap<String, String>>,
+    notifications: RefCell<Vec<Notification>>,
+    #[cfg(feature = "https")]
+    https_responder: RefCell<Option<Box<HttpsResponder>>>,
+    rng_state: Cell<u64>,
+    #[cfg(feature = "crypto")]
+    crypto: SoftwareCrypto,
+    user_queries: RefCell<Vec<String>>,
+    user_transactions: RefCell<Vec<String>>,
+    recording: Cell<bool>,
+    recorded_reads: RefCell<BTreeSet<(String, Vec<u8>)>>,
+    cancelled: Cell<bool>,
+}
+
+impl Default for MockHost {
+    fn default() -> Self {
+        MockHost::new()
+    }
+}
+
+impl MockHost {
+    pub fn new() -> MockHost {
+        MockHost::with_seed(0)
+    }
+
+    /// Mock host whose random bytes and generated keys are derived from `seed`
+    pub fn with_seed(seed: u64) -> MockHost {
+        MockHost {
+            ledger: RefCell::new(BTreeMap::new()),
+            checkpoint: RefCell::new(None),
+            context: RefCell::new(HashMap::new()),
+            notifications: RefCell::new(Vec::new()),
+            #[cfg(feature = "https")]
+            https_responder: RefCell::new(None),
+            rng_state: Cell::new(seed),
+            #[cfg(feature = "crypto")]
+            crypto: SoftwareCrypto::new(),
+            user_queries: RefCell::new(Vec::new()),
+            user_transactions: RefCell::new(Vec::new()),
+            recording: Cell::new(false),
+            recorded_reads: RefCell::new(BTreeSet::new()),
+            cancelled: Cell::new(false),
+        }
+    }
+
+    /// Set the value returned for a context parameter, see [`crate::context`]
+    pub fn set_context(&self, param: &str, value: &str) {
+        self.context
+            .borrow_mut()
+            .insert(param.to_string(), value.to_string());
+    }
+
+    pub fn remove_context(&self, param: &str) {
+        self.context.borrow_mut().remove(param);
+    }
+
+    /// Remove every context value
+    pub fn clear_context(&self) {
+        self.context.borrow_mut().clear();
+    }
+
+    /// Notifications sent so far, in order
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.notifications.borrow().clone()
+    }
+
+    /// Return the notifications sent so far and forget them
+    pub fn take_notifications(&self) -> Vec<Notification> {
+        self.notifications.take()
+    }
+
+    /// Answer the HTTPS queries with `responder`; without a responder they fail.
+    ///
+    /// Binary request bodies are passed lossily converted to text.
+    #[cfg(feature = "https")]
+    pub fn set_https_responder<F>(&self, responder: F)
+    where
+        F: Fn(&Request<String>) -> Result<Response<String>, String> + 'static,
+    {
+        self.set_https_bytes_responder(move |request| {
+            let request = request
+                .clone()
+                .map(|body| String::from_utf8_lossy(&body).into_owned());
+            Ok(responder(&request)?.map(String::into_bytes))
+        });
+    }
+
+    /// Answer the HTTPS queries with `responder`, working on raw bodies
+    #[cfg(feature = "https")]
+    pub fn set_https_bytes_responder<F>(&self, responder: F)
+    where
+        F: Fn(&Request<Vec<u8>>) -> Result<Response<Vec<u8>>, String> + 'static,
+    {
+        self.https_responder.replace(Some(Box::new(responder)));
+    }
+
+    /// Copy of the entries of a ledger table
+    pub fn table(&self, table: &str) -> TableSnapshot {
+        self.ledger.borrow().get(table).cloned().unwrap_or_default()
+    }
+
+    /// Names of the tables holding at least one entry
+    pub fn tables(&self) -> Vec<String> {
+        self.ledger
+            .borrow()
+            .iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Copy of every ledger table
+    pub fn ledger(&self) -> LedgerSnapshot {
+        self.ledger.borrow().clone()
+    }
+
+    /// Replace every ledger table, e.g. with a ledger saved from a previous run
+    pub fn set_ledger(&self, ledger: LedgerSnapshot) {
+        self.ledger.replace(ledger);
+    }
+
+    /// Start a transaction: the ledger writes that follow are undone by
+    /// [`MockHost::end_transaction`] unless it commits them
+    pub fn begin_transaction(&self) {
+        self.cancelled.set(false);
+        self.checkpoint.replace(Some(self.ledger()));
+    }
+
+    /// End the transaction started by [`MockHost::begin_transaction`], keeping its writes
+    /// if `commit` is set and `cancel-transaction` was not called. Returns whether they were kept.
+    pub fn end_transaction(&self, commit: bool) -> bool {
+        let checkpoint = self.checkpoint.take();
+        let committed = commit && !self.cancelled.get();
+        if let (false, Some(checkpoint)) = (committed, checkpoint) {
+            self.ledger.replace(checkpoint);
+        }
+        committed
+    }
+
+    /// Queries registered with `add-user-query`
+    pub fn user_queries(&self) -> Vec<String> {
+        self.user_queries.borrow().clone()
+    }
+
+    /// Transactions registered with `add-user-transaction`
+    pub fn user_transactions(&self) -> Vec<String> {
+        self.user_transactions.borrow().clone()
+    }
+
+    /// Whether `start-recording` was called more recently than `stop-recording`
+    pub fn is_recording(&self) -> bool {
+        self.recording.get()
+    }
+
+    /// Ledger entries read while recording, as `(table, key)`: the ones the host replays the query on
+    pub fn recorded_reads(&self) -> Vec<(String, Vec<u8>)> {
+        self.recorded_reads.borrow().iter().cloned().collect()
+    }
+
+    fn record_read(&self, table: &str, key: &[u8]) {
+        if self.recording.get() {
+            self.recorded_reads
+                .borrow_mut()
+                .insert((table.to_string(), key.to_vec()));
+        }
+    }
+
+    /// Whether `cancel-transaction` was called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    pub fn reset_cancelled(&self) {
+        self.cancelled.set(false);
+    }
+
+    fn rng(&self) -> MockRng<'_> {
+        MockRng(&self.rng_state)
+    }
+
+    #[cfg(feature = "https")]
+    fn respond(&self, request: &str) -> Result<String, String> {
+        let responder = self.https_responder.borrow();
+        let Some(responder) = responder.as_ref() else {
+            return Err("No https responder set on the mock host".to_string());
+        };
+        let http_request: HttpRequest<String> =
+            serde_json::from_str(request).map_err(|e| e.to_string())?;
+        let mut builder = Request::builder()
+            .method(http_request.method.as_str())
+            .uri(format!(
+                "https://{}:{}{}",
+                http_request.hostname, http_request.port, http_request.path
+            ));
+        for header in &http_request.headers {
+            if let (Some(name), Some(value)) = (header.first(), header.get(1)) {
+                builder = builder.header(name, value);
+            }
+        }
+        let body = Vec::<u8>::decode(http_request.body, http_request.body_encoding)
+            .map_err(|e| e.to_string())?;
+        let request = builder.body(body).map_err(|e| e.to_string())?;
+
+        let response = responder(&request)?;
+        let http_response = HttpResponse {
+            status_code: i32::from(response.status().as_u16()),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    vec![
+                        name.as_str().to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    ]
+                })
+                .collect(),
+            body: match http_request.response_body_encoding {
+                BodyEncoding::Utf8 => String::from_utf8_lossy(response.body()).into_owned(),
+                BodyEncoding::Base64 => response.body().encode(),
+            },
+            body_encoding: http_request.response_body_encoding,
+        };
+        serde_json::to_string(&http_response).map_err(|e| e.to_string())
+    }
+}
+
+/// Install `host` on the current thread and return a handle to script and inspect it
+pub fn install(host: MockHost) -> Rc<MockHost> {
+    let host = Rc::new(host);
+    super::install(host.clone());
+    host
+}
+
+/// SplitMix64 generator over the state of a [`MockHost`].
+/// Marked as cryptographic so that the software crypto backend can use it; it is not.
+struct MockRng<'a>(&'a Cell<u64>);
+
+impl MockRng<'_> {
+    fn next(&mut self) -> u64 {
+        let state = self.0.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.0.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl RngCore for MockRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        self.next() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl CryptoRng for MockRng<'_> {}
+
+/// Run `$body` on the software crypto backend, or fail as unsupported without the `crypto` feature
+macro_rules! with_crypto {
+    ($self:ident, $function:literal, |$crypto:ident| $body:expr) => {{
+        #[cfg(feature = "crypto")]
+        return {
+            let $crypto = &$self.crypto;
+            $body
+        };
+        #[cfg(not(feature = "crypto"))]
+        unsupported($function)
+    }};
+}
+
+fn unsupported<T>(function: &str) -> Result<T, String> {
+    Err(format!("{function} is not supported by the mock host"))
+}
+
+#[cfg_attr(
+    not(all(feature = "crypto", feature = "https")),
+    allow(unused_variables)
+)]
+impl Host for MockHost {
+    fn add_user_query(&self, query_name: &str) {
+        self.user_queries.borrow_mut().push(query_name.to_string());
+    }
+
+    fn add_user_transaction(&self, transaction_name: &str) {
+        self.user_transactions
+            .borrow_mut()
+            .push(transaction_name.to_string());
+    }
+
+    fn notify(&self, msg: &str) {
+        self.notifications
+            .borrow_mut()
+            .push(Notification::Message(msg.to_string()));
+    }
+
+    fn notify_error(&self, msg: &str) {
+        self.notifications
+            .borrow_mut()
+            .push(Notification::Error(msg.to_string()));
+    }
+
+    fn on_success_notify(&self, msg: &str) {
+        self.notifications
+            .borrow_mut()
+            .push(Notification::OnSuccess(msg.to_string()));
+    }
+
+    fn query_context(&self, param: &str) -> Result<String, String> {
+        match self.context.borrow().get(param) {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("Unknown context parameter '{param}'")),
+        }
+    }
+
+    fn read_ledger(&self, table: &str, key: &[u8]) -> Result<Vec<u8>, String> {
+        self.record_read(table, key);
+        match self.ledger.borrow().get(table).and_then(|t| t.get(key)) {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("Key not found in table '{table}'")),
+        }
+    }
+
+    fn list_keys_from_ledger(&self, table: &str) -> Result<String, String> {
+        let keys = self
+            .ledger
+            .borrow()
+            .get(table)
+            .map(|t| t.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        Ok(serde_json::json!({ "keys": keys }).to_string())
+    }
+
+    fn key_exists_in_ledger(&self, table: &str, key: &[u8]) -> Result<bool, String> {
+        self.record_read(table, key);
+        Ok(self
+            .ledger
+            .borrow()
+            .get(table)
+            .is_some_and(|t| t.contains_key(key)))
+    }
+
+    fn write_ledger(&self, table: &str, key: &[u8], value: &[u8]) -> Result<(), String> {
+        self.ledger
+            .borrow_mut()
+            .entry(table.to_string())
+            .or_default()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove_from_ledger(&self, table: &str, key: &[u8]) -> Result<(), String> {
+        if let Some(t) = self.ledger.borrow_mut().get_mut(table) {
+            t.remove(key);
+        }
+        Ok(())
+    }
+
+    fn load_lightgbm_model(&self, _name: &str, _model: &str) -> Result<(), String> {
+        unsupported("load-lightgbm-model")
+    }
+
+    fn unload_lightgbm_model(&self, _name: &str) -> Result<(), String> {
+        unsupported("unload-lightgbm-model")
+    }
+
+    fn infer_from_lightgbm_model(
+        &self,
+        _name: &str,
+        _data: &[f64],
+        _nb_outputs: i32,
+    ) -> Result<Vec<f64>, String> {
+        unsupported("infer-from-lightgbm-model")
+    }
+
+    fn key_exists(&self, key_name: &str) -> Result<bool, String> {
+        with_crypto!(self, "key-exists", |crypto| crypto.key_exists(key_name))
+    }
+
+    fn generate_key(
+        &self,
+        key_name: &str,
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        with_crypto!(self, "generate-key", |crypto| crypto.generate_key(
+            &mut self.rng(),
+            key_name,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        ))
+    }
+
+    fn import_key(
+        &self,
+        key_name: &str,
+        key_format: i32,
+        key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        with_crypto!(self, "import-key", |crypto| crypto.import_key(
+            &mut self.rng(),
+            key_name,
+            key_format,
+            key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        ))
+    }
+
+    fn export_key(&self, key_name: &str, key_format: i32) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "export-key", |crypto| crypto
+            .export_key(key_name, key_format))
+    }
+
+    fn get_public_key(&self, key_name: &str) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "get-public-key", |crypto| crypto
+            .get_public_key(key_name))
+    }
+
+    fn get_public_key_as_cryptokey(&self, key_name: &str) -> Result<String, String> {
+        with_crypto!(self, "get-public-key-as-cryptokey", |crypto| crypto
+            .get_public_key_as_cryptokey(&mut self.rng(), key_name))
+    }
+
+    fn derive_key(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        derived_key_algorithm: i32,
+        derived_key_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        with_crypto!(self, "derive-key", |crypto| crypto.derive_key(
+            &mut self.rng(),
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            derived_key_algorithm,
+            derived_key_metadata,
+            extractable,
+            usages,
+        ))
+    }
+
+    fn derive_bits(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        length: i32,
+    ) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "derive-bits", |crypto| crypto.derive_bits(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            length,
+        ))
+    }
+
+    fn encrypt(
+        &self,
+        key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+        plain_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "encrypt", |crypto| crypto.encrypt(
+            &mut self.rng(),
+            key_name,
+            encrypt_algo_id,
+            encrypt_metadata,
+            plain_txt,
+        ))
+    }
+
+    fn decrypt(
+        &self,
+        key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "decrypt", |crypto| crypto.decrypt(
+            key_name,
+            decrypt_algo_id,
+            decrypt_metadata,
+            cipher_txt
+        ))
+    }
+
+    fn sign(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "sign", |crypto| crypto.sign(
+            &mut self.rng(),
+            key_name,
+            sign_algo_id,
+            sign_metadata,
+            txt
+        ))
+    }
+
+    fn verify(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, String> {
+        with_crypto!(self, "verify", |crypto| crypto.verify(
+            key_name,
+            sign_algo_id,
+            sign_metadata,
+            txt,
+            signature
+        ))
+    }
+
+    fn digest(&self, algo_id: i32, hash_metadata: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "digest", |crypto| crypto.digest(
+            algo_id,
+            hash_metadata,
+            txt
+        ))
+    }
+
+    fn unwrap_key(
+        &self,
+        decrypt_key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        key_name_to_import: &str,
+        key_format: i32,
+        wrapped_key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        with_crypto!(self, "unwrap-key", |crypto| crypto.unwrap_key(
+            &mut self.rng(),
+            decrypt_key_name,
+            decrypt_algo_id,
+            decrypt_metadata,
+            key_name_to_import,
+            key_format,
+            wrapped_key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        ))
+    }
+
+    fn wrap_key(
+        &self,
+        key_name_to_export: &str,
+        key_format: i32,
+        encrypt_key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+    ) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "wrap-key", |crypto| crypto.wrap_key(
+            &mut self.rng(),
+            key_name_to_export,
+            key_format,
+            encrypt_key_name,
+            encrypt_algo_id,
+            encrypt_metadata,
+        ))
+    }
+
+    fn save_key(&self, key_name: &str) -> Result<(), String> {
+        with_crypto!(self, "save-key", |crypto| crypto.save_key(key_name))
+    }
+
+    fn persist_key(&self, key_persist_params: &str) -> Result<(), String> {
+        with_crypto!(self, "persist-key", |crypto| crypto
+            .persist_key(key_persist_params))
+    }
+
+    fn load_key(&self, key_name: &str) -> Result<String, String> {
+        with_crypto!(self, "load-key", |crypto| crypto.load_key(key_name))
+    }
+
+    fn delete_key(&self, key_name: &str) -> Result<(), String> {
+        with_crypto!(self, "delete-key", |crypto| crypto.delete_key(key_name))
+    }
+
+    fn generate_simple_encryption_key(&self, key_name: &str) -> Result<(), String> {
+        with_crypto!(self, "generate-simple-encryption-key", |crypto| crypto
+            .generate_simple_encryption_key(&mut self.rng(), key_name))
+    }
+
+    fn generate_simple_signing_key(&self, key_name: &str) -> Result<(), String> {
+        with_crypto!(self, "generate-simple-signing-key", |crypto| crypto
+            .generate_simple_signing_key(&mut self.rng(), key_name))
+    }
+
+    fn simple_encrypt(&self, key_name: &str, plain_txt: &[u8]) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "simple-encrypt", |crypto| crypto.simple_encrypt(
+            &mut self.rng(),
+            key_name,
+            plain_txt
+        ))
+    }
+
+    fn simple_decrypt(&self, key_name: &str, cipher_txt: &[u8]) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "simple-decrypt", |crypto| crypto
+            .simple_decrypt(key_name, cipher_txt))
+    }
+
+    fn simple_sign(&self, key_name: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "simple-sign", |crypto| crypto.simple_sign(
+            &mut self.rng(),
+            key_name,
+            txt
+        ))
+    }
+
+    fn simple_verify(&self, key_name: &str, txt: &[u8], signature: &[u8]) -> Result<bool, String> {
+        with_crypto!(self, "simple-verify", |crypto| crypto
+            .simple_verify(key_name, txt, signature))
+    }
+
+    fn simple_digest(&self, txt: &[u8]) -> Result<Vec<u8>, String> {
+        with_crypto!(self, "simple-digest", |crypto| crypto.simple_digest(txt))
+    }
+
+    fn get_random_bytes(&self, len: i32) -> Result<Vec<u8>, String> {
+        let len = usize::try_from(len).map_err(|_| format!("Invalid length {len}"))?;
+        let mut bytes = vec![0u8; len];
+        self.rng().fill(&mut bytes);
+        Ok(bytes)
+    }
+
+    fn https_query(&self, request: &str) -> Result<String, String> {
+        #[cfg(feature = "https")]
+        return self.respond(request);
+        #[cfg(not(feature = "https"))]
+        unsupported("https-query")
+    }
+
+    fn get_quote(&self, _challenge: &[u8]) -> Result<Vec<u8>, String> {
+        unsupported("get-quote")
+    }
+
+    fn verify_quote(&self, _current_time: i64, _quote_binary: &[u8]) -> Result<String, String> {
+        unsupported("verify-quote")
+    }
+
+    fn parse_quote(&self, _quote: &[u8]) -> Result<String, String> {
+        unsupported("parse-quote")
+    }
+
+    fn connection_open(&self, _uri: &str) -> Result<String, String> {
+        unsupported("connection-open")
+    }
+
+    fn sql_query(&self, _connection: &str, _query: &str) -> Result<String, String> {
+        unsupported("sql-query")
+    }
+
+    fn sql_exec(&self, _connection: &str, _command: &str) -> Result<String, String> {
+        unsupported("sql-exec")
+    }
+
+    fn start_recording(&self) {
+        self.recording.set(true);
+    }
+
+    fn stop_recording(&self) {
+        self.recording.set(false);
+    }
+
+    fn cancel_transaction(&self) {
+        self.cancelled.set(true);
+    }
+}
@@ -0,0 +1,401 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mock;
+#[allow(dead_code, clippy::too_many_arguments)]
+pub(crate) mod sdk;
+#[cfg(all(not(target_arch = "wasm32"), feature = "crypto"))]
+pub mod software_crypto;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
+#[cfg(feature = "crypto")]
+use crate::crypto::subtle_idl_v2::{
+    DerivationAlgorithm, DerivedKeyAlgorithm, EncryptionAlgorithm, KeyAlgorithm, KeyFormat,
+    KeyUsage, ShaAlgorithm, SigningAlgorithm, WrappingAlgorithm,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{cell::RefCell, rc::Rc};
+
+/// Functions imported from the Klave host, one per `klave:sdk/sdk` import.
+/// Wasm builds always call the Klave runtime; native builds use the host installed
+/// on the current thread, a [`mock::MockHost`] by default.
+#[allow(clippy::too_many_arguments)]
+pub trait Host {
+    // Routes, only called while registering routes
+    fn add_user_query(&self, query_name: &str);
+    fn add_user_transaction(&self, transaction_name: &str);
+
+    // Notifications
+    fn notify(&self, msg: &str);
+    fn notify_error(&self, msg: &str);
+    fn on_success_notify(&self, msg: &str);
+
+    // Context
+    fn query_context(&self, param: &str) -> Result<String, String>;
+
+    // Ledger
+    fn read_ledger(&self, table: &str, key: &[u8]) -> Result<Vec<u8>, String>;
+    fn list_keys_from_ledger(&self, table: &str) -> Result<String, String>;
+    fn key_exists_in_ledger(&self, table: &str, key: &[u8]) -> Result<bool, String>;
+    fn write_ledger(&self, table: &str, key: &[u8], value: &[u8]) -> Result<(), String>;
+    fn remove_from_ledger(&self, table: &str, key: &[u8]) -> Result<(), String>;
+
+    // ML inference
+    fn load_lightgbm_model(&self, name: &str, model: &str) -> Result<(), String>;
+    fn unload_lightgbm_model(&self, name: &str) -> Result<(), String>;
+    fn infer_from_lightgbm_model(
+        &self,
+        name: &str,
+        data: &[f64],
+        nb_outputs: i32,
+    ) -> Result<Vec<f64>, String>;
+
+    // Subtle crypto
+    fn key_exists(&self, key_name: &str) -> Result<bool, String>;
+    fn generate_key(
+        &self,
+        key_name: &str,
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String>;
+    fn import_key(
+        &self,
+        key_name: &str,
+        key_format: i32,
+        key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String>;
+    fn export_key(&self, key_name: &str, key_format: i32) -> Result<Vec<u8>, String>;
+    fn get_public_key(&self, key_name: &str) -> Result<Vec<u8>, String>;
+    fn get_public_key_as_cryptokey(&self, key_name: &str) -> Result<String, String>;
+    fn derive_key(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        derived_key_algorithm: i32,
+        derived_key_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String>;
+    fn derive_bits(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        length: i32,
+    ) -> Result<Vec<u8>, String>;
+    fn encrypt(
+        &self,
+        key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+        plain_txt: &[u8],
+    ) -> Result<Vec<u8>, String>;
+    fn decrypt(
+        &self,
+        key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, String>;
+    fn sign(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+    ) -> Result<Vec<u8>, String>;
+    fn verify(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, String>;
+    fn digest(&self, algo_id: i32, hash_metadata: &str, txt: &[u8]) -> Result<Vec<u8>, String>;
+    fn unwrap_key(
+        &self,
+        decrypt_key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        key_name_to_import: &str,
+        key_format: i32,
+        wrapped_key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String>;
+    fn wrap_key(
+        &self,
+        key_name_to_export: &str,
+        key_format: i32,
+        encrypt_key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+    ) -> Result<Vec<u8>, String>;
+
+    // Typed subtle crypto, one per `klave:sdk/sdk-v2` import. By default they go through the
+    // string-based calls above, which every host provides.
+    #[cfg(feature = "crypto")]
+    fn generate_key_v2(
+        &self,
+        key_name: &str,
+        algorithm: &KeyAlgorithm,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Result<String, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.generate_key(
+            key_name,
+            algo_id,
+            &algo_metadata,
+            extractable as i32,
+            &usage_ids(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto")]
+    fn import_key_v2(
+        &self,
+        key_name: &str,
+        key_format: KeyFormat,
+        key_data: &[u8],
+        algorithm: &KeyAlgorithm,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Result<String, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.import_key(
+            key_name,
+            key_format.to_v1(),
+            key_data,
+            algo_id,
+            &algo_metadata,
+            extractable as i32,
+            &usage_ids(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto")]
+    fn export_key_v2(&self, key_name: &str, key_format: KeyFormat) -> Result<Vec<u8>, String> {
+        self.export_key(key_name, key_format.to_v1())
+    }
+
+    #[cfg(feature = "crypto")]
+    fn derive_key_v2(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: &DerivationAlgorithm,
+        derived_key_algorithm: &DerivedKeyAlgorithm,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Result<String, String> {
+        let (derivation_id, derivation_metadata) = derivation_algorithm.to_v1();
+        let (derived_key_id, derived_key_metadata) = derived_key_algorithm.to_v1();
+        self.derive_key(
+            base_key_name,
+            derivation_id,
+            &derivation_metadata,
+            derived_key_id,
+            &derived_key_metadata,
+            extractable as i32,
+            &usage_ids(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto")]
+    fn derive_bits_v2(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: &DerivationAlgorithm,
+        length: u32,
+    ) -> Result<Vec<u8>, String> {
+        let (derivation_id, derivation_metadata) = derivation_algorithm.to_v1();
+        self.derive_bits(
+            base_key_name,
+            derivation_id,
+            &derivation_metadata,
+            length as i32,
+        )
+    }
+
+    #[cfg(feature = "crypto")]
+    fn encrypt_v2(
+        &self,
+        key_name: &str,
+        algorithm: &EncryptionAlgorithm,
+        plain_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.encrypt(key_name, algo_id, &algo_metadata, plain_txt)
+    }
+
+    #[cfg(feature = "crypto")]
+    fn decrypt_v2(
+        &self,
+        key_name: &str,
+        algorithm: &EncryptionAlgorithm,
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.decrypt(key_name, algo_id, &algo_metadata, cipher_txt)
+    }
+
+    #[cfg(feature = "crypto")]
+    fn sign_v2(
+        &self,
+        key_name: &str,
+        algorithm: &SigningAlgorithm,
+        txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.sign(key_name, algo_id, &algo_metadata, txt)
+    }
+
+    #[cfg(feature = "crypto")]
+    fn verify_v2(
+        &self,
+        key_name: &str,
+        algorithm: &SigningAlgorithm,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, String> {
+        let (algo_id, algo_metadata) = algorithm.to_v1();
+        self.verify(key_name, algo_id, &algo_metadata, txt, signature)
+    }
+
+    #[cfg(feature = "crypto")]
+    fn digest_v2(&self, algorithm: ShaAlgorithm, txt: &[u8]) -> Result<Vec<u8>, String> {
+        let (algo_id, hash_metadata) = algorithm.to_v1();
+        self.digest(algo_id, &hash_metadata, txt)
+    }
+
+    #[cfg(feature = "crypto")]
+    fn unwrap_key_v2(
+        &self,
+        decrypt_key_name: &str,
+        algorithm: &WrappingAlgorithm,
+        key_name_to_import: &str,
+        key_format: KeyFormat,
+        wrapped_key_data: &[u8],
+        key_algorithm: &KeyAlgorithm,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Result<String, String> {
+        let (decrypt_algo_id, decrypt_metadata) = algorithm.to_v1();
+        let (algo_id, algo_metadata) = key_algorithm.to_v1();
+        self.unwrap_key(
+            decrypt_key_name,
+            decrypt_algo_id,
+            &decrypt_metadata,
+            key_name_to_import,
+            key_format.to_v1(),
+            wrapped_key_data,
+            algo_id,
+            &algo_metadata,
+            extractable as i32,
+            &usage_ids(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto")]
+    fn wrap_key_v2(
+        &self,
+        key_name_to_export: &str,
+        key_format: KeyFormat,
+        encrypt_key_name: &str,
+        algorithm: &WrappingAlgorithm,
+    ) -> Result<Vec<u8>, String> {
+        let (encrypt_algo_id, encrypt_metadata) = algorithm.to_v1();
+        self.wrap_key(
+            key_name_to_export,
+            key_format.to_v1(),
+            encrypt_key_name,
+            encrypt_algo_id,
+            &encrypt_metadata,
+        )
+    }
+
+    // Crypto key management
+    fn save_key(&self, key_name: &str) -> Result<(), String>;
+    fn persist_key(&self, key_persist_params: &str) -> Result<(), String>;
+    fn load_key(&self, key_name: &str) -> Result<String, String>;
+    fn delete_key(&self, key_name: &str) -> Result<(), String>;
+
+    // Simple crypto
+    fn generate_simple_encryption_key(&self, key_name: &str) -> Result<(), String>;
+    fn generate_simple_signing_key(&self, key_name: &str) -> Result<(), String>;
+    fn simple_encrypt(&self, key_name: &str, plain_txt: &[u8]) -> Result<Vec<u8>, String>;
+    fn simple_decrypt(&self, key_name: &str, cipher_txt: &[u8]) -> Result<Vec<u8>, String>;
+    fn simple_sign(&self, key_name: &str, txt: &[u8]) -> Result<Vec<u8>, String>;
+    fn simple_verify(&self, key_name: &str, txt: &[u8], signature: &[u8]) -> Result<bool, String>;
+    fn simple_digest(&self, txt: &[u8]) -> Result<Vec<u8>, String>;
+
+    // Random
+    fn get_random_bytes(&self, len: i32) -> Result<Vec<u8>, String>;
+
+    // HTTPS
+    fn https_query(&self, request: &str) -> Result<String, String>;
+
+    // Attestation
+    fn get_quote(&self, challenge: &[u8]) -> Result<Vec<u8>, String>;
+    fn verify_quote(&self, current_time: i64, quote_binary: &[u8]) -> Result<String, String>;
+    fn parse_quote(&self, quote: &[u8]) -> Result<String, String>;
+
+    // PostgreSQL
+    fn connection_open(&self, uri: &str) -> Result<String, String>;
+    fn sql_query(&self, connection: &str, query: &str) -> Result<String, String>;
+    fn sql_exec(&self, connection: &str, command: &str) -> Result<String, String>;
+
+    // Subscription
+    fn start_recording(&self);
+    fn stop_recording(&self);
+
+    // Transaction
+    fn cancel_transaction(&self);
+}
+
+#[cfg(feature = "crypto")]
+fn usage_ids(usages: &[KeyUsage]) -> Vec<u8> {
+    usages.iter().map(|usage| usage.to_v1()).collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn with<R>(f: impl FnOnce(&dyn Host) -> R) -> R {
+    f(&wasm::WasmHost)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<dyn Host>>> = const { RefCell::new(None) };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn with<R>(f: impl FnOnce(&dyn Host) -> R) -> R {
+    let host = CURRENT.with(|current| {
+        current
+            .borrow_mut()
+            .get_or_insert_with(|| Rc::new(mock::MockHost::new()))
+            .clone()
+    });
+    f(host.as_ref())
+}
+
+/// Install the host used by the SDK on the current thread and return the previous one
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install(host: Rc<dyn Host>) -> Option<Rc<dyn Host>> {
+    CURRENT.with(|current| current.borrow_mut().replace(host))
+}
+
+/// Remove the host of the current thread; the next host call installs a fresh [`mock::MockHost`]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn uninstall() -> Option<Rc<dyn Host>> {
+    CURRENT.with(|current| current.borrow_mut().take())
+}
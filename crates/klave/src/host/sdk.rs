@@ -0,0 +1,469 @@
+#[cfg(feature = "crypto")]
+use crate::crypto::subtle_idl_v2::{
+    DerivationAlgorithm, DerivedKeyAlgorithm, EncryptionAlgorithm, KeyAlgorithm, KeyFormat,
+    KeyUsage, ShaAlgorithm, SigningAlgorithm, WrappingAlgorithm,
+};
+
+pub fn add_user_query(query_name: &str) {
+    super::with(|host| host.add_user_query(query_name))
+}
+
+pub fn add_user_transaction(transaction_name: &str) {
+    super::with(|host| host.add_user_transaction(transaction_name))
+}
+
+pub fn notify(msg: &str) {
+    super::with(|host| host.notify(msg))
+}
+
+pub fn notify_error(msg: &str) {
+    super::with(|host| host.notify_error(msg))
+}
+
+pub fn on_success_notify(msg: &str) {
+    super::with(|host| host.on_success_notify(msg))
+}
+
+pub fn query_context(param: &str) -> Result<String, String> {
+    super::with(|host| host.query_context(param))
+}
+
+pub fn read_ledger(table: &str, key: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.read_ledger(table, key))
+}
+
+pub fn list_keys_from_ledger(table: &str) -> Result<String, String> {
+    super::with(|host| host.list_keys_from_ledger(table))
+}
+
+pub fn key_exists_in_ledger(table: &str, key: &[u8]) -> Result<bool, String> {
+    super::with(|host| host.key_exists_in_ledger(table, key))
+}
+
+pub fn write_ledger(table: &str, key: &[u8], value: &[u8]) -> Result<(), String> {
+    super::with(|host| host.write_ledger(table, key, value))
+}
+
+pub fn remove_from_ledger(table: &str, key: &[u8]) -> Result<(), String> {
+    super::with(|host| host.remove_from_ledger(table, key))
+}
+
+pub fn load_lightgbm_model(name: &str, model: &str) -> Result<(), String> {
+    super::with(|host| host.load_lightgbm_model(name, model))
+}
+
+pub fn unload_lightgbm_model(name: &str) -> Result<(), String> {
+    super::with(|host| host.unload_lightgbm_model(name))
+}
+
+pub fn infer_from_lightgbm_model(
+    name: &str,
+    data: &[f64],
+    nb_outputs: i32,
+) -> Result<Vec<f64>, String> {
+    super::with(|host| host.infer_from_lightgbm_model(name, data, nb_outputs))
+}
+
+pub fn key_exists(key_name: &str) -> Result<bool, String> {
+    super::with(|host| host.key_exists(key_name))
+}
+
+pub fn generate_key(
+    key_name: &str,
+    algorithm: i32,
+    algo_metadata: &str,
+    extractable: i32,
+    usages: &[u8],
+) -> Result<String, String> {
+    super::with(|host| host.generate_key(key_name, algorithm, algo_metadata, extractable, usages))
+}
+
+pub fn import_key(
+    key_name: &str,
+    key_format: i32,
+    key_data: &[u8],
+    algorithm: i32,
+    algo_metadata: &str,
+    extractable: i32,
+    usages: &[u8],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.import_key(
+            key_name,
+            key_format,
+            key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        )
+    })
+}
+
+pub fn export_key(key_name: &str, key_format: i32) -> Result<Vec<u8>, String> {
+    super::with(|host| host.export_key(key_name, key_format))
+}
+
+pub fn get_public_key(key_name: &str) -> Result<Vec<u8>, String> {
+    super::with(|host| host.get_public_key(key_name))
+}
+
+pub fn get_public_key_as_cryptokey(key_name: &str) -> Result<String, String> {
+    super::with(|host| host.get_public_key_as_cryptokey(key_name))
+}
+
+pub fn derive_key(
+    base_key_name: &str,
+    derivation_algorithm: i32,
+    derivation_metadata: &str,
+    derived_key_algorithm: i32,
+    derived_key_metadata: &str,
+    extractable: i32,
+    usages: &[u8],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.derive_key(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            derived_key_algorithm,
+            derived_key_metadata,
+            extractable,
+            usages,
+        )
+    })
+}
+
+pub fn derive_bits(
+    base_key_name: &str,
+    derivation_algorithm: i32,
+    derivation_metadata: &str,
+    length: i32,
+) -> Result<Vec<u8>, String> {
+    super::with(|host| {
+        host.derive_bits(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            length,
+        )
+    })
+}
+
+pub fn encrypt(
+    key_name: &str,
+    encrypt_algo_id: i32,
+    encrypt_metadata: &str,
+    plain_txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.encrypt(key_name, encrypt_algo_id, encrypt_metadata, plain_txt))
+}
+
+pub fn decrypt(
+    key_name: &str,
+    decrypt_algo_id: i32,
+    decrypt_metadata: &str,
+    cipher_txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.decrypt(key_name, decrypt_algo_id, decrypt_metadata, cipher_txt))
+}
+
+pub fn sign(
+    key_name: &str,
+    sign_algo_id: i32,
+    sign_metadata: &str,
+    txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.sign(key_name, sign_algo_id, sign_metadata, txt))
+}
+
+pub fn verify(
+    key_name: &str,
+    sign_algo_id: i32,
+    sign_metadata: &str,
+    txt: &[u8],
+    signature: &[u8],
+) -> Result<bool, String> {
+    super::with(|host| host.verify(key_name, sign_algo_id, sign_metadata, txt, signature))
+}
+
+pub fn digest(algo_id: i32, hash_metadata: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.digest(algo_id, hash_metadata, txt))
+}
+
+pub fn unwrap_key(
+    decrypt_key_name: &str,
+    decrypt_algo_id: i32,
+    decrypt_metadata: &str,
+    key_name_to_import: &str,
+    key_format: i32,
+    wrapped_key_data: &[u8],
+    algorithm: i32,
+    algo_metadata: &str,
+    extractable: i32,
+    usages: &[u8],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.unwrap_key(
+            decrypt_key_name,
+            decrypt_algo_id,
+            decrypt_metadata,
+            key_name_to_import,
+            key_format,
+            wrapped_key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        )
+    })
+}
+
+pub fn wrap_key(
+    key_name_to_export: &str,
+    key_format: i32,
+    encrypt_key_name: &str,
+    encrypt_algo_id: i32,
+    encrypt_metadata: &str,
+) -> Result<Vec<u8>, String> {
+    super::with(|host| {
+        host.wrap_key(
+            key_name_to_export,
+            key_format,
+            encrypt_key_name,
+            encrypt_algo_id,
+            encrypt_metadata,
+        )
+    })
+}
+
+#[cfg(feature = "crypto")]
+pub fn generate_key_v2(
+    key_name: &str,
+    algorithm: &KeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Result<String, String> {
+    super::with(|host| host.generate_key_v2(key_name, algorithm, extractable, usages))
+}
+
+#[cfg(feature = "crypto")]
+pub fn import_key_v2(
+    key_name: &str,
+    key_format: KeyFormat,
+    key_data: &[u8],
+    algorithm: &KeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.import_key_v2(
+            key_name,
+            key_format,
+            key_data,
+            algorithm,
+            extractable,
+            usages,
+        )
+    })
+}
+
+#[cfg(feature = "crypto")]
+pub fn export_key_v2(key_name: &str, key_format: KeyFormat) -> Result<Vec<u8>, String> {
+    super::with(|host| host.export_key_v2(key_name, key_format))
+}
+
+#[cfg(feature = "crypto")]
+pub fn derive_key_v2(
+    base_key_name: &str,
+    derivation_algorithm: &DerivationAlgorithm,
+    derived_key_algorithm: &DerivedKeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.derive_key_v2(
+            base_key_name,
+            derivation_algorithm,
+            derived_key_algorithm,
+            extractable,
+            usages,
+        )
+    })
+}
+
+#[cfg(feature = "crypto")]
+pub fn derive_bits_v2(
+    base_key_name: &str,
+    derivation_algorithm: &DerivationAlgorithm,
+    length: u32,
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.derive_bits_v2(base_key_name, derivation_algorithm, length))
+}
+
+#[cfg(feature = "crypto")]
+pub fn encrypt_v2(
+    key_name: &str,
+    algorithm: &EncryptionAlgorithm,
+    plain_txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.encrypt_v2(key_name, algorithm, plain_txt))
+}
+
+#[cfg(feature = "crypto")]
+pub fn decrypt_v2(
+    key_name: &str,
+    algorithm: &EncryptionAlgorithm,
+    cipher_txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.decrypt_v2(key_name, algorithm, cipher_txt))
+}
+
+#[cfg(feature = "crypto")]
+pub fn sign_v2(
+    key_name: &str,
+    algorithm: &SigningAlgorithm,
+    txt: &[u8],
+) -> Result<Vec<u8>, String> {
+    super::with(|host| host.sign_v2(key_name, algorithm, txt))
+}
+
+#[cfg(feature = "crypto")]
+pub fn verify_v2(
+    key_name: &str,
+    algorithm: &SigningAlgorithm,
+    txt: &[u8],
+    signature: &[u8],
+) -> Result<bool, String> {
+    super::with(|host| host.verify_v2(key_name, algorithm, txt, signature))
+}
+
+#[cfg(feature = "crypto")]
+pub fn digest_v2(algorithm: ShaAlgorithm, txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.digest_v2(algorithm, txt))
+}
+
+#[cfg(feature = "crypto")]
+pub fn unwrap_key_v2(
+    decrypt_key_name: &str,
+    algorithm: &WrappingAlgorithm,
+    key_name_to_import: &str,
+    key_format: KeyFormat,
+    wrapped_key_data: &[u8],
+    key_algorithm: &KeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Result<String, String> {
+    super::with(|host| {
+        host.unwrap_key_v2(
+            decrypt_key_name,
+            algorithm,
+            key_name_to_import,
+            key_format,
+            wrapped_key_data,
+            key_algorithm,
+            extractable,
+            usages,
+        )
+    })
+}
+
+#[cfg(feature = "crypto")]
+pub fn wrap_key_v2(
+    key_name_to_export: &str,
+    key_format: KeyFormat,
+    encrypt_key_name: &str,
+    algorithm: &WrappingAlgorithm,
+) -> Result<Vec<u8>, String> {
+    super::with(|host| {
+        host.wrap_key_v2(key_name_to_export, key_format, encrypt_key_name, algorithm)
+    })
+}
+
+pub fn save_key(key_name: &str) -> Result<(), String> {
+    super::with(|host| host.save_key(key_name))
+}
+
+pub fn persist_key(key_persist_params: &str) -> Result<(), String> {
+    super::with(|host| host.persist_key(key_persist_params))
+}
+
+pub fn load_key(key_name: &str) -> Result<String, String> {
+    super::with(|host| host.load_key(key_name))
+}
+
+pub fn delete_key(key_name: &str) -> Result<(), String> {
+    super::with(|host| host.delete_key(key_name))
+}
+
+pub fn generate_simple_encryption_key(key_name: &str) -> Result<(), String> {
+    super::with(|host| host.generate_simple_encryption_key(key_name))
+}
+
+pub fn generate_simple_signing_key(key_name: &str) -> Result<(), String> {
+    super::with(|host| host.generate_simple_signing_key(key_name))
+}
+
+pub fn simple_encrypt(key_name: &str, plain_txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.simple_encrypt(key_name, plain_txt))
+}
+
+pub fn simple_decrypt(key_name: &str, cipher_txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.simple_decrypt(key_name, cipher_txt))
+}
+
+pub fn simple_sign(key_name: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.simple_sign(key_name, txt))
+}
+
+pub fn simple_verify(key_name: &str, txt: &[u8], signature: &[u8]) -> Result<bool, String> {
+    super::with(|host| host.simple_verify(key_name, txt, signature))
+}
+
+pub fn simple_digest(txt: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.simple_digest(txt))
+}
+
+pub fn get_random_bytes(len: i32) -> Result<Vec<u8>, String> {
+    super::with(|host| host.get_random_bytes(len))
+}
+
+pub fn https_query(request: &str) -> Result<String, String> {
+    super::with(|host| host.https_query(request))
+}
+
+pub fn get_quote(challenge: &[u8]) -> Result<Vec<u8>, String> {
+    super::with(|host| host.get_quote(challenge))
+}
+
+pub fn verify_quote(current_time: i64, quote_binary: &[u8]) -> Result<String, String> {
+    super::with(|host| host.verify_quote(current_time, quote_binary))
+}
+
+pub fn parse_quote(quote: &[u8]) -> Result<String, String> {
+    super::with(|host| host.parse_quote(quote))
+}
+
+pub fn connection_open(uri: &str) -> Result<String, String> {
+    super::with(|host| host.connection_open(uri))
+}
+
+pub fn sql_query(connection: &str, query: &str) -> Result<String, String> {
+    super::with(|host| host.sql_query(connection, query))
+}
+
+pub fn sql_exec(connection: &str, command: &str) -> Result<String, String> {
+    super::with(|host| host.sql_exec(connection, command))
+}
+
+pub fn start_recording() {
+    super::with(|host| host.start_recording())
+}
+
+pub fn stop_recording() {
+    super::with(|host| host.stop_recording())
+}
+
+pub fn cancel_transaction() {
+    super::with(|host| host.cancel_transaction())
+}
@@ -0,0 +1,1870 @@
+use crate::crypto::subtle::CryptoKey;
+use crate::crypto::subtle_idl_v1_enums::{
+    DerivationAlgorithm, DerivedKeyUsageAlgorithm, EncryptionAlgorithm, HashAlgorithm,
+    KeyAlgorithm, KeyFormat, ShaAlgorithm, SigningAlgorithm, WrappingAlgorithm,
+};
+use crate::util::to_hex;
+use aes_gcm::aead::consts::{U12, U13, U14, U15, U16};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::aes::{Aes128, Aes192, Aes256};
+use aes_gcm::AesGcm;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::CryptoRngCore;
+use rsa::pkcs1::{
+    DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey,
+};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Oaep, Pkcs1v15Encrypt, Pss, RsaPrivateKey, RsaPublicKey};
+use serde::{de::DeserializeOwned, Deserialize};
+use sha2::Digest;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+type CryptoResult<T> = Result<T, String>;
+
+const SECP_R1: i32 = KeyAlgorithm::SecpR1 as i32;
+const SECP_K1: i32 = KeyAlgorithm::SecpK1 as i32;
+const AES: i32 = KeyAlgorithm::Aes as i32;
+const RSA: i32 = KeyAlgorithm::Rsa as i32;
+const HMAC: i32 = KeyAlgorithm::Hmac as i32;
+
+const FORMAT_RAW: i32 = KeyFormat::Raw as i32;
+const FORMAT_SPKI: i32 = KeyFormat::Spki as i32;
+const FORMAT_PKCS8: i32 = KeyFormat::Pkcs8 as i32;
+const FORMAT_SEC1: i32 = KeyFormat::Sec1 as i32;
+const FORMAT_PKCS1: i32 = KeyFormat::Pkcs1 as i32;
+
+const SHA2: u32 = ShaAlgorithm::Sha2 as u32;
+const SHA3: u32 = ShaAlgorithm::Sha3 as u32;
+const SHA1: u32 = ShaAlgorithm::Sha1 as u32;
+
+// Algorithms of the simple crypto calls
+const SIMPLE_AES_METADATA: &str = r#"{"length":256}"#;
+const SIMPLE_CURVE_METADATA: &str = r#"{"length":256}"#;
+const SIMPLE_ECDSA_METADATA: &str = r#"{"sha_metadata":{"algo_id":1,"length":256}}"#;
+const SIMPLE_IV_LENGTH: usize = 12;
+
+fn fail(error: impl Display) -> String {
+    error.to_string()
+}
+
+fn unsupported<T>(what: impl Display) -> CryptoResult<T> {
+    Err(format!(
+        "{what} is not supported by the software crypto backend"
+    ))
+}
+
+fn parse<T: DeserializeOwned>(metadata: &str) -> CryptoResult<T> {
+    serde_json::from_str(metadata).map_err(|e| format!("Invalid metadata '{metadata}': {e}"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+}
+
+macro_rules! with_hash {
+    ($hash:expr, $d:ident => $body:expr) => {
+        match $hash {
+            Hash::Sha1 => {
+                type $d = sha1::Sha1;
+                $body
+            }
+            Hash::Sha256 => {
+                type $d = sha2::Sha256;
+                $body
+            }
+            Hash::Sha384 => {
+                type $d = sha2::Sha384;
+                $body
+            }
+            Hash::Sha512 => {
+                type $d = sha2::Sha512;
+                $body
+            }
+            Hash::Sha3_256 => {
+                type $d = sha3::Sha3_256;
+                $body
+            }
+            Hash::Sha3_384 => {
+                type $d = sha3::Sha3_384;
+                $body
+            }
+            Hash::Sha3_512 => {
+                type $d = sha3::Sha3_512;
+                $body
+            }
+        }
+    };
+}
+
+impl Hash {
+    fn name(&self) -> &'static str {
+        match self {
+            Hash::Sha1 => "SHA1",
+            Hash::Sha256 => "SHA2-256",
+            Hash::Sha384 => "SHA2-384",
+            Hash::Sha512 => "SHA2-512",
+            Hash::Sha3_256 => "SHA3-256",
+            Hash::Sha3_384 => "SHA3-384",
+            Hash::Sha3_512 => "SHA3-512",
+        }
+    }
+
+    /// Block size in bytes, the default length of HMAC keys
+    fn block_size(&self) -> usize {
+        match self {
+            Hash::Sha1 | Hash::Sha256 => 64,
+            Hash::Sha384 | Hash::Sha512 => 128,
+            Hash::Sha3_256 => 136,
+            Hash::Sha3_384 => 104,
+            Hash::Sha3_512 => 72,
+        }
+    }
+
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        with_hash!(self, D => D::digest(data).to_vec())
+    }
+}
+
+#[derive(Deserialize)]
+struct ShaInfo {
+    algo_id: u32,
+    length: u32,
+}
+
+impl ShaInfo {
+    fn hash(&self) -> CryptoResult<Hash> {
+        match (self.algo_id, self.length) {
+            (SHA2 | SHA1, 160) => Ok(Hash::Sha1),
+            (SHA2, 256) => Ok(Hash::Sha256),
+            (SHA2, 384) => Ok(Hash::Sha384),
+            (SHA2, 512) => Ok(Hash::Sha512),
+            (SHA3, 256) => Ok(Hash::Sha3_256),
+            (SHA3, 384) => Ok(Hash::Sha3_384),
+            (SHA3, 512) => Ok(Hash::Sha3_512),
+            (algo_id, length) => unsupported(format!("Hash {algo_id} of {length} bits")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LengthMetadata {
+    length: u32,
+}
+
+#[derive(Deserialize)]
+struct HmacMetadata {
+    sha_metadata: ShaInfo,
+    length: u32,
+}
+
+#[derive(Deserialize)]
+struct RsaMetadata {
+    modulus: u32,
+    public_exponent: u32,
+    sha_metadata: ShaInfo,
+}
+
+#[derive(Deserialize)]
+struct HashMetadata {
+    sha_metadata: ShaInfo,
+}
+
+#[derive(Deserialize)]
+struct RsaPssMetadata {
+    #[serde(rename = "saltLength")]
+    salt_length: u64,
+}
+
+#[derive(Deserialize)]
+struct AesGcmMetadata {
+    iv: Vec<u8>,
+    #[serde(rename = "additionalData")]
+    additional_data: Vec<u8>,
+    #[serde(rename = "tagLength")]
+    tag_length: u32,
+}
+
+#[derive(Deserialize)]
+struct RsaOaepMetadata {
+    label: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct AesKwMetadata {
+    with_padding: bool,
+}
+
+#[derive(Deserialize)]
+struct EcdhMetadata {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct HkdfMetadata {
+    salt: Vec<u8>,
+    info: Vec<u8>,
+    hash_info: ShaInfo,
+}
+
+#[derive(Deserialize)]
+struct KeyPersistParams {
+    key_id: String,
+    key_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    P256,
+    P384,
+    K256,
+}
+
+macro_rules! on_curve {
+    ($curve:expr, $c:ident => $body:expr) => {
+        match $curve {
+            Curve::P256 => {
+                use p256 as $c;
+                $body
+            }
+            Curve::P384 => {
+                use p384 as $c;
+                $body
+            }
+            Curve::K256 => {
+                use k256 as $c;
+                $body
+            }
+        }
+    };
+}
+
+impl Curve {
+    fn from_metadata(algorithm: i32, metadata: &str) -> CryptoResult<Curve> {
+        let metadata: LengthMetadata = parse(metadata)?;
+        match (algorithm, metadata.length) {
+            (SECP_R1, 256) => Ok(Curve::P256),
+            (SECP_R1, 384) => Ok(Curve::P384),
+            (SECP_K1, 256) => Ok(Curve::K256),
+            (_, length) => unsupported(format!("Elliptic curve of {length} bits")),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Curve::P256 => "P-256",
+            Curve::P384 => "P-384",
+            Curve::K256 => "secp256k1",
+        }
+    }
+}
+
+/// Private scalars and public points are kept in their SEC1 byte encoding
+enum Material {
+    Aes(Vec<u8>),
+    Hmac(Vec<u8>, Hash),
+    EcPrivate(Curve, Vec<u8>),
+    EcPublic(Curve, Vec<u8>),
+    RsaPrivate(Box<RsaPrivateKey>, Hash),
+    RsaPublic(RsaPublicKey, Hash),
+}
+
+impl Material {
+    fn key_type(&self) -> &'static str {
+        match self {
+            Material::Aes(_) | Material::Hmac(..) => "secret",
+            Material::EcPrivate(..) | Material::RsaPrivate(..) => "private",
+            Material::EcPublic(..) | Material::RsaPublic(..) => "public",
+        }
+    }
+
+    fn family(&self) -> &'static str {
+        match self {
+            Material::Aes(_) => "aes",
+            Material::Hmac(..) => "hmac",
+            Material::EcPrivate(..) | Material::EcPublic(..) => "ecc",
+            Material::RsaPrivate(..) | Material::RsaPublic(..) => "rsa",
+        }
+    }
+
+    fn algorithm(&self) -> String {
+        match self {
+            Material::Aes(bytes) => format!("AES-{}", bytes.len() * 8),
+            Material::Hmac(_, hash) => format!("HMAC-{}", hash.name()),
+            Material::EcPrivate(curve, _) | Material::EcPublic(curve, _) => {
+                curve.name().to_string()
+            }
+            Material::RsaPrivate(key, _) => format!("RSA-{}", key.size() * 8),
+            Material::RsaPublic(key, _) => format!("RSA-{}", key.size() * 8),
+        }
+    }
+
+    fn secret(&self) -> CryptoResult<&[u8]> {
+        match self {
+            Material::Aes(bytes) | Material::Hmac(bytes, _) => Ok(bytes),
+            _ => Err("Expected a secret key".to_string()),
+        }
+    }
+
+    fn public(&self) -> CryptoResult<Material> {
+        match self {
+            Material::EcPrivate(curve, scalar) => {
+                let point = on_curve!(curve, c => {
+                    use c::elliptic_curve::sec1::ToEncodedPoint;
+                    let secret = c::SecretKey::from_slice(scalar).map_err(fail)?;
+                    secret.public_key().to_encoded_point(false).as_bytes().to_vec()
+                });
+                Ok(Material::EcPublic(*curve, point))
+            }
+            Material::EcPublic(curve, point) => Ok(Material::EcPublic(*curve, point.clone())),
+            Material::RsaPrivate(key, hash) => Ok(Material::RsaPublic(key.to_public_key(), *hash)),
+            Material::RsaPublic(key, hash) => Ok(Material::RsaPublic(key.clone(), *hash)),
+            Material::Aes(_) | Material::Hmac(..) => {
+                Err("A secret key has no public key".to_string())
+            }
+        }
+    }
+
+    fn rsa_public(&self) -> CryptoResult<(RsaPublicKey, Hash)> {
+        match self {
+            Material::RsaPrivate(key, hash) => Ok((key.to_public_key(), *hash)),
+            Material::RsaPublic(key, hash) => Ok((key.clone(), *hash)),
+            _ => Err("Expected an RSA key".to_string()),
+        }
+    }
+
+    fn rsa_private(&self) -> CryptoResult<(&RsaPrivateKey, Hash)> {
+        match self {
+            Material::RsaPrivate(key, hash) => Ok((key, *hash)),
+            _ => Err("Expected an RSA private key".to_string()),
+        }
+    }
+
+    fn generate(
+        rng: &mut impl CryptoRngCore,
+        algorithm: i32,
+        metadata: &str,
+    ) -> CryptoResult<Material> {
+        match algorithm {
+            SECP_R1 | SECP_K1 => {
+                let curve = Curve::from_metadata(algorithm, metadata)?;
+                let scalar = on_curve!(curve, c => c::SecretKey::random(rng).to_bytes().to_vec());
+                Ok(Material::EcPrivate(curve, scalar))
+            }
+            AES => {
+                let metadata: LengthMetadata = parse(metadata)?;
+                let mut bytes = vec![0u8; aes_key_size(metadata.length)?];
+                rng.fill_bytes(&mut bytes);
+                Ok(Material::Aes(bytes))
+            }
+            RSA => {
+                let metadata: RsaMetadata = parse(metadata)?;
+                // `rsa::generate_key` leaves the exponent to the host
+                let exponent = match metadata.public_exponent {
+                    0 => 65537,
+                    exponent => exponent,
+                };
+                let key = RsaPrivateKey::new_with_exp(
+                    rng,
+                    metadata.modulus as usize,
+                    &BigUint::from(exponent),
+                )
+                .map_err(fail)?;
+                Ok(Material::RsaPrivate(
+                    Box::new(key),
+                    metadata.sha_metadata.hash()?,
+                ))
+            }
+            HMAC => {
+                let metadata: HmacMetadata = parse(metadata)?;
+                let hash = metadata.sha_metadata.hash()?;
+                let mut bytes = vec![0u8; hmac_key_size(metadata.length, hash)];
+                rng.fill_bytes(&mut bytes);
+                Ok(Material::Hmac(bytes, hash))
+            }
+            _ => unsupported(format!("Key algorithm {algorithm}")),
+        }
+    }
+
+    fn import(format: i32, data: &[u8], algorithm: i32, metadata: &str) -> CryptoResult<Material> {
+        match algorithm {
+            SECP_R1 | SECP_K1 => {
+                let curve = Curve::from_metadata(algorithm, metadata)?;
+                on_curve!(curve, c => {
+                    use c::elliptic_curve::sec1::ToEncodedPoint;
+                    use c::pkcs8::{DecodePrivateKey, DecodePublicKey};
+                    let private = |secret: c::SecretKey| {
+                        Ok(Material::EcPrivate(curve, secret.to_bytes().to_vec()))
+                    };
+                    let public = |public: c::PublicKey| {
+                        Ok(Material::EcPublic(
+                            curve,
+                            public.to_encoded_point(false).as_bytes().to_vec(),
+                        ))
+                    };
+                    match format {
+                        FORMAT_RAW => public(c::PublicKey::from_sec1_bytes(data).map_err(fail)?),
+                        FORMAT_SPKI => public(c::PublicKey::from_public_key_der(data).map_err(fail)?),
+                        FORMAT_PKCS8 => private(c::SecretKey::from_pkcs8_der(data).map_err(fail)?),
+                        FORMAT_SEC1 => match c::SecretKey::from_sec1_der(data) {
+                            Ok(secret) => private(secret),
+                            Err(_) => public(c::PublicKey::from_sec1_bytes(data).map_err(fail)?),
+                        },
+                        _ => unsupported(format!("Key format {format} for elliptic curve keys")),
+                    }
+                })
+            }
+            AES => {
+                let metadata: LengthMetadata = parse(metadata)?;
+                if format != FORMAT_RAW {
+                    return unsupported(format!("Key format {format} for AES keys"));
+                }
+                if data.len() != aes_key_size(metadata.length)? {
+                    return Err(format!("Invalid AES-{} key length", metadata.length));
+                }
+                Ok(Material::Aes(data.to_vec()))
+            }
+            RSA => {
+                let metadata: RsaMetadata = parse(metadata)?;
+                let hash = metadata.sha_metadata.hash()?;
+                match format {
+                    FORMAT_SPKI => Ok(Material::RsaPublic(
+                        RsaPublicKey::from_public_key_der(data).map_err(fail)?,
+                        hash,
+                    )),
+                    FORMAT_PKCS8 => Ok(Material::RsaPrivate(
+                        Box::new(RsaPrivateKey::from_pkcs8_der(data).map_err(fail)?),
+                        hash,
+                    )),
+                    FORMAT_PKCS1 => match RsaPrivateKey::from_pkcs1_der(data) {
+                        Ok(key) => Ok(Material::RsaPrivate(Box::new(key), hash)),
+                        Err(_) => Ok(Material::RsaPublic(
+                            RsaPublicKey::from_pkcs1_der(data).map_err(fail)?,
+                            hash,
+                        )),
+                    },
+                    _ => unsupported(format!("Key format {format} for RSA keys")),
+                }
+            }
+            HMAC => {
+                let metadata: HmacMetadata = parse(metadata)?;
+                if format != FORMAT_RAW {
+                    return unsupported(format!("Key format {format} for HMAC keys"));
+                }
+                Ok(Material::Hmac(data.to_vec(), metadata.sha_metadata.hash()?))
+            }
+            _ => unsupported(format!("Key algorithm {algorithm}")),
+        }
+    }
+
+    /// Encode the key in `format`, along with whether the encoding exposes private material
+    fn export(&self, format: i32) -> CryptoResult<(Vec<u8>, bool)> {
+        match (self, format) {
+            (Material::Aes(bytes) | Material::Hmac(bytes, _), FORMAT_RAW) => {
+                Ok((bytes.clone(), true))
+            }
+            (Material::EcPublic(curve, point), _) => on_curve!(curve, c => {
+                use c::pkcs8::EncodePublicKey;
+                let public = c::PublicKey::from_sec1_bytes(point).map_err(fail)?;
+                match format {
+                    FORMAT_RAW | FORMAT_SEC1 => Ok((point.clone(), false)),
+                    FORMAT_SPKI => Ok((public.to_public_key_der().map_err(fail)?.as_bytes().to_vec(), false)),
+                    _ => unsupported(format!("Key format {format} for public elliptic curve keys")),
+                }
+            }),
+            (Material::EcPrivate(curve, scalar), FORMAT_PKCS8 | FORMAT_SEC1) => {
+                on_curve!(curve, c => {
+                    use c::pkcs8::EncodePrivateKey;
+                    let secret = c::SecretKey::from_slice(scalar).map_err(fail)?;
+                    let der = if format == FORMAT_PKCS8 {
+                        secret.to_pkcs8_der().map_err(fail)?.as_bytes().to_vec()
+                    } else {
+                        secret.to_sec1_der().map_err(fail)?.to_vec()
+                    };
+                    Ok((der, true))
+                })
+            }
+            (Material::RsaPublic(key, _), FORMAT_SPKI) => Ok((
+                key.to_public_key_der().map_err(fail)?.as_bytes().to_vec(),
+                false,
+            )),
+            (Material::RsaPublic(key, _), FORMAT_PKCS1) => {
+                Ok((key.to_pkcs1_der().map_err(fail)?.as_bytes().to_vec(), false))
+            }
+            (Material::RsaPrivate(key, _), FORMAT_PKCS8) => {
+                Ok((key.to_pkcs8_der().map_err(fail)?.as_bytes().to_vec(), true))
+            }
+            (Material::RsaPrivate(key, _), FORMAT_PKCS1) => {
+                Ok((key.to_pkcs1_der().map_err(fail)?.as_bytes().to_vec(), true))
+            }
+            (Material::EcPrivate(..) | Material::RsaPrivate(..), FORMAT_RAW | FORMAT_SPKI) => {
+                self.public()?.export(format)
+            }
+            _ => unsupported(format!(
+                "Key format {format} for {} {} keys",
+                self.key_type(),
+                self.family()
+            )),
+        }
+    }
+}
+
+fn aes_key_size(bits: u32) -> CryptoResult<usize> {
+    match bits {
+        128 | 192 | 256 => Ok(bits as usize / 8),
+        _ => Err(format!("Invalid AES key length {bits}")),
+    }
+}
+
+fn hmac_key_size(bits: u32, hash: Hash) -> usize {
+    if bits == 0 {
+        hash.block_size()
+    } else {
+        (bits as usize).div_ceil(8)
+    }
+}
+
+/// Keep the first `bits` bits of `bytes`
+fn truncate_bits(mut bytes: Vec<u8>, bits: u32) -> CryptoResult<Vec<u8>> {
+    let len = (bits as usize).div_ceil(8);
+    if len > bytes.len() {
+        return Err(format!(
+            "Cannot derive {bits} bits from a {} bits secret",
+            bytes.len() * 8
+        ));
+    }
+    bytes.truncate(len);
+    if !bits.is_multiple_of(8) {
+        if let Some(last) = bytes.last_mut() {
+            *last &= 0xffu8 << (8 - bits % 8);
+        }
+    }
+    Ok(bytes)
+}
+
+macro_rules! with_aes_gcm {
+    ($key:expr, $tag:expr, $cipher:ident => $body:expr) => {
+        match ($key.len(), $tag) {
+            (16, 12) => {
+                type $cipher = AesGcm<Aes128, U12, U12>;
+                $body
+            }
+            (16, 13) => {
+                type $cipher = AesGcm<Aes128, U12, U13>;
+                $body
+            }
+            (16, 14) => {
+                type $cipher = AesGcm<Aes128, U12, U14>;
+                $body
+            }
+            (16, 15) => {
+                type $cipher = AesGcm<Aes128, U12, U15>;
+                $body
+            }
+            (16, 16) => {
+                type $cipher = AesGcm<Aes128, U12, U16>;
+                $body
+            }
+            (24, 12) => {
+                type $cipher = AesGcm<Aes192, U12, U12>;
+                $body
+            }
+            (24, 13) => {
+                type $cipher = AesGcm<Aes192, U12, U13>;
+                $body
+            }
+            (24, 14) => {
+                type $cipher = AesGcm<Aes192, U12, U14>;
+                $body
+            }
+            (24, 15) => {
+                type $cipher = AesGcm<Aes192, U12, U15>;
+                $body
+            }
+            (24, 16) => {
+                type $cipher = AesGcm<Aes192, U12, U16>;
+                $body
+            }
+            (32, 12) => {
+                type $cipher = AesGcm<Aes256, U12, U12>;
+                $body
+            }
+            (32, 13) => {
+                type $cipher = AesGcm<Aes256, U12, U13>;
+                $body
+            }
+            (32, 14) => {
+                type $cipher = AesGcm<Aes256, U12, U14>;
+                $body
+            }
+            (32, 15) => {
+                type $cipher = AesGcm<Aes256, U12, U15>;
+                $body
+            }
+            (32, 16) => {
+                type $cipher = AesGcm<Aes256, U12, U16>;
+                $body
+            }
+            (key, tag) => Err(format!(
+                "Unsupported AES-GCM parameters: {} bits key, {} bytes tag",
+                key * 8,
+                tag
+            )),
+        }
+    };
+}
+
+/// Symmetric or asymmetric cipher shared by encryption and key wrapping
+enum Cipher {
+    AesGcm,
+    AesKw,
+    RsaOaep,
+    RsaPkcs1,
+}
+
+impl Cipher {
+    fn encryption(algorithm: i32) -> CryptoResult<Cipher> {
+        match algorithm {
+            a if a == EncryptionAlgorithm::AesGcm as i32 => Ok(Cipher::AesGcm),
+            a if a == EncryptionAlgorithm::RsaOaep as i32 => Ok(Cipher::RsaOaep),
+            a if a == EncryptionAlgorithm::RsaPkcs1V1_5 as i32 => Ok(Cipher::RsaPkcs1),
+            _ => unsupported(format!("Encryption algorithm {algorithm}")),
+        }
+    }
+
+    fn wrapping(algorithm: i32) -> CryptoResult<Cipher> {
+        match algorithm {
+            a if a == WrappingAlgorithm::AesKw as i32 => Ok(Cipher::AesKw),
+            a if a == WrappingAlgorithm::AesGcm as i32 => Ok(Cipher::AesGcm),
+            a if a == WrappingAlgorithm::RsaOaep as i32 => Ok(Cipher::RsaOaep),
+            a if a == WrappingAlgorithm::RsaPkcs1V1_5 as i32 => Ok(Cipher::RsaPkcs1),
+            _ => unsupported(format!("Wrapping algorithm {algorithm}")),
+        }
+    }
+
+    fn encrypt(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key: &Material,
+        metadata: &str,
+        data: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        match self {
+            Cipher::AesGcm => {
+                let metadata: AesGcmMetadata = parse(metadata)?;
+                let key = aes_key(key)?;
+                check_iv(&metadata.iv)?;
+                let payload = Payload {
+                    msg: data,
+                    aad: &metadata.additional_data,
+                };
+                with_aes_gcm!(key, metadata.tag_length, C => C::new_from_slice(key)
+                    .map_err(fail)?
+                    .encrypt(metadata.iv.as_slice().into(), payload)
+                    .map_err(fail))
+            }
+            Cipher::AesKw => {
+                let metadata: AesKwMetadata = parse(metadata)?;
+                aes_kw(aes_key(key)?, data, metadata.with_padding, true)
+            }
+            Cipher::RsaOaep => {
+                let metadata: RsaOaepMetadata = parse(metadata)?;
+                let (key, hash) = key.rsa_public()?;
+                let padding = oaep(hash, &metadata.label)?;
+                key.encrypt(rng, padding, data).map_err(fail)
+            }
+            Cipher::RsaPkcs1 => {
+                let (key, _) = key.rsa_public()?;
+                key.encrypt(rng, Pkcs1v15Encrypt, data).map_err(fail)
+            }
+        }
+    }
+
+    fn decrypt(&self, key: &Material, metadata: &str, data: &[u8]) -> CryptoResult<Vec<u8>> {
+        match self {
+            Cipher::AesGcm => {
+                let metadata: AesGcmMetadata = parse(metadata)?;
+                let key = aes_key(key)?;
+                check_iv(&metadata.iv)?;
+                let payload = Payload {
+                    msg: data,
+                    aad: &metadata.additional_data,
+                };
+                with_aes_gcm!(key, metadata.tag_length, C => C::new_from_slice(key)
+                    .map_err(fail)?
+                    .decrypt(metadata.iv.as_slice().into(), payload)
+                    .map_err(fail))
+            }
+            Cipher::AesKw => {
+                let metadata: AesKwMetadata = parse(metadata)?;
+                aes_kw(aes_key(key)?, data, metadata.with_padding, false)
+            }
+            Cipher::RsaOaep => {
+                let metadata: RsaOaepMetadata = parse(metadata)?;
+                let (key, hash) = key.rsa_private()?;
+                key.decrypt(oaep(hash, &metadata.label)?, data)
+                    .map_err(fail)
+            }
+            Cipher::RsaPkcs1 => {
+                let (key, _) = key.rsa_private()?;
+                key.decrypt(Pkcs1v15Encrypt, data).map_err(fail)
+            }
+        }
+    }
+}
+
+fn aes_key(key: &Material) -> CryptoResult<&[u8]> {
+    match key {
+        Material::Aes(bytes) => Ok(bytes),
+        _ => Err("Expected an AES key".to_string()),
+    }
+}
+
+fn check_iv(iv: &[u8]) -> CryptoResult<()> {
+    if iv.len() != 12 {
+        return unsupported(format!("AES-GCM IV of {} bytes", iv.len()));
+    }
+    Ok(())
+}
+
+fn aes_kw(key: &[u8], data: &[u8], with_padding: bool, wrap: bool) -> CryptoResult<Vec<u8>> {
+    macro_rules! kek {
+        ($kek:ty) => {{
+            let kek = <$kek>::try_from(key).map_err(fail)?;
+            match (with_padding, wrap) {
+                (true, true) => kek.wrap_with_padding_vec(data),
+                (true, false) => kek.unwrap_with_padding_vec(data),
+                (false, true) => kek.wrap_vec(data),
+                (false, false) => kek.unwrap_vec(data),
+            }
+            .map_err(fail)
+        }};
+    }
+    match key.len() {
+        16 => kek!(aes_kw::KekAes128),
+        24 => kek!(aes_kw::KekAes192),
+        32 => kek!(aes_kw::KekAes256),
+        len => Err(format!("Invalid AES key length {}", len * 8)),
+    }
+}
+
+fn oaep(hash: Hash, label: &[u8]) -> CryptoResult<Oaep> {
+    if label.is_empty() {
+        return Ok(with_hash!(hash, D => Oaep::new::<D>()));
+    }
+    let label = String::from_utf8(label.to_vec())
+        .map_err(|_| "RSA-OAEP labels must be valid UTF-8".to_string())?;
+    Ok(with_hash!(hash, D => Oaep::new_with_label::<D, _>(label)))
+}
+
+fn hmac(key: &[u8], hash: Hash, data: &[u8]) -> CryptoResult<Vec<u8>> {
+    with_hash!(hash, D => {
+        let mut mac = <Hmac<D> as Mac>::new_from_slice(key).map_err(fail)?;
+        mac.update(data);
+        Ok(mac.finalize().into_bytes().to_vec())
+    })
+}
+
+fn hkdf(ikm: &[u8], metadata: &HkdfMetadata, len: usize) -> CryptoResult<Vec<u8>> {
+    let mut okm = vec![0u8; len];
+    with_hash!(metadata.hash_info.hash()?, D => Hkdf::<D>::new(Some(&metadata.salt), ikm)
+        .expand(&metadata.info, &mut okm)
+        .map_err(fail))?;
+    Ok(okm)
+}
+
+struct StoredKey {
+    key: CryptoKey,
+    material: Material,
+}
+
+/// Pure Rust implementation of the host crypto imports, used by [`super::mock::MockHost`].
+///
+/// Keys live in memory and are identified the same way as on the host: `id` for keys
+/// created during the call, and an alias once saved. Key usages are recorded but not enforced.
+#[derive(Default)]
+pub struct SoftwareCrypto {
+    keys: RefCell<HashMap<String, StoredKey>>,
+    aliases: RefCell<HashMap<String, String>>,
+}
+
+fn usage_name(usage: u8) -> Option<&'static str> {
+    match usage {
+        0 => Some("encrypt"),
+        1 => Some("decrypt"),
+        2 => Some("sign"),
+        3 => Some("verify"),
+        4 => Some("deriveKey"),
+        5 => Some("deriveBits"),
+        6 => Some("wrapKey"),
+        7 => Some("unwrapKey"),
+        _ => None,
+    }
+}
+
+fn simple_aes_gcm_metadata(iv: &[u8]) -> String {
+    serde_json::json!({ "iv": iv, "additionalData": [], "tagLength": 16 }).to_string()
+}
+
+fn random_id(rng: &mut impl CryptoRngCore) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+fn to_json(key: &CryptoKey) -> CryptoResult<String> {
+    serde_json::to_string(key).map_err(fail)
+}
+
+impl SoftwareCrypto {
+    pub fn new() -> SoftwareCrypto {
+        SoftwareCrypto::default()
+    }
+
+    fn resolve(&self, name: &str) -> CryptoResult<String> {
+        if self.keys.borrow().contains_key(name) {
+            return Ok(name.to_string());
+        }
+        match self.aliases.borrow().get(name) {
+            Some(id) => Ok(id.clone()),
+            None => Err(format!("Key '{name}' not found")),
+        }
+    }
+
+    fn with_key<T>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&StoredKey) -> CryptoResult<T>,
+    ) -> CryptoResult<T> {
+        let id = self.resolve(name)?;
+        let keys = self.keys.borrow();
+        match keys.get(&id) {
+            Some(key) => f(key),
+            None => Err(format!("Key '{name}' not found")),
+        }
+    }
+
+    fn store(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        material: Material,
+        extractable: bool,
+        usages: Vec<String>,
+    ) -> CryptoResult<String> {
+        let id = if key_name.is_empty() {
+            random_id(rng)
+        } else {
+            key_name.to_string()
+        };
+        if self.keys.borrow().contains_key(&id) {
+            return Err(format!("Key '{id}' already exists"));
+        }
+        let mut key = CryptoKey::new(
+            &id,
+            material.key_type(),
+            extractable,
+            usages,
+            &material.algorithm(),
+        );
+        key.family = material.family().to_string();
+        let json = to_json(&key)?;
+        self.keys
+            .borrow_mut()
+            .insert(id, StoredKey { key, material });
+        Ok(json)
+    }
+
+    fn usages(usages: &[u8]) -> Vec<String> {
+        usages
+            .iter()
+            .filter_map(|usage| usage_name(*usage))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn key_exists(&self, key_name: &str) -> CryptoResult<bool> {
+        Ok(self.aliases.borrow().contains_key(key_name)
+            || self.keys.borrow().contains_key(key_name))
+    }
+
+    pub fn generate_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> CryptoResult<String> {
+        let material = Material::generate(rng, algorithm, algo_metadata)?;
+        self.store(
+            rng,
+            key_name,
+            material,
+            extractable != 0,
+            Self::usages(usages),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn import_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        key_format: i32,
+        key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> CryptoResult<String> {
+        let material = Material::import(key_format, key_data, algorithm, algo_metadata)?;
+        self.store(
+            rng,
+            key_name,
+            material,
+            extractable != 0,
+            Self::usages(usages),
+        )
+    }
+
+    pub fn export_key(&self, key_name: &str, key_format: i32) -> CryptoResult<Vec<u8>> {
+        self.with_key(key_name, |stored| {
+            let (bytes, private) = stored.material.export(key_format)?;
+            if private && !stored.key.extractable {
+                return Err(format!("Key '{key_name}' is not extractable"));
+            }
+            Ok(bytes)
+        })
+    }
+
+    /// SPKI encoding of the public part of an asymmetric key
+    pub fn get_public_key(&self, key_name: &str) -> CryptoResult<Vec<u8>> {
+        self.with_key(key_name, |stored| {
+            Ok(stored.material.public()?.export(FORMAT_SPKI)?.0)
+        })
+    }
+
+    pub fn get_public_key_as_cryptokey(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+    ) -> CryptoResult<String> {
+        let (material, usages) = self.with_key(key_name, |stored| {
+            let usages = stored
+                .key
+                .usages
+                .iter()
+                .filter(|usage| matches!(usage.as_str(), "encrypt" | "verify" | "wrapKey"))
+                .cloned()
+                .collect::<Vec<_>>();
+            Ok((stored.material.public()?, usages))
+        })?;
+        self.store(rng, "", material, true, usages)
+    }
+
+    fn derive(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        bits: u32,
+    ) -> CryptoResult<Vec<u8>> {
+        match derivation_algorithm {
+            a if a == DerivationAlgorithm::Ecdh as i32 => {
+                let metadata: EcdhMetadata = parse(derivation_metadata)?;
+                let public =
+                    self.with_key(&metadata.public_key, |stored| stored.material.public())?;
+                let shared =
+                    self.with_key(base_key_name, |stored| match (&stored.material, &public) {
+                        (Material::EcPrivate(curve, scalar), Material::EcPublic(other, point))
+                            if curve == other =>
+                        {
+                            on_curve!(curve, c => {
+                                let secret = c::SecretKey::from_slice(scalar).map_err(fail)?;
+                                let public = c::PublicKey::from_sec1_bytes(point).map_err(fail)?;
+                                let shared = c::ecdh::diffie_hellman(
+                                    secret.to_nonzero_scalar(),
+                                    public.as_affine(),
+                                );
+                                Ok(shared.raw_secret_bytes().to_vec())
+                            })
+                        }
+                        _ => Err(
+                            "ECDH requires a private key and a public key on the same curve"
+                                .to_string(),
+                        ),
+                    })?;
+                truncate_bits(shared, bits)
+            }
+            a if a == DerivationAlgorithm::Hkdf as i32 => {
+                let metadata: HkdfMetadata = parse(derivation_metadata)?;
+                let okm = self.with_key(base_key_name, |stored| {
+                    hkdf(
+                        stored.material.secret()?,
+                        &metadata,
+                        (bits as usize).div_ceil(8),
+                    )
+                })?;
+                truncate_bits(okm, bits)
+            }
+            _ => unsupported(format!("Derivation algorithm {derivation_algorithm}")),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        derived_key_algorithm: i32,
+        derived_key_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> CryptoResult<String> {
+        let material = match derived_key_algorithm {
+            a if a == DerivedKeyUsageAlgorithm::Aes as i32 => {
+                let metadata: LengthMetadata = parse(derived_key_metadata)?;
+                aes_key_size(metadata.length)?;
+                Material::Aes(self.derive(
+                    base_key_name,
+                    derivation_algorithm,
+                    derivation_metadata,
+                    metadata.length,
+                )?)
+            }
+            a if a == DerivedKeyUsageAlgorithm::Hmac as i32 => {
+                let metadata: HmacMetadata = parse(derived_key_metadata)?;
+                let hash = metadata.sha_metadata.hash()?;
+                let bits = hmac_key_size(metadata.length, hash) as u32 * 8;
+                Material::Hmac(
+                    self.derive(
+                        base_key_name,
+                        derivation_algorithm,
+                        derivation_metadata,
+                        bits,
+                    )?,
+                    hash,
+                )
+            }
+            _ => return unsupported(format!("Derived key algorithm {derived_key_algorithm}")),
+        };
+        self.store(rng, "", material, extractable != 0, Self::usages(usages))
+    }
+
+    pub fn derive_bits(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        length: i32,
+    ) -> CryptoResult<Vec<u8>> {
+        let bits = u32::try_from(length).map_err(|_| format!("Invalid length {length}"))?;
+        self.derive(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            bits,
+        )
+    }
+
+    pub fn encrypt(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+        plain_txt: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        let cipher = Cipher::encryption(encrypt_algo_id)?;
+        self.with_key(key_name, |stored| {
+            cipher.encrypt(rng, &stored.material, encrypt_metadata, plain_txt)
+        })
+    }
+
+    pub fn decrypt(
+        &self,
+        key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        cipher_txt: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        let cipher = Cipher::encryption(decrypt_algo_id)?;
+        self.with_key(key_name, |stored| {
+            cipher.decrypt(&stored.material, decrypt_metadata, cipher_txt)
+        })
+    }
+
+    pub fn sign(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        self.with_key(key_name, |stored| match (sign_algo_id, &stored.material) {
+            (a, Material::EcPrivate(curve, scalar)) if a == SigningAlgorithm::Ecdsa as i32 => {
+                let metadata: HashMetadata = parse(sign_metadata)?;
+                let digest = metadata.sha_metadata.hash()?.digest(txt);
+                on_curve!(curve, c => {
+                    use c::ecdsa::signature::hazmat::PrehashSigner;
+                    let secret = c::SecretKey::from_slice(scalar).map_err(fail)?;
+                    let signature: c::ecdsa::Signature = c::ecdsa::SigningKey::from(&secret)
+                        .sign_prehash(&digest)
+                        .map_err(fail)?;
+                    Ok(signature.to_bytes().to_vec())
+                })
+            }
+            (a, Material::RsaPrivate(key, hash)) if a == SigningAlgorithm::RsaPss as i32 => {
+                let metadata: RsaPssMetadata = parse(sign_metadata)?;
+                let digest = hash.digest(txt);
+                let padding =
+                    with_hash!(hash, D => Pss::new_with_salt::<D>(metadata.salt_length as usize));
+                key.sign_with_rng(rng, padding, &digest).map_err(fail)
+            }
+            (a, Material::Hmac(key, hash)) if a == SigningAlgorithm::Hmac as i32 => {
+                hmac(key, *hash, txt)
+            }
+            _ => unsupported(format!(
+                "Signing algorithm {sign_algo_id} with a {} {} key",
+                stored.key.key_type,
+                stored.material.family()
+            )),
+        })
+    }
+
+    pub fn verify(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> CryptoResult<bool> {
+        let public = self.with_key(key_name, |stored| match &stored.material {
+            Material::Hmac(key, hash) => Ok(Material::Hmac(key.clone(), *hash)),
+            material => material.public(),
+        })?;
+        match (sign_algo_id, &public) {
+            (a, Material::EcPublic(curve, point)) if a == SigningAlgorithm::Ecdsa as i32 => {
+                let metadata: HashMetadata = parse(sign_metadata)?;
+                let digest = metadata.sha_metadata.hash()?.digest(txt);
+                on_curve!(curve, c => {
+                    use c::ecdsa::signature::hazmat::PrehashVerifier;
+                    let public = c::PublicKey::from_sec1_bytes(point).map_err(fail)?;
+                    let signature = match c::ecdsa::Signature::from_slice(signature) {
+                        Ok(signature) => signature,
+                        Err(_) => match c::ecdsa::Signature::from_der(signature) {
+                            Ok(signature) => signature,
+                            Err(_) => return Ok(false),
+                        },
+                    };
+                    Ok(c::ecdsa::VerifyingKey::from(&public)
+                        .verify_prehash(&digest, &signature)
+                        .is_ok())
+                })
+            }
+            (a, Material::RsaPublic(key, hash)) if a == SigningAlgorithm::RsaPss as i32 => {
+                let metadata: RsaPssMetadata = parse(sign_metadata)?;
+                let digest = hash.digest(txt);
+                let padding =
+                    with_hash!(hash, D => Pss::new_with_salt::<D>(metadata.salt_length as usize));
+                Ok(key.verify(padding, &digest, signature).is_ok())
+            }
+            (a, Material::Hmac(key, hash)) if a == SigningAlgorithm::Hmac as i32 => {
+                Ok(with_hash!(hash, D => {
+                    let mut mac = <Hmac<D> as Mac>::new_from_slice(key).map_err(fail)?;
+                    mac.update(txt);
+                    mac.verify_slice(signature).is_ok()
+                }))
+            }
+            _ => unsupported(format!(
+                "Signing algorithm {sign_algo_id} with a {} key",
+                public.family()
+            )),
+        }
+    }
+
+    pub fn digest(&self, algo_id: i32, hash_metadata: &str, txt: &[u8]) -> CryptoResult<Vec<u8>> {
+        if algo_id != HashAlgorithm::Sha as i32 {
+            return unsupported(format!("Hash algorithm {algo_id}"));
+        }
+        let metadata: ShaInfo = parse(hash_metadata)?;
+        Ok(metadata.hash()?.digest(txt))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn unwrap_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        decrypt_key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        key_name_to_import: &str,
+        key_format: i32,
+        wrapped_key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> CryptoResult<String> {
+        let cipher = Cipher::wrapping(decrypt_algo_id)?;
+        let key_data = self.with_key(decrypt_key_name, |stored| {
+            cipher.decrypt(&stored.material, decrypt_metadata, wrapped_key_data)
+        })?;
+        self.import_key(
+            rng,
+            key_name_to_import,
+            key_format,
+            &key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        )
+    }
+
+    pub fn wrap_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name_to_export: &str,
+        key_format: i32,
+        encrypt_key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+    ) -> CryptoResult<Vec<u8>> {
+        let cipher = Cipher::wrapping(encrypt_algo_id)?;
+        let key_data = self.with_key(key_name_to_export, |stored| {
+            if !stored.key.extractable {
+                return Err(format!("Key '{key_name_to_export}' is not extractable"));
+            }
+            Ok(stored.material.export(key_format)?.0)
+        })?;
+        self.with_key(encrypt_key_name, |stored| {
+            cipher.encrypt(rng, &stored.material, encrypt_metadata, &key_data)
+        })
+    }
+
+    fn alias(&self, key_id: &str, key_name: &str) -> CryptoResult<()> {
+        self.resolve(key_id)?;
+        let mut aliases = self.aliases.borrow_mut();
+        if aliases.contains_key(key_name) {
+            return Err(format!("Key name '{key_name}' already exists"));
+        }
+        aliases.insert(key_name.to_string(), key_id.to_string());
+        Ok(())
+    }
+
+    pub fn save_key(&self, key_name: &str) -> CryptoResult<()> {
+        self.alias(key_name, key_name)
+    }
+
+    pub fn persist_key(&self, key_persist_params: &str) -> CryptoResult<()> {
+        let params: KeyPersistParams = parse(key_persist_params)?;
+        self.alias(&params.key_id, &params.key_name)
+    }
+
+    pub fn load_key(&self, key_name: &str) -> CryptoResult<String> {
+        let id = match self.aliases.borrow().get(key_name) {
+            Some(id) => id.clone(),
+            None => return Err(format!("Key '{key_name}' not found")),
+        };
+        self.with_key(&id, |stored| {
+            let mut key = stored.key.clone();
+            key.alias = Some(key_name.to_string());
+            to_json(&key)
+        })
+    }
+
+    pub fn delete_key(&self, key_name: &str) -> CryptoResult<()> {
+        match self.aliases.borrow_mut().remove(key_name) {
+            Some(_) => Ok(()),
+            None => Err(format!("Key '{key_name}' not found")),
+        }
+    }
+
+    /// AES-256-GCM key for the simple encryption calls
+    pub fn generate_simple_encryption_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+    ) -> CryptoResult<()> {
+        self.generate_key(rng, key_name, AES, SIMPLE_AES_METADATA, 0, &[0, 1])?;
+        Ok(())
+    }
+
+    /// ECDSA P-256 key for the simple signing calls
+    pub fn generate_simple_signing_key(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+    ) -> CryptoResult<()> {
+        self.generate_key(rng, key_name, SECP_R1, SIMPLE_CURVE_METADATA, 0, &[2, 3])?;
+        Ok(())
+    }
+
+    /// AES-GCM encryption under a random IV, returned as `iv || ciphertext || tag`
+    pub fn simple_encrypt(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        plain_txt: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        let mut iv = [0u8; SIMPLE_IV_LENGTH];
+        rng.fill_bytes(&mut iv);
+        let ciphertext = self.encrypt(
+            rng,
+            key_name,
+            EncryptionAlgorithm::AesGcm as i32,
+            &simple_aes_gcm_metadata(&iv),
+            plain_txt,
+        )?;
+        Ok([iv.as_slice(), &ciphertext].concat())
+    }
+
+    pub fn simple_decrypt(&self, key_name: &str, cipher_txt: &[u8]) -> CryptoResult<Vec<u8>> {
+        if cipher_txt.len() < SIMPLE_IV_LENGTH {
+            return Err("Ciphertext is too short".to_string());
+        }
+        let (iv, ciphertext) = cipher_txt.split_at(SIMPLE_IV_LENGTH);
+        self.decrypt(
+            key_name,
+            EncryptionAlgorithm::AesGcm as i32,
+            &simple_aes_gcm_metadata(iv),
+            ciphertext,
+        )
+    }
+
+    /// ECDSA with SHA-256, as raw `r || s`
+    pub fn simple_sign(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        key_name: &str,
+        txt: &[u8],
+    ) -> CryptoResult<Vec<u8>> {
+        self.sign(
+            rng,
+            key_name,
+            SigningAlgorithm::Ecdsa as i32,
+            SIMPLE_ECDSA_METADATA,
+            txt,
+        )
+    }
+
+    pub fn simple_verify(
+        &self,
+        key_name: &str,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> CryptoResult<bool> {
+        self.verify(
+            key_name,
+            SigningAlgorithm::Ecdsa as i32,
+            SIMPLE_ECDSA_METADATA,
+            txt,
+            signature,
+        )
+    }
+
+    /// SHA-256
+    pub fn simple_digest(&self, txt: &[u8]) -> CryptoResult<Vec<u8>> {
+        Ok(Hash::Sha256.digest(txt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::{CryptoRng, RngCore};
+
+    const SHA256: &str = r#"{"algo_id":1,"length":256}"#;
+    const ECDSA_SHA256: &str = r#"{"sha_metadata":{"algo_id":1,"length":256}}"#;
+    const ECDSA_SHA384: &str = r#"{"sha_metadata":{"algo_id":1,"length":384}}"#;
+    const RSA_1024_SHA256: &str =
+        r#"{"modulus":1024,"public_exponent":65537,"sha_metadata":{"algo_id":1,"length":256}}"#;
+    /// Key generated with OpenSSL, along with the ciphertexts and signature of `known answer`
+    const RSA_PKCS8: &str = "30820278020100300d06092a864886f70d0101010500048202623082025e02010002818100f2578dc37c01f3574ba9d4bae6dd698c27c13d0c040c94f751f119d674ddc32dc81ac3816d0680d565232f8a02c819952657693bc94226329815f483356fd5c2f1003b0e8d1396e78e006436829ba0d2696e22c0c5f2d577f62ef3098bc278419fe4992a33beb4182f2eab9165a84c67c0bb97ea23d3f1a97c470df7068603f3020301000102818100960127d8d6cb864a84a52cee3bb4d57e51a5451a48ef58cdf5c49d4fa7ab150323979d686189e2f933cf21bff594d27105f89944ce80c9900b9f9b68140d936c10c2d9b779a1ac9df12d5ed1d7f137880dd97560adce8ebf641cfed9a04083dad68f748133ebcb1226045c31c82954ed87cf6d5557e679188f6766dc6d75f291024100fe2e32652aaa4d89205eabde34f83343c6815fa0d1e0efaa154e1de4d48bf58d15876d7598cabceaabaaf504b471f089d44414f89226e51576fde0df0f799617024100f413a981bea429f4e3abe499bd8eef221567727920f8155970c5c0eb856870af9a573e9bb35808cf6d48f5878d96738d44abfbed7594f61155dfaf4c106d86850241009811b972cf40ce5c90f232ef214fb114066a167557bbb4875471c4eec7bee86cbd09a6122a14794c08e2a1ee6baa935752985cd78e5118d36bba5e53f5a52f87024064848c74807cbf42b9589b46ad8865f755754af193a057ba83f50d2471c6b751ba705fba63bbc7b5153ecccf4c823f885855b3e4f15dad912a1a7ae90a82f0b9024100f562236853ff7a8c09c5a932e13b5b706f95f816264d02223cb791ddb3fd314f1d926d845eeed9f9bf9a138d3f6f85b1e8251f3952d25415121df3db99f49528";
+    const RSA_SPKI: &str = "30819f300d06092a864886f70d010101050003818d0030818902818100f2578dc37c01f3574ba9d4bae6dd698c27c13d0c040c94f751f119d674ddc32dc81ac3816d0680d565232f8a02c819952657693bc94226329815f483356fd5c2f1003b0e8d1396e78e006436829ba0d2696e22c0c5f2d577f62ef3098bc278419fe4992a33beb4182f2eab9165a84c67c0bb97ea23d3f1a97c470df7068603f30203010001";
+    const RSA_OAEP_SHA256: &str = "55632c540fd02f5b0699885e985c429fd2bbd7f5d0181dd14ddc9aae62a0d4c5328e13c48605b1a53076379c83f14909cb9b4dd8e84f7db51c8e336141f415ead7a9cac9ca896d9996be788897194f9b804d1098465a67003f36706b0672ffe74f131d715c97873593119d155ce1d7a51b478b081ced5f1548a71979fe1d29a7";
+    const RSA_PKCS1: &str = "ccb1273fd95860ef482f371fe18ac27e46e26e072c1f8fdcb1e8a93946277737422802bd03a73730eac37d0c386e0e464a1951e451d42cb67ad9f11cdc9f7e710d7388302aedfdd320cb4e968f3b34dc7ed0c5b8c1562cd3a718f8685264af1ddbae045e7ac38ca75e5fe0ba98f87eb2b724233fb79ec4c8c785bed7509fdb0f";
+    const RSA_PSS_SHA256: &str = "70b4b8bae7cfb1c22885743f0f7e0a2e0176493e3823c13c0e52bbbcc8fafaf3e566be08b87ef3d537031955c4c797361708b29129089615e89b72d48bdd349106d319f8c469e5e00b96a4c5021d84a6089e9afaea9b761993eeae90807488cd0b167971864bf248be86e5b013ba90c8a3cd5039680f503e8c53a94edfbb103c";
+
+    /// Counter-based generator; the vectors below do not depend on randomness
+    struct TestRng(u64);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for TestRng {}
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn import(
+        crypto: &SoftwareCrypto,
+        name: &str,
+        format: i32,
+        data: &[u8],
+        algorithm: i32,
+        metadata: &str,
+    ) {
+        crypto
+            .import_key(
+                &mut TestRng(0),
+                name,
+                format,
+                data,
+                algorithm,
+                metadata,
+                1,
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn import_ec(crypto: &SoftwareCrypto, name: &str, curve: Curve, scalar: &str) {
+        let (algorithm, metadata) = match curve {
+            Curve::P256 => (SECP_R1, r#"{"length":256}"#),
+            Curve::P384 => (SECP_R1, r#"{"length":384}"#),
+            Curve::K256 => (SECP_K1, r#"{"length":256}"#),
+        };
+        let sec1 = on_curve!(curve, c => c::SecretKey::from_slice(&hex(scalar))
+            .unwrap()
+            .to_sec1_der()
+            .unwrap()
+            .to_vec());
+        import(crypto, name, FORMAT_SEC1, &sec1, algorithm, metadata);
+    }
+
+    #[test]
+    fn digests_match_fips_vectors() {
+        let crypto = SoftwareCrypto::new();
+        let sha = HashAlgorithm::Sha as i32;
+        for (metadata, expected) in [
+            (r#"{"algo_id":3,"length":160}"#, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (SHA256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                r#"{"algo_id":1,"length":384}"#,
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            ),
+            (
+                r#"{"algo_id":1,"length":512}"#,
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            ),
+            (
+                r#"{"algo_id":2,"length":256}"#,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+        ] {
+            assert_eq!(crypto.digest(sha, metadata, b"abc").unwrap(), hex(expected), "{metadata}");
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        let crypto = SoftwareCrypto::new();
+        let hmac = SigningAlgorithm::Hmac as i32;
+        for (bits, key, data, expected) in [
+            (256, "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+            (384, "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "Hi There", "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6"),
+            (512, "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "Hi There", "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"),
+            (256, "4a656665", "what do ya want for nothing?", "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+        ] {
+            let name = format!("hmac-{bits}-{key}");
+            let metadata = format!(r#"{{"sha_metadata":{{"algo_id":1,"length":{bits}}},"length":0}}"#);
+            import(&crypto, &name, FORMAT_RAW, &hex(key), HMAC, &metadata);
+            let tag = crypto.sign(&mut TestRng(0), &name, hmac, "{}", data.as_bytes()).unwrap();
+            assert_eq!(tag, hex(expected), "{name}");
+            assert!(crypto.verify(&name, hmac, "{}", data.as_bytes(), &tag).unwrap());
+            assert!(!crypto.verify(&name, hmac, "{}", b"other", &tag).unwrap());
+        }
+    }
+
+    #[test]
+    fn hkdf_matches_rfc_5869() {
+        let crypto = SoftwareCrypto::new();
+        let ikm = [0x0b; 22];
+        import(
+            &crypto,
+            "ikm",
+            FORMAT_RAW,
+            &ikm,
+            HMAC,
+            r#"{"sha_metadata":{"algo_id":1,"length":256},"length":0}"#,
+        );
+        let metadata = format!(
+            r#"{{"salt":{:?},"info":{:?},"hash_info":{SHA256}}}"#,
+            hex("000102030405060708090a0b0c"),
+            hex("f0f1f2f3f4f5f6f7f8f9")
+        );
+        let okm = crypto
+            .derive_bits("ikm", DerivationAlgorithm::Hkdf as i32, &metadata, 42 * 8)
+            .unwrap();
+        assert_eq!(
+            okm,
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+    }
+
+    #[test]
+    fn aes_gcm_matches_mcgrew_viega() {
+        let crypto = SoftwareCrypto::new();
+        let gcm = EncryptionAlgorithm::AesGcm as i32;
+        let key = "feffe9928665731c6d6a8f9467308308";
+        let plaintext = hex("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39");
+        let metadata = format!(
+            r#"{{"iv":{:?},"additionalData":{:?},"tagLength":16}}"#,
+            hex("cafebabefacedbaddecaf888"),
+            hex("feedfacedeadbeeffeedfacedeadbeefabaddad2")
+        );
+        // Test cases 4 (AES-128) and 16 (AES-256)
+        for (bits, key, expected) in [
+            (128, key.to_string(), "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e0915bc94fbc3221a5db94fae95ae7121a47"),
+            (256, key.repeat(2), "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f66276fc6ece0f4e1768cddf8853bb2d551b"),
+        ] {
+            let name = format!("aes-{bits}");
+            import(&crypto, &name, FORMAT_RAW, &hex(&key), AES, &format!(r#"{{"length":{bits}}}"#));
+            let ciphertext = crypto.encrypt(&mut TestRng(0), &name, gcm, &metadata, &plaintext).unwrap();
+            assert_eq!(ciphertext, hex(expected), "{name}");
+            assert_eq!(crypto.decrypt(&name, gcm, &metadata, &ciphertext).unwrap(), plaintext);
+
+            let mut tampered = ciphertext.clone();
+            tampered[0] ^= 1;
+            assert!(crypto.decrypt(&name, gcm, &metadata, &tampered).is_err());
+        }
+    }
+
+    #[test]
+    fn aes_kw_matches_rfc_3394_and_rfc_5649() {
+        let crypto = SoftwareCrypto::new();
+        let kw = WrappingAlgorithm::AesKw as i32;
+        let hmac_metadata = r#"{"sha_metadata":{"algo_id":1,"length":256},"length":0}"#;
+
+        // RFC 3394 4.1: 128 bits of key data with a 128-bit KEK
+        import(
+            &crypto,
+            "kek-128",
+            FORMAT_RAW,
+            &hex("000102030405060708090a0b0c0d0e0f"),
+            AES,
+            r#"{"length":128}"#,
+        );
+        import(
+            &crypto,
+            "data-128",
+            FORMAT_RAW,
+            &hex("00112233445566778899aabbccddeeff"),
+            AES,
+            r#"{"length":128}"#,
+        );
+        let wrapped = crypto
+            .wrap_key(
+                &mut TestRng(0),
+                "data-128",
+                FORMAT_RAW,
+                "kek-128",
+                kw,
+                r#"{"with_padding":false}"#,
+            )
+            .unwrap();
+        assert_eq!(
+            wrapped,
+            hex("1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5")
+        );
+
+        // RFC 5649 6: 20 octets of key data with a 192-bit KEK
+        import(
+            &crypto,
+            "kek-192",
+            FORMAT_RAW,
+            &hex("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8"),
+            AES,
+            r#"{"length":192}"#,
+        );
+        import(
+            &crypto,
+            "data-160",
+            FORMAT_RAW,
+            &hex("c37b7e6492584340bed12207808941155068f738"),
+            HMAC,
+            hmac_metadata,
+        );
+        let wrapped = crypto
+            .wrap_key(
+                &mut TestRng(0),
+                "data-160",
+                FORMAT_RAW,
+                "kek-192",
+                kw,
+                r#"{"with_padding":true}"#,
+            )
+            .unwrap();
+        assert_eq!(
+            wrapped,
+            hex("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a")
+        );
+
+        crypto
+            .unwrap_key(
+                &mut TestRng(0),
+                "kek-192",
+                kw,
+                r#"{"with_padding":true}"#,
+                "unwrapped",
+                FORMAT_RAW,
+                &wrapped,
+                HMAC,
+                hmac_metadata,
+                1,
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            crypto.export_key("unwrapped", FORMAT_RAW).unwrap(),
+            hex("c37b7e6492584340bed12207808941155068f738")
+        );
+    }
+
+    #[test]
+    fn ecdsa_matches_rfc_6979() {
+        let crypto = SoftwareCrypto::new();
+        let ecdsa = SigningAlgorithm::Ecdsa as i32;
+        for (curve, scalar, metadata, message, expected) in [
+            // RFC 6979 A.2.5, P-256 with SHA-256
+            (
+                Curve::P256,
+                "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+                ECDSA_SHA256,
+                "sample",
+                "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+            ),
+            // RFC 6979 A.2.6, P-384 with SHA-384
+            (
+                Curve::P384,
+                "6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5",
+                ECDSA_SHA384,
+                "sample",
+                "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8",
+            ),
+            // secp256k1 with the private key 1, normalized to a low S as in Bitcoin implementations
+            (
+                Curve::K256,
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                ECDSA_SHA256,
+                "Satoshi Nakamoto",
+                "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+            ),
+        ] {
+            let name = curve.name();
+            import_ec(&crypto, name, curve, scalar);
+            let signature = crypto
+                .sign(&mut TestRng(0), name, ecdsa, metadata, message.as_bytes())
+                .unwrap();
+            assert_eq!(signature, hex(expected), "{name}");
+            assert!(crypto.verify(name, ecdsa, metadata, message.as_bytes(), &signature).unwrap());
+            assert!(!crypto.verify(name, ecdsa, metadata, b"other", &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn ecdh_matches_nist_cavs() {
+        let crypto = SoftwareCrypto::new();
+        let ecdh = DerivationAlgorithm::Ecdh as i32;
+        // KAS ECC CDH primitive, COUNT = 0
+        for (curve, metadata, peer, scalar, expected) in [
+            (
+                Curve::P256,
+                r#"{"length":256}"#,
+                "04700c48f77f56584c5cc632ca65640db91b6bacce3a4df6b42ce7cc838833d287db71e509e3fd9b060ddb20ba5c51dcc5948d46fbf640dfe0441782cab85fa4ac",
+                "7d7dc5f71eb29ddaf80d6214632eeae03d9058af1fb6d22ed80badb62bc1a534",
+                "46fc62106420ff012e54a434fbdd2d25ccc5852060561e68040dd7778997bd7b",
+            ),
+            (
+                Curve::P384,
+                r#"{"length":384}"#,
+                "04a7c76b970c3b5fe8b05d2838ae04ab47697b9eaf52e764592efda27fe7513272734466b400091adbf2d68c58e0c50066ac68f19f2e1cb879aed43a9969b91a0839c4c38a49749b661efedf243451915ed0905a32b060992b468c64766fc8437a",
+                "3cc3122a68f0d95027ad38c067916ba0eb8c38894d22e1b15618b6818a661774ad463b205da88cf699ab4d43c9cf98a1",
+                "5f9d29dc5e31a163060356213669c8ce132e22f57c9a04f40ba7fcead493b457e5621e766c40a2e3d4d6a04b25e533f1",
+            ),
+        ] {
+            let name = curve.name();
+            let peer_name = format!("{name} peer");
+            import(&crypto, &peer_name, FORMAT_RAW, &hex(peer), SECP_R1, metadata);
+            import_ec(&crypto, name, curve, scalar);
+            let bits = expected.len() as i32 * 4;
+            let shared = crypto
+                .derive_bits(name, ecdh, &format!(r#"{{"public_key":"{peer_name}"}}"#), bits)
+                .unwrap();
+            assert_eq!(shared, hex(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn rsa_interoperates_with_openssl() {
+        let crypto = SoftwareCrypto::new();
+        let message = b"known answer";
+        import(
+            &crypto,
+            "rsa",
+            FORMAT_PKCS8,
+            &hex(RSA_PKCS8),
+            RSA,
+            RSA_1024_SHA256,
+        );
+
+        let oaep = EncryptionAlgorithm::RsaOaep as i32;
+        let pkcs1 = EncryptionAlgorithm::RsaPkcs1V1_5 as i32;
+        let pss = SigningAlgorithm::RsaPss as i32;
+        let oaep_metadata = r#"{"label":[]}"#;
+        let pss_metadata = r#"{"saltLength":32}"#;
+        assert_eq!(
+            crypto
+                .decrypt("rsa", oaep, oaep_metadata, &hex(RSA_OAEP_SHA256))
+                .unwrap(),
+            message
+        );
+        assert_eq!(
+            crypto.decrypt("rsa", pkcs1, "{}", &hex(RSA_PKCS1)).unwrap(),
+            message
+        );
+        assert!(crypto
+            .verify("rsa", pss, pss_metadata, message, &hex(RSA_PSS_SHA256))
+            .unwrap());
+
+        // What the backend produces is accepted by the public key alone
+        import(
+            &crypto,
+            "rsa public",
+            FORMAT_SPKI,
+            &hex(RSA_SPKI),
+            RSA,
+            RSA_1024_SHA256,
+        );
+        let signature = crypto
+            .sign(&mut TestRng(0), "rsa", pss, pss_metadata, message)
+            .unwrap();
+        assert!(crypto
+            .verify("rsa public", pss, pss_metadata, message, &signature)
+            .unwrap());
+        assert!(!crypto
+            .verify("rsa public", pss, pss_metadata, b"other", &signature)
+            .unwrap());
+        for (algorithm, metadata) in [(oaep, oaep_metadata), (pkcs1, "{}")] {
+            let ciphertext = crypto
+                .encrypt(&mut TestRng(0), "rsa public", algorithm, metadata, message)
+                .unwrap();
+            assert_eq!(
+                crypto
+                    .decrypt("rsa", algorithm, metadata, &ciphertext)
+                    .unwrap(),
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn keys_round_trip_through_export() {
+        let crypto = SoftwareCrypto::new();
+        import(
+            &crypto,
+            "rsa",
+            FORMAT_PKCS8,
+            &hex(RSA_PKCS8),
+            RSA,
+            RSA_1024_SHA256,
+        );
+        assert_eq!(
+            crypto.export_key("rsa", FORMAT_PKCS8).unwrap(),
+            hex(RSA_PKCS8)
+        );
+        assert_eq!(
+            crypto.export_key("rsa", FORMAT_SPKI).unwrap(),
+            hex(RSA_SPKI)
+        );
+        assert_eq!(crypto.get_public_key("rsa").unwrap(), hex(RSA_SPKI));
+        let pkcs1 = crypto.export_key("rsa", FORMAT_PKCS1).unwrap();
+        import(
+            &crypto,
+            "rsa pkcs1",
+            FORMAT_PKCS1,
+            &pkcs1,
+            RSA,
+            RSA_1024_SHA256,
+        );
+        assert_eq!(
+            crypto.export_key("rsa pkcs1", FORMAT_PKCS8).unwrap(),
+            hex(RSA_PKCS8)
+        );
+
+        let scalar = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+        let point = "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+        import_ec(&crypto, "ec", Curve::P256, scalar);
+        assert_eq!(crypto.export_key("ec", FORMAT_RAW).unwrap(), hex(point));
+        for format in [FORMAT_PKCS8, FORMAT_SEC1, FORMAT_SPKI] {
+            let der = crypto.export_key("ec", format).unwrap();
+            let name = format!("ec {format}");
+            import(&crypto, &name, format, &der, SECP_R1, r#"{"length":256}"#);
+            assert_eq!(
+                crypto.export_key(&name, FORMAT_RAW).unwrap(),
+                hex(point),
+                "{name}"
+            );
+        }
+
+        let aes = hex("000102030405060708090a0b0c0d0e0f");
+        import(&crypto, "aes", FORMAT_RAW, &aes, AES, r#"{"length":128}"#);
+        assert_eq!(crypto.export_key("aes", FORMAT_RAW).unwrap(), aes);
+
+        crypto
+            .import_key(
+                &mut TestRng(0),
+                "secret",
+                FORMAT_RAW,
+                &aes,
+                AES,
+                r#"{"length":128}"#,
+                0,
+                &[],
+            )
+            .unwrap();
+        assert!(crypto.export_key("secret", FORMAT_RAW).is_err());
+    }
+}
@@ -0,0 +1,682 @@
+use super::Host;
+use crate::bindings::klave::sdk::sdk;
+#[cfg(feature = "crypto-v2")]
+use crate::bindings::klave::sdk::sdk_v2;
+#[cfg(feature = "crypto-v2")]
+use crate::crypto::subtle_idl_v2 as v2;
+
+/// Host backed by the imports of the Klave runtime
+pub(crate) struct WasmHost;
+
+impl Host for WasmHost {
+    fn add_user_query(&self, query_name: &str) {
+        sdk::add_user_query(query_name)
+    }
+
+    fn add_user_transaction(&self, transaction_name: &str) {
+        sdk::add_user_transaction(transaction_name)
+    }
+
+    fn notify(&self, msg: &str) {
+        sdk::notify(msg)
+    }
+
+    fn notify_error(&self, msg: &str) {
+        sdk::notify_error(msg)
+    }
+
+    fn on_success_notify(&self, msg: &str) {
+        sdk::on_success_notify(msg)
+    }
+
+    fn query_context(&self, param: &str) -> Result<String, String> {
+        sdk::query_context(param)
+    }
+
+    fn read_ledger(&self, table: &str, key: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::read_ledger(table, key)
+    }
+
+    fn list_keys_from_ledger(&self, table: &str) -> Result<String, String> {
+        sdk::list_keys_from_ledger(table)
+    }
+
+    fn key_exists_in_ledger(&self, table: &str, key: &[u8]) -> Result<bool, String> {
+        sdk::key_exists_in_ledger(table, key)
+    }
+
+    fn write_ledger(&self, table: &str, key: &[u8], value: &[u8]) -> Result<(), String> {
+        sdk::write_ledger(table, key, value)
+    }
+
+    fn remove_from_ledger(&self, table: &str, key: &[u8]) -> Result<(), String> {
+        sdk::remove_from_ledger(table, key)
+    }
+
+    fn load_lightgbm_model(&self, name: &str, model: &str) -> Result<(), String> {
+        sdk::load_lightgbm_model(name, model)
+    }
+
+    fn unload_lightgbm_model(&self, name: &str) -> Result<(), String> {
+        sdk::unload_lightgbm_model(name)
+    }
+
+    fn infer_from_lightgbm_model(
+        &self,
+        name: &str,
+        data: &[f64],
+        nb_outputs: i32,
+    ) -> Result<Vec<f64>, String> {
+        sdk::infer_from_lightgbm_model(name, data, nb_outputs)
+    }
+
+    fn key_exists(&self, key_name: &str) -> Result<bool, String> {
+        sdk::key_exists(key_name)
+    }
+
+    fn generate_key(
+        &self,
+        key_name: &str,
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        sdk::generate_key(key_name, algorithm, algo_metadata, extractable, usages)
+    }
+
+    fn import_key(
+        &self,
+        key_name: &str,
+        key_format: i32,
+        key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        sdk::import_key(
+            key_name,
+            key_format,
+            key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        )
+    }
+
+    fn export_key(&self, key_name: &str, key_format: i32) -> Result<Vec<u8>, String> {
+        sdk::export_key(key_name, key_format)
+    }
+
+    fn get_public_key(&self, key_name: &str) -> Result<Vec<u8>, String> {
+        sdk::get_public_key(key_name)
+    }
+
+    fn get_public_key_as_cryptokey(&self, key_name: &str) -> Result<String, String> {
+        sdk::get_public_key_as_cryptokey(key_name)
+    }
+
+    fn derive_key(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        derived_key_algorithm: i32,
+        derived_key_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        sdk::derive_key(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            derived_key_algorithm,
+            derived_key_metadata,
+            extractable,
+            usages,
+        )
+    }
+
+    fn derive_bits(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: i32,
+        derivation_metadata: &str,
+        length: i32,
+    ) -> Result<Vec<u8>, String> {
+        sdk::derive_bits(
+            base_key_name,
+            derivation_algorithm,
+            derivation_metadata,
+            length,
+        )
+    }
+
+    fn encrypt(
+        &self,
+        key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+        plain_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk::encrypt(key_name, encrypt_algo_id, encrypt_metadata, plain_txt)
+    }
+
+    fn decrypt(
+        &self,
+        key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk::decrypt(key_name, decrypt_algo_id, decrypt_metadata, cipher_txt)
+    }
+
+    fn sign(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk::sign(key_name, sign_algo_id, sign_metadata, txt)
+    }
+
+    fn verify(
+        &self,
+        key_name: &str,
+        sign_algo_id: i32,
+        sign_metadata: &str,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, String> {
+        sdk::verify(key_name, sign_algo_id, sign_metadata, txt, signature)
+    }
+
+    fn digest(&self, algo_id: i32, hash_metadata: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::digest(algo_id, hash_metadata, txt)
+    }
+
+    fn unwrap_key(
+        &self,
+        decrypt_key_name: &str,
+        decrypt_algo_id: i32,
+        decrypt_metadata: &str,
+        key_name_to_import: &str,
+        key_format: i32,
+        wrapped_key_data: &[u8],
+        algorithm: i32,
+        algo_metadata: &str,
+        extractable: i32,
+        usages: &[u8],
+    ) -> Result<String, String> {
+        sdk::unwrap_key(
+            decrypt_key_name,
+            decrypt_algo_id,
+            decrypt_metadata,
+            key_name_to_import,
+            key_format,
+            wrapped_key_data,
+            algorithm,
+            algo_metadata,
+            extractable,
+            usages,
+        )
+    }
+
+    fn wrap_key(
+        &self,
+        key_name_to_export: &str,
+        key_format: i32,
+        encrypt_key_name: &str,
+        encrypt_algo_id: i32,
+        encrypt_metadata: &str,
+    ) -> Result<Vec<u8>, String> {
+        sdk::wrap_key(
+            key_name_to_export,
+            key_format,
+            encrypt_key_name,
+            encrypt_algo_id,
+            encrypt_metadata,
+        )
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn generate_key_v2(
+        &self,
+        key_name: &str,
+        algorithm: &v2::KeyAlgorithm,
+        extractable: bool,
+        usages: &[v2::KeyUsage],
+    ) -> Result<String, String> {
+        sdk_v2::generate_key(key_name, algorithm.into(), extractable, &usages_v2(usages))
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn import_key_v2(
+        &self,
+        key_name: &str,
+        key_format: v2::KeyFormat,
+        key_data: &[u8],
+        algorithm: &v2::KeyAlgorithm,
+        extractable: bool,
+        usages: &[v2::KeyUsage],
+    ) -> Result<String, String> {
+        sdk_v2::import_key(
+            key_name,
+            key_format.into(),
+            key_data,
+            algorithm.into(),
+            extractable,
+            &usages_v2(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn export_key_v2(&self, key_name: &str, key_format: v2::KeyFormat) -> Result<Vec<u8>, String> {
+        sdk_v2::export_key(key_name, key_format.into())
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn derive_key_v2(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: &v2::DerivationAlgorithm,
+        derived_key_algorithm: &v2::DerivedKeyAlgorithm,
+        extractable: bool,
+        usages: &[v2::KeyUsage],
+    ) -> Result<String, String> {
+        sdk_v2::derive_key(
+            base_key_name,
+            &derivation_algorithm.into(),
+            derived_key_algorithm.into(),
+            extractable,
+            &usages_v2(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn derive_bits_v2(
+        &self,
+        base_key_name: &str,
+        derivation_algorithm: &v2::DerivationAlgorithm,
+        length: u32,
+    ) -> Result<Vec<u8>, String> {
+        sdk_v2::derive_bits(base_key_name, &derivation_algorithm.into(), length)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn encrypt_v2(
+        &self,
+        key_name: &str,
+        algorithm: &v2::EncryptionAlgorithm,
+        plain_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk_v2::encrypt(key_name, &algorithm.into(), plain_txt)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn decrypt_v2(
+        &self,
+        key_name: &str,
+        algorithm: &v2::EncryptionAlgorithm,
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk_v2::decrypt(key_name, &algorithm.into(), cipher_txt)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn sign_v2(
+        &self,
+        key_name: &str,
+        algorithm: &v2::SigningAlgorithm,
+        txt: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        sdk_v2::sign(key_name, algorithm.into(), txt)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn verify_v2(
+        &self,
+        key_name: &str,
+        algorithm: &v2::SigningAlgorithm,
+        txt: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, String> {
+        sdk_v2::verify(key_name, algorithm.into(), txt, signature)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn digest_v2(&self, algorithm: v2::ShaAlgorithm, txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk_v2::digest(algorithm.into(), txt)
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn unwrap_key_v2(
+        &self,
+        decrypt_key_name: &str,
+        algorithm: &v2::WrappingAlgorithm,
+        key_name_to_import: &str,
+        key_format: v2::KeyFormat,
+        wrapped_key_data: &[u8],
+        key_algorithm: &v2::KeyAlgorithm,
+        extractable: bool,
+        usages: &[v2::KeyUsage],
+    ) -> Result<String, String> {
+        sdk_v2::unwrap_key(
+            decrypt_key_name,
+            &algorithm.into(),
+            key_name_to_import,
+            key_format.into(),
+            wrapped_key_data,
+            key_algorithm.into(),
+            extractable,
+            &usages_v2(usages),
+        )
+    }
+
+    #[cfg(feature = "crypto-v2")]
+    fn wrap_key_v2(
+        &self,
+        key_name_to_export: &str,
+        key_format: v2::KeyFormat,
+        encrypt_key_name: &str,
+        algorithm: &v2::WrappingAlgorithm,
+    ) -> Result<Vec<u8>, String> {
+        sdk_v2::wrap_key(
+            key_name_to_export,
+            key_format.into(),
+            encrypt_key_name,
+            &algorithm.into(),
+        )
+    }
+
+    fn save_key(&self, key_name: &str) -> Result<(), String> {
+        sdk::save_key(key_name)
+    }
+
+    fn persist_key(&self, key_persist_params: &str) -> Result<(), String> {
+        sdk::persist_key(key_persist_params)
+    }
+
+    fn load_key(&self, key_name: &str) -> Result<String, String> {
+        sdk::load_key(key_name)
+    }
+
+    fn delete_key(&self, key_name: &str) -> Result<(), String> {
+        sdk::delete_key(key_name)
+    }
+
+    fn generate_simple_encryption_key(&self, key_name: &str) -> Result<(), String> {
+        sdk::generate_simple_encryption_key(key_name)
+    }
+
+    fn generate_simple_signing_key(&self, key_name: &str) -> Result<(), String> {
+        sdk::generate_simple_signing_key(key_name)
+    }
+
+    fn simple_encrypt(&self, key_name: &str, plain_txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::simple_encrypt(key_name, plain_txt)
+    }
+
+    fn simple_decrypt(&self, key_name: &str, cipher_txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::simple_decrypt(key_name, cipher_txt)
+    }
+
+    fn simple_sign(&self, key_name: &str, txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::simple_sign(key_name, txt)
+    }
+
+    fn simple_verify(&self, key_name: &str, txt: &[u8], signature: &[u8]) -> Result<bool, String> {
+        sdk::simple_verify(key_name, txt, signature)
+    }
+
+    fn simple_digest(&self, txt: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::simple_digest(txt)
+    }
+
+    fn get_random_bytes(&self, len: i32) -> Result<Vec<u8>, String> {
+        sdk::get_random_bytes(len)
+    }
+
+    fn https_query(&self, request: &str) -> Result<String, String> {
+        sdk::https_query(request)
+    }
+
+    fn get_quote(&self, challenge: &[u8]) -> Result<Vec<u8>, String> {
+        sdk::get_quote(challenge)
+    }
+
+    fn verify_quote(&self, current_time: i64, quote_binary: &[u8]) -> Result<String, String> {
+        sdk::verify_quote(current_time, quote_binary)
+    }
+
+    fn parse_quote(&self, quote: &[u8]) -> Result<String, String> {
+        sdk::parse_quote(quote)
+    }
+
+    fn connection_open(&self, uri: &str) -> Result<String, String> {
+        sdk::connection_open(uri)
+    }
+
+    fn sql_query(&self, connection: &str, query: &str) -> Result<String, String> {
+        sdk::sql_query(connection, query)
+    }
+
+    fn sql_exec(&self, connection: &str, command: &str) -> Result<String, String> {
+        sdk::sql_exec(connection, command)
+    }
+
+    fn start_recording(&self) {
+        sdk::start_recording()
+    }
+
+    fn stop_recording(&self) {
+        sdk::stop_recording()
+    }
+
+    fn cancel_transaction(&self) {
+        sdk::cancel_transaction()
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+fn usages_v2(usages: &[v2::KeyUsage]) -> Vec<sdk_v2::KeyUsage> {
+    usages
+        .iter()
+        .map(|usage| match usage {
+            v2::KeyUsage::Encrypt => sdk_v2::KeyUsage::Encrypt,
+            v2::KeyUsage::Decrypt => sdk_v2::KeyUsage::Decrypt,
+            v2::KeyUsage::Sign => sdk_v2::KeyUsage::Sign,
+            v2::KeyUsage::Verify => sdk_v2::KeyUsage::Verify,
+            v2::KeyUsage::DeriveKey => sdk_v2::KeyUsage::DeriveKey,
+            v2::KeyUsage::DeriveBits => sdk_v2::KeyUsage::DeriveBits,
+            v2::KeyUsage::WrapKey => sdk_v2::KeyUsage::WrapKey,
+            v2::KeyUsage::UnwrapKey => sdk_v2::KeyUsage::UnwrapKey,
+        })
+        .collect()
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<v2::KeyFormat> for sdk_v2::KeyFormat {
+    fn from(format: v2::KeyFormat) -> Self {
+        match format {
+            v2::KeyFormat::Raw => sdk_v2::KeyFormat::Raw,
+            v2::KeyFormat::Spki => sdk_v2::KeyFormat::Spki,
+            v2::KeyFormat::Pkcs8 => sdk_v2::KeyFormat::Pkcs8,
+            v2::KeyFormat::Jwk => sdk_v2::KeyFormat::Jwk,
+            v2::KeyFormat::Sec1 => sdk_v2::KeyFormat::Sec1,
+            v2::KeyFormat::Pkcs1 => sdk_v2::KeyFormat::Pkcs1,
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<v2::ShaSize> for sdk_v2::ShaSize {
+    fn from(size: v2::ShaSize) -> Self {
+        match size {
+            v2::ShaSize::Bits256 => sdk_v2::ShaSize::Bits256,
+            v2::ShaSize::Bits384 => sdk_v2::ShaSize::Bits384,
+            v2::ShaSize::Bits512 => sdk_v2::ShaSize::Bits512,
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<v2::ShaAlgorithm> for sdk_v2::ShaAlgorithm {
+    fn from(algorithm: v2::ShaAlgorithm) -> Self {
+        match algorithm {
+            v2::ShaAlgorithm::Sha1 => sdk_v2::ShaAlgorithm::Sha1,
+            v2::ShaAlgorithm::Sha2(size) => sdk_v2::ShaAlgorithm::Sha2(size.into()),
+            v2::ShaAlgorithm::Sha3(size) => sdk_v2::ShaAlgorithm::Sha3(size.into()),
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<v2::AesKeySize> for sdk_v2::AesKeySize {
+    fn from(size: v2::AesKeySize) -> Self {
+        match size {
+            v2::AesKeySize::Bits128 => sdk_v2::AesKeySize::Bits128,
+            v2::AesKeySize::Bits192 => sdk_v2::AesKeySize::Bits192,
+            v2::AesKeySize::Bits256 => sdk_v2::AesKeySize::Bits256,
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::HmacParams> for sdk_v2::HmacParams {
+    fn from(params: &v2::HmacParams) -> Self {
+        sdk_v2::HmacParams {
+            hash: params.hash.into(),
+            length: params.length,
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::KeyAlgorithm> for sdk_v2::KeyAlgorithm {
+    fn from(algorithm: &v2::KeyAlgorithm) -> Self {
+        match algorithm {
+            v2::KeyAlgorithm::SecpR1(curve) => sdk_v2::KeyAlgorithm::SecpR1(match curve {
+                v2::SecpR1Curve::P256 => sdk_v2::SecpR1Curve::P256,
+                v2::SecpR1Curve::P384 => sdk_v2::SecpR1Curve::P384,
+                v2::SecpR1Curve::P521 => sdk_v2::SecpR1Curve::P521,
+            }),
+            v2::KeyAlgorithm::SecpK1 => sdk_v2::KeyAlgorithm::SecpK1,
+            v2::KeyAlgorithm::Aes(size) => sdk_v2::KeyAlgorithm::Aes((*size).into()),
+            v2::KeyAlgorithm::Rsa(params) => sdk_v2::KeyAlgorithm::Rsa(sdk_v2::RsaParams {
+                modulus: match params.modulus {
+                    v2::RsaModulus::Bits2048 => sdk_v2::RsaModulus::Bits2048,
+                    v2::RsaModulus::Bits3072 => sdk_v2::RsaModulus::Bits3072,
+                    v2::RsaModulus::Bits4096 => sdk_v2::RsaModulus::Bits4096,
+                },
+                public_exponent: params.public_exponent,
+                hash: params.hash.into(),
+            }),
+            v2::KeyAlgorithm::Hmac(params) => sdk_v2::KeyAlgorithm::Hmac(params.into()),
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::AesGcmParams> for sdk_v2::AesGcmParams {
+    fn from(params: &v2::AesGcmParams) -> Self {
+        sdk_v2::AesGcmParams {
+            iv: params.iv.clone(),
+            additional_data: params.additional_data.clone(),
+            tag_length: match params.tag_length {
+                v2::AesTagLength::Bits96 => sdk_v2::AesTagLength::Bits96,
+                v2::AesTagLength::Bits104 => sdk_v2::AesTagLength::Bits104,
+                v2::AesTagLength::Bits112 => sdk_v2::AesTagLength::Bits112,
+                v2::AesTagLength::Bits120 => sdk_v2::AesTagLength::Bits120,
+                v2::AesTagLength::Bits128 => sdk_v2::AesTagLength::Bits128,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::RsaOaepParams> for sdk_v2::RsaOaepParams {
+    fn from(params: &v2::RsaOaepParams) -> Self {
+        sdk_v2::RsaOaepParams {
+            label: params.label.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::EncryptionAlgorithm> for sdk_v2::EncryptionAlgorithm {
+    fn from(algorithm: &v2::EncryptionAlgorithm) -> Self {
+        match algorithm {
+            v2::EncryptionAlgorithm::AesGcm(params) => {
+                sdk_v2::EncryptionAlgorithm::AesGcm(params.into())
+            }
+            v2::EncryptionAlgorithm::RsaOaep(params) => {
+                sdk_v2::EncryptionAlgorithm::RsaOaep(params.into())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::WrappingAlgorithm> for sdk_v2::WrappingAlgorithm {
+    fn from(algorithm: &v2::WrappingAlgorithm) -> Self {
+        match algorithm {
+            v2::WrappingAlgorithm::AesKw(with_padding) => {
+                sdk_v2::WrappingAlgorithm::AesKw(*with_padding)
+            }
+            v2::WrappingAlgorithm::AesGcm(params) => {
+                sdk_v2::WrappingAlgorithm::AesGcm(params.into())
+            }
+            v2::WrappingAlgorithm::RsaOaep(params) => {
+                sdk_v2::WrappingAlgorithm::RsaOaep(params.into())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::SigningAlgorithm> for sdk_v2::SigningAlgorithm {
+    fn from(algorithm: &v2::SigningAlgorithm) -> Self {
+        match algorithm {
+            v2::SigningAlgorithm::Ecdsa(hash) => sdk_v2::SigningAlgorithm::Ecdsa((*hash).into()),
+            v2::SigningAlgorithm::RsaPss(salt_length) => {
+                sdk_v2::SigningAlgorithm::RsaPss(*salt_length)
+            }
+            v2::SigningAlgorithm::Hmac => sdk_v2::SigningAlgorithm::Hmac,
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::DerivationAlgorithm> for sdk_v2::DerivationAlgorithm {
+    fn from(algorithm: &v2::DerivationAlgorithm) -> Self {
+        match algorithm {
+            v2::DerivationAlgorithm::Ecdh(public_key) => {
+                sdk_v2::DerivationAlgorithm::Ecdh(public_key.clone())
+            }
+            v2::DerivationAlgorithm::Hkdf(params) => {
+                sdk_v2::DerivationAlgorithm::Hkdf(sdk_v2::HkdfParams {
+                    salt: params.salt.clone(),
+                    info: params.info.clone(),
+                    hash: params.hash.into(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crypto-v2")]
+impl From<&v2::DerivedKeyAlgorithm> for sdk_v2::DerivedKeyAlgorithm {
+    fn from(algorithm: &v2::DerivedKeyAlgorithm) -> Self {
+        match algorithm {
+            v2::DerivedKeyAlgorithm::Aes(size) => sdk_v2::DerivedKeyAlgorithm::Aes((*size).into()),
+            v2::DerivedKeyAlgorithm::Hmac(params) => {
+                sdk_v2::DerivedKeyAlgorithm::Hmac(params.into())
+            }
+        }
+    }
+}
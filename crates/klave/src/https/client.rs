@@ -0,0 +1,465 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use http::header::{
+    HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, COOKIE, PROXY_AUTHORIZATION,
+};
+use http::{HeaderMap, Method, Request, Response, StatusCode, Uri};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Display;
+
+/// Non-success response, kept as the source of the error returned by [`Client`]
+#[derive(Debug, Clone)]
+pub struct StatusError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl StatusError {
+    /// Status error behind `error`, if it comes from a response of a [`Client`]
+    pub fn of(error: &Error) -> Option<&StatusError> {
+        std::error::Error::source(error).and_then(|source| source.downcast_ref::<StatusError>())
+    }
+}
+
+impl Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "HTTP {}: {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// Retries of idempotent requests that failed to reach the server or got a 408, 429 or 5xx status.
+///
+/// Apps run without a timer on the Klave host, so attempts follow each other immediately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Including the first one
+    pub max_attempts: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_attempts: 3 }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> RetryPolicy {
+        RetryPolicy { max_attempts: 1 }
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+/// Error for a non-success `response`, with the credentials of `request` redacted from its body
+fn status_error(request: &Request<String>, response: Response<String>) -> Error {
+    let status = response.status();
+    let kind = match status {
+        StatusCode::NOT_FOUND => ErrorKind::NotFound,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+        StatusCode::TOO_MANY_REQUESTS => ErrorKind::RateLimited,
+        StatusCode::CONFLICT => ErrorKind::AlreadyExists,
+        status if status.is_client_error() => ErrorKind::InvalidInput,
+        _ => ErrorKind::Host,
+    };
+    Error::new(Subsystem::Https, kind, "Request failed").with_source(StatusError {
+        status,
+        body: super::redact(request.headers(), response.body()),
+    })
+}
+
+/// HTTPS client sending requests relative to a base URL, with default headers and retries.
+///
+/// Responses with a status other than 2xx are turned into errors whose kind follows the
+/// status, e.g. [`ErrorKind::NotFound`] for 404, with a [`StatusError`] as source.
+///
+/// Requests have no timeout: `https-query` takes none and an app cannot interrupt it,
+/// so each attempt lasts as long as the host lets it.
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    headers: HeaderMap,
+    retry: RetryPolicy,
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// URL of `path`; absolute URLs must have the scheme, host and port of the base URL,
+    /// so that the default headers, credentials included, never go to another server
+    fn url(&self, path: &str) -> Result<String> {
+        let is_absolute = path.split_once("://").is_some_and(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+        });
+        if !is_absolute {
+            if self.base_url.is_empty() {
+                return Err(Error::invalid_input(
+                    Subsystem::Https,
+                    format!("Invalid path '{path}': the client has no base URL"),
+                ));
+            }
+            return Ok(format!(
+                "{}/{}",
+                self.base_url.trim_end_matches('/'),
+                path.trim_start_matches('/')
+            ));
+        }
+        if !self.base_url.is_empty() && origin(path)? != origin(&self.base_url)? {
+            return Err(Error::new(
+                Subsystem::Https,
+                ErrorKind::PermissionDenied,
+                format!("URL '{path}' is not on the server of the base URL"),
+            ));
+        }
+        Ok(path.to_string())
+    }
+
+    /// Request builder for `path`, with the default headers.
+    ///
+    /// Fails if `path` is an absolute URL to another server than the base URL.
+    pub fn request(&self, method: Method, path: &str) -> Result<http::request::Builder> {
+        let mut builder = Request::builder().method(method).uri(self.url(path)?);
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(self.headers.clone());
+        }
+        Ok(builder)
+    }
+
+    /// Request for `path` with `body`, accepting, and sending if it has a body, JSON
+    fn json_request_with(
+        &self,
+        method: Method,
+        path: &str,
+        body: String,
+    ) -> Result<Request<String>> {
+        let mut builder = self.request(method, path)?;
+        if let Some(headers) = builder.headers_mut() {
+            let json = HeaderValue::from_static("application/json");
+            headers.insert(ACCEPT, json.clone());
+            if !body.is_empty() {
+                headers.insert(CONTENT_TYPE, json);
+            }
+        }
+        builder
+            .body(body)
+            .or_error(Subsystem::Https, ErrorKind::InvalidInput, "Invalid request")
+    }
+
+    /// Send `request`, retrying it if it is idempotent, and fail on non-success statuses
+    pub fn send(&self, request: &Request<String>) -> Result<Response<String>> {
+        let attempts = if request.method().is_idempotent() {
+            self.retry.max_attempts.max(1)
+        } else {
+            1
+        };
+        let mut attempt = 1;
+        loop {
+            let retry = attempt < attempts;
+            match super::request(request) {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if !(retry && is_retryable(response.status())) => {
+                    return Err(status_error(request, response))
+                }
+                Err(err) if !(retry && err.kind() == ErrorKind::Host) => return Err(err),
+                _ => (),
+            }
+            attempt += 1;
+        }
+    }
+
+    fn send_json<TResp: DeserializeOwned>(&self, request: Request<String>) -> Result<TResp> {
+        let response = self.send(&request)?;
+        serde_json::from_str(response.body()).or_error(
+            Subsystem::Https,
+            ErrorKind::Serialization,
+            "Failed to deserialize response body",
+        )
+    }
+
+    fn json_request<TReq: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &TReq,
+    ) -> Result<Request<String>> {
+        let body = serde_json::to_string(body).or_error(
+            Subsystem::Https,
+            ErrorKind::Serialization,
+            "Failed to serialize request body",
+        )?;
+        self.json_request_with(method, path, body)
+    }
+
+    pub fn get_json<TResp: DeserializeOwned>(&self, path: &str) -> Result<TResp> {
+        self.send_json(self.json_request_with(Method::GET, path, String::new())?)
+    }
+
+    pub fn post_json<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &TReq,
+    ) -> Result<TResp> {
+        self.send_json(self.json_request(Method::POST, path, body)?)
+    }
+
+    pub fn put_json<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &TReq,
+    ) -> Result<TResp> {
+        self.send_json(self.json_request(Method::PUT, path, body)?)
+    }
+}
+
+#[derive(Default)]
+pub struct ClientBuilder {
+    base_url: String,
+    headers: Vec<(String, String)>,
+    retry: RetryPolicy,
+}
+
+impl ClientBuilder {
+    /// Prefix of the paths given to the client, e.g. `https://api.example.com/v1`
+    pub fn base_url(mut self, base_url: &str) -> ClientBuilder {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Header sent with every request; values of credential headers, e.g. `authorization`
+    /// or `x-api-key`, are marked sensitive so they are redacted from debug output and errors
+    pub fn header(mut self, name: &str, value: &str) -> ClientBuilder {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Retries of idempotent requests; attempts follow each other immediately, so prefer few of them
+    pub fn retry(mut self, retry: RetryPolicy) -> ClientBuilder {
+        self.retry = retry;
+        self
+    }
+
+    /// Fails if the base URL is not HTTPS or a header is invalid
+    pub fn build(self) -> Result<Client> {
+        if !self.base_url.is_empty() && !self.base_url.starts_with("https://") {
+            return Err(Error::invalid_input(
+                Subsystem::Https,
+                format!(
+                    "Invalid base URL '{}': must start with https://",
+                    self.base_url
+                ),
+            ));
+        }
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = name.parse::<HeaderName>().or_error(
+                Subsystem::Https,
+                ErrorKind::InvalidInput,
+                &format!("Invalid header name '{name}'"),
+            )?;
+            let mut value = value.parse::<HeaderValue>().or_error(
+                Subsystem::Https,
+                ErrorKind::InvalidInput,
+                &format!("Invalid value for header '{name}'"),
+            )?;
+            value.set_sensitive(is_credential(&name));
+            headers.append(name, value);
+        }
+        Ok(Client {
+            base_url: self.base_url,
+            headers,
+            retry: self.retry,
+        })
+    }
+}
+
+/// Scheme, lowercase host and port of an absolute URL
+fn origin(url: &str) -> Result<(String, String, u16)> {
+    let uri = url.parse::<Uri>().or_error(
+        Subsystem::Https,
+        ErrorKind::InvalidInput,
+        &format!("Invalid URL '{url}'"),
+    )?;
+    let (Some(scheme), Some(host)) = (uri.scheme_str(), uri.host()) else {
+        return Err(Error::invalid_input(
+            Subsystem::Https,
+            format!("Invalid URL '{url}': expected a scheme and a host"),
+        ));
+    };
+    Ok((
+        scheme.to_ascii_lowercase(),
+        host.to_ascii_lowercase(),
+        uri.port_u16().unwrap_or(443),
+    ))
+}
+
+fn is_credential(name: &HeaderName) -> bool {
+    *name == AUTHORIZATION
+        || *name == PROXY_AUTHORIZATION
+        || *name == COOKIE
+        || ["key", "token", "secret", "password", "signature"]
+            .iter()
+            .any(|part| name.as_str().contains(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn client(retry: RetryPolicy) -> Client {
+        Client::builder()
+            .base_url("https://api.example.com/v1")
+            .header("authorization", "Bearer s3cr3t")
+            .header("x-api-key", "k3y")
+            .header("x-request-source", "tests")
+            .retry(retry)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn credential_headers_are_sensitive() {
+        let client = client(RetryPolicy::none());
+        let request = client
+            .request(Method::GET, "/items")
+            .unwrap()
+            .body(())
+            .unwrap();
+        assert_eq!(request.uri(), "https://api.example.com/v1/items");
+        assert!(request.headers()["authorization"].is_sensitive());
+        assert!(request.headers()["x-api-key"].is_sensitive());
+        assert!(!request.headers()["x-request-source"].is_sensitive());
+
+        let debug = format!("{client:?}");
+        assert!(!debug.contains("s3cr3t") && !debug.contains("k3y"));
+        assert!(debug.contains("tests"));
+    }
+
+    #[test]
+    fn idempotent_requests_are_retried() {
+        let host = install(MockHost::new());
+        let attempts = Rc::new(Cell::new(0));
+        let counter = attempts.clone();
+        host.set_https_responder(move |_| {
+            counter.set(counter.get() + 1);
+            let status = if counter.get() < 3 { 503 } else { 200 };
+            Ok(Response::builder()
+                .status(status)
+                .body(r#"{"ok":true}"#.to_string())
+                .unwrap())
+        });
+        let retry = RetryPolicy { max_attempts: 3 };
+
+        let value: serde_json::Value = client(retry.clone()).get_json("/items").unwrap();
+        assert_eq!(value, serde_json::json!({ "ok": true }));
+        assert_eq!(attempts.get(), 3);
+
+        attempts.set(0);
+        let err = client(retry)
+            .post_json::<_, serde_json::Value>("/items", &1)
+            .unwrap_err();
+        assert_eq!(attempts.get(), 1);
+        assert_eq!(
+            StatusError::of(&err).unwrap().status,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn statuses_map_to_error_kinds() {
+        let host = install(MockHost::new());
+        host.set_https_responder(|_| {
+            Ok(Response::builder()
+                .status(404)
+                .body("no such item".to_string())
+                .unwrap())
+        });
+        let err = client(RetryPolicy::none())
+            .get_json::<serde_json::Value>("/items/1")
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(StatusError::of(&err).unwrap().body, "no such item");
+    }
+
+    #[test]
+    fn status_bodies_are_redacted() {
+        let host = install(MockHost::new());
+        host.set_https_responder(|request| {
+            Ok(Response::builder()
+                .status(401)
+                .body(format!(
+                    "invalid credentials {:?}",
+                    request.headers()["authorization"]
+                ))
+                .unwrap())
+        });
+        let err = client(RetryPolicy::none())
+            .get_json::<serde_json::Value>("/items")
+            .unwrap_err();
+        let status = StatusError::of(&err).unwrap();
+        assert_eq!(status.body, r#"invalid credentials "[REDACTED]""#);
+        assert!(!err.to_string().contains("s3cr3t"));
+    }
+
+    #[test]
+    fn absolute_urls_stay_on_the_base_server() {
+        let client = client(RetryPolicy::none());
+        for url in [
+            "https://api.example.com/v2/items",
+            "https://API.example.com:443/items?next=https://other.example.com",
+        ] {
+            assert!(client.request(Method::GET, url).is_ok(), "{url}");
+        }
+        for url in [
+            "https://evil.example.com/items",
+            "https://api.example.com:8443/items",
+            "http://api.example.com/items",
+            "https://api.example.com.evil.com/items",
+        ] {
+            assert_eq!(
+                client.request(Method::GET, url).unwrap_err().kind(),
+                ErrorKind::PermissionDenied,
+                "{url}"
+            );
+        }
+        let request = client
+            .request(Method::GET, "/redirect?to=https://evil.example.com")
+            .unwrap()
+            .body(())
+            .unwrap();
+        assert_eq!(request.uri().host(), Some("api.example.com"));
+    }
+
+    #[test]
+    fn json_headers_replace_the_defaults() {
+        let host = install(MockHost::new());
+        host.set_https_responder(|request| {
+            assert_eq!(request.headers().get_all(ACCEPT).iter().count(), 1);
+            assert_eq!(request.headers()[ACCEPT], "application/json");
+            assert_eq!(request.headers().get_all(CONTENT_TYPE).iter().count(), 1);
+            if request.method() == Method::POST {
+                assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+            }
+            Ok(Response::new("null".to_string()))
+        });
+        let client = Client::builder()
+            .base_url("https://api.example.com")
+            .header("accept", "text/plain")
+            .header("content-type", "text/plain")
+            .build()
+            .unwrap();
+        client.get_json::<()>("/items").unwrap();
+        client.post_json::<_, ()>("/items", &1).unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+use crate::error::{Error, ErrorKind, Result, Subsystem};
+use std::cell::RefCell;
+
+thread_local! {
+    static POLICY: RefCell<Option<EgressPolicy>> = const { RefCell::new(None) };
+}
+
+/// Destination allowed by an [`EgressPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressRule {
+    host: String,
+    ports: Vec<u16>,
+    path: String,
+}
+
+impl EgressRule {
+    /// Requests to `host` on port 443, any path.
+    ///
+    /// `*.example.com` allows every subdomain of `example.com`, but not `example.com` itself.
+    pub fn host(host: &str) -> EgressRule {
+        EgressRule {
+            host: host.to_ascii_lowercase(),
+            ports: vec![443],
+            path: "*".to_string(),
+        }
+    }
+
+    /// Allowed ports, instead of 443 only
+    pub fn ports(mut self, ports: &[u16]) -> EgressRule {
+        self.ports = ports.to_vec();
+        self
+    }
+
+    /// Pattern the path must match, without the query; `*` matches any sequence of characters.
+    ///
+    /// Paths are matched once percent-encoded unreserved characters are decoded and `.` and `..`
+    /// segments are resolved, as the server would; paths with an encoded `/` or `\`, a `\`,
+    /// or a `..` above the root match no rule.
+    pub fn path(mut self, pattern: &str) -> EgressRule {
+        self.path = pattern.to_string();
+        self
+    }
+
+    fn matches(&self, host: &str, port: u16, path: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let host_matches = match self.host.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+            None => host == self.host,
+        };
+        host_matches
+            && self.ports.contains(&port)
+            && normalize_path(path).is_some_and(|path| glob(&self.path, &path))
+    }
+}
+
+/// Destinations the app may send HTTPS requests to.
+///
+/// Once set with [`set_egress_policy`], requests matching none of its rules are rejected
+/// before reaching the host; without a policy, every destination is allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EgressPolicy {
+    rules: Vec<EgressRule>,
+}
+
+impl EgressPolicy {
+    pub fn new() -> EgressPolicy {
+        EgressPolicy::default()
+    }
+
+    pub fn allow(mut self, rule: EgressRule) -> EgressPolicy {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn allows(&self, host: &str, port: u16, path: &str) -> bool {
+        self.rules.iter().any(|rule| rule.matches(host, port, path))
+    }
+}
+
+/// Restrict the destinations of HTTPS requests to `policy`.
+///
+/// The policy cannot be changed once set, so that code running later, such as a dependency,
+/// cannot widen it; setting the same policy again is allowed.
+pub fn set_egress_policy(policy: EgressPolicy) -> Result<()> {
+    POLICY.with(|current| {
+        let mut current = current.borrow_mut();
+        match current.as_ref() {
+            Some(existing) if *existing != policy => Err(Error::new(
+                Subsystem::Https,
+                ErrorKind::AlreadyExists,
+                "An egress policy is already set",
+            )),
+            _ => {
+                *current = Some(policy);
+                Ok(())
+            }
+        }
+    })
+}
+
+pub(super) fn check(host: &str, port: u16, path: &str) -> Result<()> {
+    POLICY.with(|policy| match policy.borrow().as_ref() {
+        Some(policy) if !policy.allows(host, port, path) => Err(Error::new(
+            Subsystem::Https,
+            ErrorKind::PermissionDenied,
+            format!("Egress policy does not allow requests to {host}:{port}{path}"),
+        )),
+        _ => Ok(()),
+    })
+}
+
+fn glob(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Path as resolved by the server, or `None` if it could be resolved differently by servers
+fn normalize_path(path: &str) -> Option<String> {
+    let decoded = decode_unreserved(path);
+    let lowercase = decoded.to_ascii_lowercase();
+    if decoded.contains('\\') || lowercase.contains("%2f") || lowercase.contains("%5c") {
+        return None;
+    }
+    let mut segments = Vec::new();
+    let mut trailing_slash = false;
+    for segment in decoded.strip_prefix('/').unwrap_or(&decoded).split('/') {
+        trailing_slash = matches!(segment, "." | "..");
+        match segment {
+            "." => (),
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    if trailing_slash {
+        segments.push("");
+    }
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Decode the percent-encoded letters, digits, `-`, `.`, `_` and `~` of `path`
+fn decode_unreserved(path: &str) -> String {
+    let mut decoded = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(index) = rest.find('%') {
+        decoded.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let byte = rest
+            .get(1..3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'));
+        match byte {
+            Some(byte) => {
+                decoded.push(byte as char);
+                rest = &rest[3..];
+            }
+            None => {
+                decoded.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> EgressPolicy {
+        EgressPolicy::new().allow(EgressRule::host("api.example.com").path("/v1/*"))
+    }
+
+    #[test]
+    fn paths_are_matched_once_resolved() {
+        let policy = policy();
+        for path in [
+            "/v1/items",
+            "/v1/./items",
+            "/v1/a/../items",
+            "/v%31/items",
+            "/%761/%7Eme",
+        ] {
+            assert!(policy.allows("api.example.com", 443, path), "{path}");
+        }
+        assert!(!policy.allows("api.example.com", 443, "/v2/items"));
+        assert!(!policy.allows("api.example.com", 8443, "/v1/items"));
+        assert!(!policy.allows("other.example.com", 443, "/v1/items"));
+    }
+
+    #[test]
+    fn path_traversals_are_rejected() {
+        let policy = policy();
+        for path in [
+            "/v1/../admin",
+            "/v1/items/../../admin",
+            "/v1/%2e%2e/admin",
+            "/v1/%2E%2E/admin",
+            "/v1/.%2e/admin",
+            "/v1/..%2fadmin",
+            "/v1/%2e%2e%2Fadmin",
+            "/v1/..\\admin",
+            "/v1/..%5cadmin",
+            "/../v1/items",
+        ] {
+            assert!(!policy.allows("api.example.com", 443, path), "{path}");
+        }
+    }
+
+    #[test]
+    fn dot_segments_resolve_like_servers() {
+        assert_eq!(normalize_path("/a/b/../c/./d").as_deref(), Some("/a/c/d"));
+        assert_eq!(normalize_path("/a/b/..").as_deref(), Some("/a/"));
+        assert_eq!(normalize_path("/a/%2e").as_deref(), Some("/a/"));
+        assert_eq!(normalize_path("").as_deref(), Some("/"));
+        assert_eq!(normalize_path("/a%20b/%zz").as_deref(), Some("/a%20b/%zz"));
+        assert_eq!(normalize_path("/.."), None);
+    }
+}
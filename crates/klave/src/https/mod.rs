@@ -0,0 +1,320 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::sdk;
+use base64::{engine::general_purpose, Engine as _};
+use http::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+mod client;
+mod egress;
+#[cfg(feature = "crypto")]
+pub mod sigv4;
+
+pub use client::{Client, ClientBuilder, RetryPolicy, StatusError};
+pub use egress::{set_egress_policy, EgressPolicy, EgressRule};
+
+/// Encoding of a body in the `https-query` protocol.
+///
+/// Bodies are sent as text unless they say otherwise, so that hosts unaware of binary bodies
+/// keep working with text ones.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyEncoding {
+    #[default]
+    Utf8,
+    Base64,
+}
+
+impl BodyEncoding {
+    fn is_utf8(&self) -> bool {
+        *self == BodyEncoding::Utf8
+    }
+}
+
+/// Body of an HTTPS request or response, either text or binary
+pub trait Body: Sized {
+    /// Encoding of the bodies of this type, also asked for the response
+    const ENCODING: BodyEncoding;
+
+    fn encode(&self) -> String;
+
+    fn decode(body: String, encoding: BodyEncoding) -> Result<Self>;
+}
+
+fn decode_base64(body: &str) -> Result<Vec<u8>> {
+    general_purpose::STANDARD.decode(body).or_error(
+        Subsystem::Https,
+        ErrorKind::Serialization,
+        "Body is not valid base64",
+    )
+}
+
+impl Body for String {
+    const ENCODING: BodyEncoding = BodyEncoding::Utf8;
+
+    fn encode(&self) -> String {
+        self.clone()
+    }
+
+    fn decode(body: String, encoding: BodyEncoding) -> Result<Self> {
+        match encoding {
+            BodyEncoding::Utf8 => Ok(body),
+            BodyEncoding::Base64 => String::from_utf8(decode_base64(&body)?).or_error(
+                Subsystem::Https,
+                ErrorKind::Serialization,
+                "Body is not valid UTF-8",
+            ),
+        }
+    }
+}
+
+impl Body for Vec<u8> {
+    const ENCODING: BodyEncoding = BodyEncoding::Base64;
+
+    fn encode(&self) -> String {
+        general_purpose::STANDARD.encode(self)
+    }
+
+    fn decode(body: String, encoding: BodyEncoding) -> Result<Self> {
+        match encoding {
+            BodyEncoding::Utf8 => Ok(body.into_bytes()),
+            BodyEncoding::Base64 => decode_base64(&body),
+        }
+    }
+}
+
+impl Body for bytes::Bytes {
+    const ENCODING: BodyEncoding = BodyEncoding::Base64;
+
+    fn encode(&self) -> String {
+        general_purpose::STANDARD.encode(self)
+    }
+
+    fn decode(body: String, encoding: BodyEncoding) -> Result<Self> {
+        Vec::<u8>::decode(body, encoding).map(bytes::Bytes::from)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct HttpRequest<T> {
+    pub(crate) method: String,
+    pub(crate) hostname: String,
+    pub(crate) port: i32,
+    pub(crate) path: String,
+    pub(crate) version: String,
+    pub(crate) headers: Vec<Vec<String>>,
+    pub(crate) body: T,
+    #[serde(default, skip_serializing_if = "BodyEncoding::is_utf8")]
+    pub(crate) body_encoding: BodyEncoding,
+    /// Encoding the host should use for the body of the response
+    #[serde(default, skip_serializing_if = "BodyEncoding::is_utf8")]
+    pub(crate) response_body_encoding: BodyEncoding,
+}
+
+impl Display for HttpRequest<String> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "HttpRequest: method: {}, hostname: {}, port: {}, path: {}, version: {}, headers: {:?}, body: {}", self.method, self.hostname, self.port, self.path, self.version, self.headers, self.body)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct HttpResponse<T> {
+    pub(crate) status_code: i32,
+    pub(crate) headers: Vec<Vec<String>>,
+    pub(crate) body: T,
+    #[serde(default, skip_serializing_if = "BodyEncoding::is_utf8")]
+    pub(crate) body_encoding: BodyEncoding,
+}
+
+impl Display for HttpResponse<String> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "HttpResponse: status_code: {}, headers: {:?}, body: {}",
+            self.status_code, self.headers, self.body
+        )
+    }
+}
+
+/// `message` without the values of sensitive headers, nor those of the secrets read by the app
+fn redact(headers: &http::HeaderMap, message: &str) -> String {
+    let message = headers
+        .values()
+        .filter(|value| value.is_sensitive())
+        .filter_map(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .fold(message.to_string(), |message, value| {
+            message.replace(value, "[REDACTED]")
+        });
+    #[cfg(all(feature = "ledger", feature = "crypto"))]
+    let message = crate::secrets::redact(&message);
+    message
+}
+
+/// Send a http request.
+///
+/// The URI must use the `https` scheme and be allowed by the egress policy, if one is set.
+/// `String` bodies go as text; binary ones, `Vec<u8>` or `Bytes`, go base64 encoded and
+/// the host is asked to encode the response body the same way.
+pub fn request<B: Body>(request: &Request<B>) -> Result<Response<B>> {
+    match request.uri().scheme_str() {
+        Some("https") => (),
+        Some(scheme) => {
+            return Err(Error::invalid_input(
+                Subsystem::Https,
+                format!("Unsupported scheme '{scheme}': only https is allowed"),
+            ))
+        }
+        None => {
+            return Err(Error::invalid_input(
+                Subsystem::Https,
+                "Missing scheme in URI: expected https",
+            ))
+        }
+    }
+    let port = match request.uri().port() {
+        Some(port) => port.as_u16(),
+        None => 443,
+    };
+    let hostname = request
+        .uri()
+        .host()
+        .ok_or_else(|| Error::invalid_input(Subsystem::Https, "Missing host in URI"))?;
+    egress::check(hostname, port, request.uri().path())?;
+
+    let http_request = HttpRequest {
+        method: request.method().as_str().to_string(),
+        hostname: hostname.to_string(),
+        port: i32::from(port),
+        path: match request.uri().path_and_query() {
+            Some(path_and_query) => path_and_query.as_str().to_string(),
+            None => "/".to_string(),
+        },
+        version: format!("{:?}", request.version()),
+        headers: request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                Ok(vec![
+                    name.as_str().to_string(),
+                    value
+                        .to_str()
+                        .or_error(
+                            Subsystem::Https,
+                            ErrorKind::InvalidInput,
+                            "Invalid header value",
+                        )?
+                        .to_string(),
+                ])
+            })
+            .collect::<Result<Vec<_>>>()?,
+        body: request.body().encode(),
+        body_encoding: B::ENCODING,
+        response_body_encoding: B::ENCODING,
+    };
+
+    let http_request_str = serde_json::to_string(&http_request).or_error(
+        Subsystem::Https,
+        ErrorKind::Serialization,
+        "Failed to serialize http request",
+    )?;
+
+    let response = match sdk::https_query(&http_request_str) {
+        Ok(response) => response,
+        Err(e) => {
+            return Err(Error::host(
+                Subsystem::Https,
+                redact(
+                    request.headers(),
+                    &format!("Failed to send https query:\n{http_request}\n{http_request_str:?}"),
+                ),
+                redact(request.headers(), &e),
+            ))
+        }
+    };
+
+    let http_response: HttpResponse<String> = match serde_json::from_str(&response) {
+        Ok(http_response) => http_response,
+        Err(e) => {
+            return Err(Error::new(
+                Subsystem::Https,
+                ErrorKind::Serialization,
+                redact(
+                    request.headers(),
+                    &format!("Failed to deserialize http response:\n{response:?}\n{http_request}\n{http_request_str:?}"),
+                ),
+            )
+            .with_source(e))
+        }
+    };
+
+    let mut parts = Response::new(String::new()).into_parts().0;
+
+    parts.headers = http_response
+        .headers
+        .iter()
+        .map(|header| match (header.first(), header.get(1)) {
+            (Some(name), Some(value)) => {
+                let parsed_name = name.parse::<http::header::HeaderName>().or_error(
+                    Subsystem::Https,
+                    ErrorKind::Serialization,
+                    &format!("Failed to parse header name '{name}'"),
+                )?;
+                let parsed_value = value.parse::<http::header::HeaderValue>().or_error(
+                    Subsystem::Https,
+                    ErrorKind::Serialization,
+                    &format!("Failed to parse header value '{value}'"),
+                )?;
+                Ok((parsed_name, parsed_value))
+            }
+            _ => Err(Error::new(
+                Subsystem::Https,
+                ErrorKind::Serialization,
+                "Malformed header entry: missing name or value",
+            )),
+        })
+        .collect::<Result<http::HeaderMap>>()?;
+
+    parts.status = StatusCode::from_u16(http_response.status_code as u16).or_error(
+        Subsystem::Https,
+        ErrorKind::Serialization,
+        "Invalid status code",
+    )?;
+    parts.version = request.version();
+
+    let body = B::decode(http_response.body, http_response.body_encoding)?;
+    let response = Response::from_parts(parts, body);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    #[test]
+    fn host_errors_are_redacted() {
+        let host = install(MockHost::new());
+        host.set_https_responder(|request| {
+            Err(format!(
+                "connection reset while sending {:?}",
+                request.headers()["authorization"]
+            ))
+        });
+        let mut token = http::HeaderValue::from_static("Bearer s3cr3t");
+        token.set_sensitive(true);
+        let request = Request::builder()
+            .uri("https://api.example.com/items")
+            .header("authorization", token)
+            .body(String::new())
+            .unwrap();
+
+        let err = super::request(&request).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Host);
+        assert_eq!(
+            err.host_error(),
+            Some(r#"connection reset while sending "[REDACTED]""#)
+        );
+        assert!(!format!("{err} {err:?}").contains("s3cr3t"));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::context::{self, Timestamp};
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::ledger;
+use crate::util::{compound_key, read_or_default};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::Duration;
+
+const IDEMPOTENCY_TABLE: &str = "__klave_idempotency";
+
+/// Default retention of stored results
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    result: String,
+    expires_at: Timestamp,
+}
+
+fn require_transaction() -> Result<()> {
+    context::current().require_transaction(Subsystem::Access, "Idempotency")
+}
+
+fn record_key(key: &str) -> Result<String> {
+    if key.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid idempotency key: cannot be empty",
+        ));
+    }
+    let sender = context::current().sender()?;
+    Ok(compound_key(&[&sender, key]))
+}
+
+/// Run a transaction handler at most once per idempotency key.
+///
+/// The serialized result of the first successful run is stored in the ledger and returned
+/// as-is on replays of the same key by the same caller, until `ttl` has elapsed.
+/// Failed runs are not stored, so a retry runs the handler again. Queries are refused with
+/// [`ErrorKind::Unsupported`]: their ledger writes are not kept, so nothing would be stored.
+pub fn run<R, F>(key: &str, ttl: Duration, handler: F) -> Result<R>
+where
+    R: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<R>,
+{
+    require_transaction()?;
+    let table = ledger::get_table(IDEMPOTENCY_TABLE);
+    let record_key = record_key(key)?;
+    let now = context::current().trusted_time()?;
+
+    if let Some(record) = read_or_default::<Option<Record>>(&table, &record_key)? {
+        if now < record.expires_at {
+            return serde_json::from_str(&record.result).or_error(
+                Subsystem::Access,
+                ErrorKind::Serialization,
+                "Failed to deserialize stored result",
+            );
+        }
+    }
+
+    let result = handler()?;
+    table.set_json(
+        &record_key,
+        &Record {
+            result: serde_json::to_string(&result).or_error(
+                Subsystem::Access,
+                ErrorKind::Serialization,
+                "Failed to serialize result",
+            )?,
+            expires_at: now.saturating_add(ttl),
+        },
+    )?;
+    Ok(result)
+}
+
+/// Remove the stored results whose retention has elapsed and return how many were removed;
+/// like [`run`], it must be called from a transaction
+pub fn purge_expired() -> Result<usize> {
+    require_transaction()?;
+    let table = ledger::get_table(IDEMPOTENCY_TABLE);
+    let now = context::current().trusted_time()?;
+    let mut removed = 0;
+    for key in table.list_keys()? {
+        let record: Record = table.get_json(&key)?;
+        if now >= record.expires_at {
+            table.remove(&key)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn transaction(sender: &str, now: u64) -> Rc<MockHost> {
+        let host = install(MockHost::new());
+        host.set_context(context::SENDER, sender);
+        host.set_context(context::TRUSTED_TIME, &now.to_string());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host
+    }
+
+    #[test]
+    fn replays_return_the_stored_result() {
+        let _host = transaction("alice", 1000);
+        let runs = Cell::new(0);
+        let handler = || {
+            runs.set(runs.get() + 1);
+            Ok(runs.get())
+        };
+        assert_eq!(run("k", DEFAULT_TTL, handler).unwrap(), 1);
+        assert_eq!(run("k", DEFAULT_TTL, handler).unwrap(), 1);
+        assert_eq!(runs.get(), 1);
+        assert_eq!(run("other", DEFAULT_TTL, handler).unwrap(), 2);
+    }
+
+    #[test]
+    fn failed_runs_are_not_stored() {
+        let _host = transaction("alice", 1000);
+        let err = run::<u32, _>("k", DEFAULT_TTL, || {
+            Err(Error::invalid_input(Subsystem::Access, "bad"))
+        })
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(run("k", DEFAULT_TTL, || Ok(7)).unwrap(), 7);
+    }
+
+    #[test]
+    fn results_expire_after_the_ttl() {
+        let host = transaction("alice", 1000);
+        let ttl = Duration::from_secs(60);
+        assert_eq!(run("k", ttl, || Ok(1)).unwrap(), 1);
+
+        let expiry = 1000 + ttl.as_nanos() as u64;
+        host.set_context(context::TRUSTED_TIME, &(expiry - 1).to_string());
+        assert_eq!(run("k", ttl, || Ok(2)).unwrap(), 1);
+        assert_eq!(purge_expired().unwrap(), 0);
+
+        host.set_context(context::TRUSTED_TIME, &expiry.to_string());
+        assert_eq!(purge_expired().unwrap(), 1);
+        assert_eq!(run("k", ttl, || Ok(3)).unwrap(), 3);
+    }
+
+    #[test]
+    fn senders_have_their_own_keys() {
+        let host = transaction("alice", 1000);
+        assert_eq!(
+            run("k", DEFAULT_TTL, || Ok("alice".to_string())).unwrap(),
+            "alice"
+        );
+        host.set_context(context::SENDER, "bob");
+        assert_eq!(
+            run("k", DEFAULT_TTL, || Ok("bob".to_string())).unwrap(),
+            "bob"
+        );
+
+        host.set_context(context::SENDER, "a");
+        assert_eq!(
+            run("b:k", DEFAULT_TTL, || Ok("a".to_string())).unwrap(),
+            "a"
+        );
+        host.set_context(context::SENDER, "a:b");
+        assert_eq!(
+            run("k", DEFAULT_TTL, || Ok("a:b".to_string())).unwrap(),
+            "a:b"
+        );
+    }
+
+    #[test]
+    fn queries_are_refused() {
+        let host = install(MockHost::new());
+        host.set_context(context::SENDER, "alice");
+        host.set_context(context::TRUSTED_TIME, "1000");
+        host.set_context(context::QUERY_ID, "q1");
+        let ran = Cell::new(false);
+        let err = run("k", DEFAULT_TTL, || {
+            ran.set(true);
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(!ran.get());
+        assert_eq!(purge_expired().unwrap_err().kind(), ErrorKind::Unsupported);
+    }
+}
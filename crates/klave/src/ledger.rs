@@ -1,9 +1,40 @@
+use crate::error::{Error, ErrorKind, HostError, Result, ResultExt, Subsystem};
 use crate::sdk;
-use serde::{de::DeserializeOwned, Serialize};
-use std::error::Error;
+use crate::subscription::topic::Topic;
+use crate::util::to_hex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Kind of change made to a ledger entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Set,
+    Remove,
+}
+
+/// Change of a ledger entry, published on the change topic of its table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub table: String,
+    pub key: String,
+    pub operation: Operation,
+    /// Hex SHA-256 of the new value, absent on removal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+/// Topic the changes of `table` are published on, once enabled with [`Table::with_change_events`].
+///
+/// Topic names cannot contain `:`, so `%` and `:` are percent-encoded in the table name.
+/// The version of an entry is the `seq` of its latest event.
+pub fn change_topic(table: &str) -> String {
+    format!("ledger.{}", table.replace('%', "%25").replace(':', "%3A"))
+}
 
 pub struct Table {
     name: String,
+    change_events: bool,
 }
 
 impl Table {
@@ -11,48 +42,113 @@ impl Table {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            change_events: false,
+        }
+    }
+
+    /// Publish a [`ChangeEvent`] to subscribers of [`change_topic`] on every write and removal
+    pub fn with_change_events(mut self) -> Self {
+        self.change_events = true;
+        self
+    }
+
+    fn publish_change(&self, key: &str, value: Option<&[u8]>) -> Result<()> {
+        if !self.change_events {
+            return Ok(());
         }
+        let event = ChangeEvent {
+            table: self.name.clone(),
+            key: key.to_string(),
+            operation: match value {
+                Some(_) => Operation::Set,
+                None => Operation::Remove,
+            },
+            digest: value.map(|value| to_hex(&Sha256::digest(value))),
+        };
+        Topic::new(&change_topic(&self.name))?.publish(key, &event)?;
+        Ok(())
+    }
+
+    fn not_found(&self, key: &str) -> Error {
+        Error::not_found(
+            Subsystem::Ledger,
+            format!("Key '{key}' not found in table '{}'", self.name),
+        )
     }
 
     /// Insert or update a key-value pair with raw bytes
-    pub fn set(&self, key: &str, value: &[u8]) -> Result<(), Box<dyn Error>> {
-        sdk::write_ledger(&self.name, key.as_bytes(), value).map_err(Into::into)
+    pub fn set(&self, key: &str, value: &[u8]) -> Result<()> {
+        sdk::write_ledger(&self.name, key.as_bytes(), value).map_err(|err| {
+            Error::host(
+                Subsystem::Ledger,
+                format!("Failed to write key '{key}' in table '{}'", self.name),
+                err,
+            )
+        })?;
+        self.publish_change(key, Some(value))
     }
 
     /// Insert or update a key-value pair with a string value
-    pub fn set_string(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    pub fn set_string(&self, key: &str, value: &str) -> Result<()> {
         self.set(key, value.as_bytes())
     }
 
     /// Insert an object as a JSON string
-    pub fn set_json<T: Serialize>(&self, key: &str, value: &T) -> Result<(), Box<dyn Error>> {
-        let json = serde_json::to_string(value)?;
+    pub fn set_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string(value).or_error(
+            Subsystem::Ledger,
+            ErrorKind::Serialization,
+            "Failed to serialize value",
+        )?;
         self.set_string(key, &json)
     }
 
-    /// Retrieve a value as raw bytes
-    pub fn get(&self, key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
-        sdk::read_ledger(&self.name, key.as_bytes()).map_err(Into::into)
+    /// Retrieve a value as raw bytes.
+    /// Fails with [`ErrorKind::NotFound`] if the key does not exist.
+    pub fn get(&self, key: &str) -> Result<Vec<u8>> {
+        match sdk::read_ledger(&self.name, key.as_bytes()) {
+            Ok(value) if value.is_empty() && !self.exists(key)? => Err(self.not_found(key)),
+            Ok(value) => Ok(value),
+            Err(err) => match self.exists(key) {
+                Ok(false) => Err(self.not_found(key).with_source(HostError(err))),
+                _ => Err(Error::host(
+                    Subsystem::Ledger,
+                    format!("Failed to read key '{key}' from table '{}'", self.name),
+                    err,
+                )),
+            },
+        }
     }
 
     /// Retrieve a value as a UTF-8 string
-    pub fn get_string(&self, key: &str) -> Result<String, Box<dyn Error>> {
+    pub fn get_string(&self, key: &str) -> Result<String> {
         let bytes = self.get(key)?;
-        String::from_utf8(bytes).map_err(Into::into)
+        String::from_utf8(bytes).or_error(
+            Subsystem::Ledger,
+            ErrorKind::Serialization,
+            "Value is not valid UTF-8",
+        )
     }
 
     /// Retrieve an object by deserializing from JSON
-    pub fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<T, Box<dyn Error>> {
+    pub fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
         let json = self.get_string(key)?;
-        let obj = serde_json::from_str(&json)?;
-        Ok(obj)
+        serde_json::from_str(&json).or_error(
+            Subsystem::Ledger,
+            ErrorKind::Serialization,
+            "Failed to deserialize value",
+        )
     }
 
     /// List all keys in the table
-    pub fn list_keys(&self) -> Result<Vec<String>, Box<dyn Error>> {
+    pub fn list_keys(&self) -> Result<Vec<String>> {
         match sdk::list_keys_from_ledger(&self.name) {
             Ok(res) => {
-                let v: serde_json::Value = serde_json::from_str(&res)?;
+                let v: serde_json::Value = serde_json::from_str(&res).or_error(
+                    Subsystem::Ledger,
+                    ErrorKind::Serialization,
+                    "Failed to parse key list",
+                )?;
                 if let Some(keys) = v["keys"].as_array() {
                     let keys_list = keys
                         .iter()
@@ -68,24 +164,42 @@ impl Table {
                         .collect::<Vec<_>>();
                     Ok(keys_list)
                 } else {
-                    Err("Invalid format: 'keys' is not an array".into())
+                    Err(Error::new(
+                        Subsystem::Ledger,
+                        ErrorKind::Serialization,
+                        "Invalid format: 'keys' is not an array",
+                    ))
                 }
             }
-            Err(err) => {
-                let error_message = format!("Error while listing keys: {err}");
-                Err(error_message.into())
-            }
+            Err(err) => Err(Error::host(
+                Subsystem::Ledger,
+                "Error while listing keys",
+                err,
+            )),
         }
     }
 
     /// Check if a key exists in the table
-    pub fn exists(&self, key: &str) -> Result<bool, Box<dyn Error>> {
-        sdk::key_exists_in_ledger(&self.name, key.as_bytes()).map_err(Into::into)
+    pub fn exists(&self, key: &str) -> Result<bool> {
+        sdk::key_exists_in_ledger(&self.name, key.as_bytes()).map_err(|err| {
+            Error::host(
+                Subsystem::Ledger,
+                format!("Failed to check key '{key}' in table '{}'", self.name),
+                err,
+            )
+        })
     }
 
     /// Remove a key-value pair from the table
-    pub fn remove(&self, key: &str) -> Result<(), Box<dyn Error>> {
-        sdk::remove_from_ledger(&self.name, key.as_bytes()).map_err(Into::into)
+    pub fn remove(&self, key: &str) -> Result<()> {
+        sdk::remove_from_ledger(&self.name, key.as_bytes()).map_err(|err| {
+            Error::host(
+                Subsystem::Ledger,
+                format!("Failed to remove key '{key}' from table '{}'", self.name),
+                err,
+            )
+        })?;
+        self.publish_change(key, None)
     }
 }
 
@@ -93,3 +207,32 @@ impl Table {
 pub fn get_table(table: &str) -> Table {
     Table::new(table)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    #[test]
+    fn change_events_of_tables_with_colons() {
+        install(MockHost::new());
+        let table = Table::new("orders:eu").with_change_events();
+        table.set_string("o-1", "pending").unwrap();
+        table.remove("o-1").unwrap();
+        assert!(!table.exists("o-1").unwrap());
+
+        let topic = Topic::new(&change_topic("orders:eu")).unwrap();
+        let event = topic.latest("o-1").unwrap().unwrap();
+        assert_eq!(event.seq, 2);
+        assert_eq!(
+            serde_json::from_value::<ChangeEvent>(event.data).unwrap(),
+            ChangeEvent {
+                table: "orders:eu".to_string(),
+                key: "o-1".to_string(),
+                operation: Operation::Remove,
+                digest: None,
+            }
+        );
+        assert_ne!(change_topic("orders:eu"), change_topic("orders%3Aeu"));
+    }
+}
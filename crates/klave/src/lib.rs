@@ -1,15 +1,41 @@
+#[cfg(target_arch = "wasm32")]
 #[allow(warnings)]
 mod bindings;
 
-use bindings::klave::sdk::sdk;
+use host::sdk;
 
+#[cfg(feature = "ledger")]
+pub mod acl;
+#[cfg(feature = "attestation")]
 pub mod attestation;
+#[cfg(all(feature = "ledger", feature = "crypto"))]
+pub mod auth;
 pub mod context;
+#[cfg(feature = "crypto")]
 pub mod crypto;
+pub mod error;
+pub mod host;
+#[cfg(feature = "https")]
 pub mod https;
+#[cfg(feature = "ledger")]
+pub mod idempotency;
+#[cfg(feature = "ledger")]
 pub mod ledger;
+#[cfg(feature = "logging")]
+pub mod logging;
+#[cfg(feature = "ml")]
 pub mod ml;
 pub mod notifier;
+#[cfg(feature = "postgresql")]
 pub mod postgresql;
+#[cfg(all(feature = "ledger", feature = "crypto"))]
+pub mod quorum;
+#[cfg(feature = "ledger")]
+pub mod rate_limit;
 pub mod router;
+#[cfg(all(feature = "ledger", feature = "crypto"))]
+pub mod secrets;
 pub mod subscription;
+mod util;
+
+pub use error::{Error, ErrorKind, Result};
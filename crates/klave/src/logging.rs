@@ -0,0 +1,251 @@
+use crate::sdk;
+use log::kv::{self, VisitSource};
+use log::{Log, Metadata};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use log::{Level, LevelFilter};
+
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Destination of the log records that pass the level filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sink {
+    /// Keep the latest records in memory, see [`take_records`]; they are dropped with the instance.
+    /// This is the default, so that logs never reach clients unless asked for.
+    #[default]
+    Buffer,
+    /// Debug mode: send each record to the client as a notification `{"log": <record>}`
+    Notify,
+    /// Debug mode: send each record to the client as a line of text through `notify-error`
+    NotifyError,
+}
+
+/// Log record with its structured fields, as kept in the buffer or sent to the client
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{} {}] {}", self.level, self.target, self.message)?;
+        for (key, value) in &self.fields {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+impl From<&log::Record<'_>> for Record {
+    fn from(record: &log::Record<'_>) -> Self {
+        let mut fields = BTreeMap::new();
+        let _ = record.key_values().visit(&mut Fields(&mut fields));
+        Record {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            fields,
+        }
+    }
+}
+
+struct State {
+    level: LevelFilter,
+    sink: Sink,
+    capacity: usize,
+    buffer: VecDeque<Record>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = const {
+        RefCell::new(State {
+            level: LevelFilter::Info,
+            sink: Sink::Buffer,
+            capacity: DEFAULT_CAPACITY,
+            buffer: VecDeque::new(),
+        })
+    };
+}
+
+struct KlaveLogger;
+
+static LOGGER: KlaveLogger = KlaveLogger;
+
+/// Whether [`LOGGER`] is the logger of the `log` macros
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+impl Log for KlaveLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        STATE.with(|state| metadata.level() <= state.borrow().level)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let record = Record::from(record);
+        let sink = STATE.with(|state| state.borrow().sink);
+        match sink {
+            Sink::Buffer => STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                if state.capacity == 0 {
+                    return;
+                }
+                while state.buffer.len() >= state.capacity {
+                    state.buffer.pop_front();
+                }
+                state.buffer.push_back(record);
+            }),
+            Sink::Notify => {
+                let json = serde_json::json!({ "log": record });
+                sdk::notify(&json.to_string());
+            }
+            Sink::NotifyError => sdk::notify_error(&record.to_string()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the SDK as the backend of the `log` macros, and of `tracing` through its `log` feature.
+///
+/// Does nothing if it is already installed; if another logger is, records keep going to that one
+/// and the global maximum level is left to it.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+    if INSTALLED.load(Ordering::Relaxed) {
+        log::set_max_level(STATE.with(|state| state.borrow().level));
+    }
+}
+
+/// Most verbose level recorded, `Info` by default
+pub fn set_level(level: LevelFilter) {
+    STATE.with(|state| state.borrow_mut().level = level);
+    init();
+}
+
+pub fn set_sink(sink: Sink) {
+    STATE.with(|state| state.borrow_mut().sink = sink);
+    init();
+}
+
+/// Number of records kept by [`Sink::Buffer`], the oldest being dropped first
+pub fn set_capacity(capacity: usize) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.capacity = capacity;
+        while state.buffer.len() > capacity {
+            state.buffer.pop_front();
+        }
+    });
+}
+
+/// Records kept by [`Sink::Buffer`], oldest first
+pub fn records() -> Vec<Record> {
+    STATE.with(|state| state.borrow().buffer.iter().cloned().collect())
+}
+
+/// Return the records kept by [`Sink::Buffer`] and empty the buffer
+pub fn take_records() -> Vec<Record> {
+    STATE.with(|state| state.borrow_mut().buffer.drain(..).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost, Notification};
+
+    /// Install the logger at `Trace`, filtering on this thread only: the global maximum level
+    /// is shared by the tests running in parallel
+    fn setup(sink: Sink, level: LevelFilter) {
+        set_level(LevelFilter::Trace);
+        set_sink(sink);
+        take_records();
+        STATE.with(|state| state.borrow_mut().level = level);
+    }
+
+    #[test]
+    fn records_are_buffered_with_their_fields() {
+        setup(Sink::Buffer, LevelFilter::Info);
+        log::info!(target: "app", user = "alice", count = 2; "hello {}", "world");
+        log::debug!(target: "app", "filtered out");
+        assert_eq!(
+            take_records(),
+            [Record {
+                level: Level::Info,
+                target: "app".to_string(),
+                message: "hello world".to_string(),
+                fields: BTreeMap::from([
+                    ("count".to_string(), "2".to_string()),
+                    ("user".to_string(), "alice".to_string()),
+                ]),
+            }]
+        );
+        assert!(records().is_empty());
+    }
+
+    #[test]
+    fn the_buffer_keeps_the_latest_records() {
+        setup(Sink::Buffer, LevelFilter::Trace);
+        set_capacity(3);
+        for i in 0..5 {
+            log::warn!(target: "app", "record {i}");
+        }
+        let messages = |records: Vec<Record>| {
+            records
+                .into_iter()
+                .map(|record| record.message)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(messages(records()), ["record 2", "record 3", "record 4"]);
+        set_capacity(1);
+        assert_eq!(messages(take_records()), ["record 4"]);
+        set_capacity(0);
+        log::warn!(target: "app", "dropped");
+        assert!(records().is_empty());
+        set_capacity(DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn sinks_send_records_to_the_client() {
+        let host = install(MockHost::new());
+        setup(Sink::Notify, LevelFilter::Info);
+        log::error!(target: "app", code = 7; "failed");
+        log::debug!(target: "app", "filtered out");
+        setup(Sink::NotifyError, LevelFilter::Info);
+        log::warn!(target: "app", code = 8; "slow");
+
+        let notifications = host.take_notifications();
+        let [Notification::Message(message), Notification::Error(error)] = notifications.as_slice()
+        else {
+            panic!("unexpected notifications {notifications:?}");
+        };
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(message).unwrap(),
+            serde_json::json!({
+                "log": { "level": "ERROR", "target": "app", "message": "failed", "fields": { "code": "7" } }
+            })
+        );
+        assert_eq!(error, "[WARN app] slow code=8");
+        assert!(records().is_empty());
+    }
+}
@@ -1,26 +1,35 @@
+use crate::error::{Error, Result, Subsystem};
 use crate::sdk;
 
-pub fn load_lightgbm_model(name: &str, model: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn load_lightgbm_model(name: &str, model: &str) -> Result<()> {
     match sdk::load_lightgbm_model(name, model) {
         Ok(_) => Ok(()),
-        Err(err) => Err(err.into()),
+        Err(err) => Err(Error::host(
+            Subsystem::Ml,
+            format!("Failed to load model '{name}'"),
+            err,
+        )),
     }
 }
 
-pub fn unload_lightgbm_model(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn unload_lightgbm_model(name: &str) -> Result<()> {
     match sdk::unload_lightgbm_model(name) {
         Ok(_) => Ok(()),
-        Err(err) => Err(err.into()),
+        Err(err) => Err(Error::host(
+            Subsystem::Ml,
+            format!("Failed to unload model '{name}'"),
+            err,
+        )),
     }
 }
 
-pub fn infer_from_lightgbm_model(
-    name: &str,
-    data: Vec<f64>,
-    nb_outputs: i32,
-) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+pub fn infer_from_lightgbm_model(name: &str, data: Vec<f64>, nb_outputs: i32) -> Result<Vec<f64>> {
     match sdk::infer_from_lightgbm_model(name, &data, nb_outputs) {
         Ok(result) => Ok(result),
-        Err(err) => Err(err.into()),
+        Err(err) => Err(Error::host(
+            Subsystem::Ml,
+            format!("Failed to infer from model '{name}'"),
+            err,
+        )),
     }
 }
@@ -0,0 +1,358 @@
+use crate::crypto::subtle::{
+    self, AesGcmParams, AesKeyGenParams, EcKeyGenParams, EcdhDerivParams, EncryptAlgorithm,
+    KeyDerivationAlgorithm, KeyGenAlgorithm, KeyWrapAlgorithm, RsaHashedKeyGenParams,
+    RsaOaepParams,
+};
+use crate::crypto::{random, sha};
+use crate::error::{ErrorKind, Result, ResultExt, Subsystem};
+use crate::sdk;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// HKDF info of the content key of ECDH envelopes
+const HKDF_INFO: &[u8] = b"klave encrypted notification";
+
+const ECDH_ALG: &str = "ECDH-ES+HKDF-SHA256+A256GCM";
+const RSA_ALG: &str = "RSA-OAEP-256+A256GCM";
+
+/// Public key of the client a notification is encrypted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecipientKey {
+    /// SPKI of an ECC key on `P-256` or `P-384`
+    Ecc { named_curve: String, spki: Vec<u8> },
+    /// SPKI of an RSA key of 2048, 3072 or 4096 bits
+    Rsa { modulus_length: u32, spki: Vec<u8> },
+}
+
+/// Encrypted notification; binary fields are base64 encoded.
+///
+/// The payload is encrypted with AES-256-GCM under a fresh content key, using the algorithm
+/// name as additional data. For `ECDH-ES+HKDF-SHA256+A256GCM`, the content key is derived with
+/// HKDF-SHA256 from the first 256 bits of the ECDH secret between the ephemeral key `epk` and
+/// the recipient key; for `RSA-OAEP-256+A256GCM`, it is wrapped to the recipient key with
+/// RSA-OAEP and SHA-256.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "alg")]
+pub enum Envelope {
+    #[serde(rename = "ECDH-ES+HKDF-SHA256+A256GCM")]
+    Ecdh {
+        named_curve: String,
+        /// SPKI of the ephemeral public key
+        epk: String,
+        salt: String,
+        iv: String,
+        ciphertext: String,
+    },
+    #[serde(rename = "RSA-OAEP-256+A256GCM")]
+    RsaOaep {
+        encrypted_key: String,
+        iv: String,
+        ciphertext: String,
+    },
+}
+
+fn aes_gcm(iv: &[u8], alg: &str) -> EncryptAlgorithm {
+    EncryptAlgorithm::AesGcm(AesGcmParams {
+        iv: iv.to_vec(),
+        additional_data: alg.as_bytes().to_vec(),
+        tag_length: 128,
+    })
+}
+
+fn aes_256() -> AesKeyGenParams {
+    AesKeyGenParams { length: 256 }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    general_purpose::STANDARD.encode(bytes)
+}
+
+/// HKDF-SHA256 of `ikm` to a 256 bit key, see RFC 5869
+fn hkdf_sha256(salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>> {
+    let prk = sha::hmac_sha256(salt, ikm)?;
+    sha::hmac_sha256(&prk, &[HKDF_INFO, &[1]].concat())
+}
+
+fn seal_ecdh(named_curve: &str, spki: &[u8], payload: &[u8]) -> Result<Envelope> {
+    let curve = || {
+        KeyGenAlgorithm::Ecc(EcKeyGenParams {
+            named_curve: named_curve.to_string(),
+        })
+    };
+    let recipient = subtle::import_key("spki", spki, &curve(), true, &[])?;
+    let ephemeral = subtle::generate_key(&curve(), false, &["deriveBits"])?;
+    let shared = subtle::derive_bits(
+        &KeyDerivationAlgorithm::Ecdh(EcdhDerivParams { public: recipient }),
+        &ephemeral,
+        256,
+    )?;
+    let salt = random::get_random_bytes(32)?;
+    let content_key = subtle::import_key(
+        "raw",
+        &hkdf_sha256(&salt, &shared)?,
+        &KeyGenAlgorithm::Aes(aes_256()),
+        false,
+        &["encrypt"],
+    )?;
+    let iv = random::get_random_bytes(12)?;
+    let ciphertext = subtle::encrypt(&aes_gcm(&iv, ECDH_ALG), &content_key, payload)?;
+    let epk = subtle::export_key("spki", &subtle::get_public_key(&ephemeral)?)?;
+    Ok(Envelope::Ecdh {
+        named_curve: named_curve.to_string(),
+        epk: encode(&epk),
+        salt: encode(&salt),
+        iv: encode(&iv),
+        ciphertext: encode(&ciphertext),
+    })
+}
+
+fn seal_rsa(modulus_length: u32, spki: &[u8], payload: &[u8]) -> Result<Envelope> {
+    let recipient = subtle::import_key(
+        "spki",
+        spki,
+        &KeyGenAlgorithm::Rsa(RsaHashedKeyGenParams {
+            modulus_length,
+            ..Default::default()
+        }),
+        true,
+        &["wrapKey"],
+    )?;
+    let content_key = subtle::generate_key(&KeyGenAlgorithm::Aes(aes_256()), true, &["encrypt"])?;
+    let iv = random::get_random_bytes(12)?;
+    let ciphertext = subtle::encrypt(&aes_gcm(&iv, RSA_ALG), &content_key, payload)?;
+    let encrypted_key = subtle::wrap_key(
+        "raw",
+        &content_key,
+        &recipient,
+        &KeyWrapAlgorithm::RsaOaep(RsaOaepParams::default()),
+    )?;
+    Ok(Envelope::RsaOaep {
+        encrypted_key: encode(&encrypted_key),
+        iv: encode(&iv),
+        ciphertext: encode(&ciphertext),
+    })
+}
+
+/// Encrypt `payload` so that only the holder of the private key of `recipient` can read it
+pub fn seal(recipient: &RecipientKey, payload: &[u8]) -> Result<Envelope> {
+    match recipient {
+        RecipientKey::Ecc { named_curve, spki } => seal_ecdh(named_curve, spki, payload),
+        RecipientKey::Rsa {
+            modulus_length,
+            spki,
+        } => seal_rsa(*modulus_length, spki, payload),
+    }
+}
+
+/// Send `payload` through `notify`, encrypted to `recipient`
+pub fn send(recipient: &RecipientKey, payload: &[u8]) -> Result<()> {
+    let envelope = seal(recipient, payload)?;
+    let json = serde_json::to_string(&envelope).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to serialize notification",
+    )?;
+    sdk::notify(&json);
+    Ok(())
+}
+
+/// Send `value` as JSON through `notify`, encrypted to `recipient`
+pub fn send_json<T: Serialize>(recipient: &RecipientKey, value: &T) -> Result<()> {
+    let json = serde_json::to_vec(value).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to serialize notification",
+    )?;
+    send(recipient, &json)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decode(field: &str, value: &str) -> Result<Vec<u8>> {
+    general_purpose::STANDARD.decode(value).or_error(
+        Subsystem::Crypto,
+        ErrorKind::InvalidInput,
+        &format!("Envelope field {field} is not valid base64"),
+    )
+}
+
+/// Decrypt `envelope` on the client, with the PKCS#8 private key of the recipient.
+///
+/// Runs in pure Rust, without a host.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open(envelope: &Envelope, pkcs8: &[u8]) -> Result<Vec<u8>> {
+    use crate::error::Error;
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::Aes256Gcm;
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    let invalid_key = |_| Error::invalid_input(Subsystem::Crypto, "Invalid recipient private key");
+    let (content_key, iv, ciphertext, alg) = match envelope {
+        Envelope::Ecdh {
+            named_curve,
+            epk,
+            salt,
+            iv,
+            ciphertext,
+        } => {
+            macro_rules! ecdh {
+                ($curve:ident) => {{
+                    use $curve::pkcs8::{DecodePrivateKey, DecodePublicKey};
+                    let secret = $curve::SecretKey::from_pkcs8_der(pkcs8).map_err(invalid_key)?;
+                    let epk = $curve::PublicKey::from_public_key_der(&decode("epk", epk)?)
+                        .map_err(|_| {
+                            Error::invalid_input(Subsystem::Crypto, "Invalid ephemeral public key")
+                        })?;
+                    $curve::ecdh::diffie_hellman(secret.to_nonzero_scalar(), epk.as_affine())
+                        .raw_secret_bytes()
+                        .to_vec()
+                }};
+            }
+            let shared = match named_curve.as_str() {
+                "P-256" => ecdh!(p256),
+                "P-384" => ecdh!(p384),
+                _ => {
+                    return Err(Error::invalid_input(
+                        Subsystem::Crypto,
+                        format!("Unsupported curve {named_curve}"),
+                    ))
+                }
+            };
+            let mut content_key = vec![0u8; 32];
+            Hkdf::<Sha256>::new(Some(&decode("salt", salt)?), &shared[..32])
+                .expand(HKDF_INFO, &mut content_key)
+                .map_err(|_| {
+                    Error::invalid_input(Subsystem::Crypto, "Failed to derive content key")
+                })?;
+            (content_key, iv, ciphertext, ECDH_ALG)
+        }
+        Envelope::RsaOaep {
+            encrypted_key,
+            iv,
+            ciphertext,
+        } => {
+            use rsa::pkcs8::DecodePrivateKey;
+            let key = rsa::RsaPrivateKey::from_pkcs8_der(pkcs8).map_err(invalid_key)?;
+            let content_key = key
+                .decrypt(
+                    rsa::Oaep::new::<Sha256>(),
+                    &decode("encrypted_key", encrypted_key)?,
+                )
+                .or_error(
+                    Subsystem::Crypto,
+                    ErrorKind::InvalidInput,
+                    "Failed to unwrap content key",
+                )?;
+            (content_key, iv, ciphertext, RSA_ALG)
+        }
+    };
+    let cipher = Aes256Gcm::new_from_slice(&content_key)
+        .map_err(|_| Error::invalid_input(Subsystem::Crypto, "Invalid content key"))?;
+    let iv = decode("iv", iv)?;
+    if iv.len() != 12 {
+        return Err(Error::invalid_input(Subsystem::Crypto, "Invalid IV"));
+    }
+    cipher
+        .decrypt(
+            iv.as_slice().into(),
+            Payload {
+                msg: &decode("ciphertext", ciphertext)?,
+                aad: alg.as_bytes(),
+            },
+        )
+        .map_err(|_| Error::invalid_input(Subsystem::Crypto, "Failed to decrypt envelope"))
+}
+
+/// Decrypt a notification sent with [`send_json`] and deserialize its content
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_json<T: serde::de::DeserializeOwned>(message: &str, pkcs8: &[u8]) -> Result<T> {
+    let envelope: Envelope = serde_json::from_str(message).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Notification is not an encrypted envelope",
+    )?;
+    serde_json::from_slice(&open(&envelope, pkcs8)?).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to deserialize decrypted notification",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost, Notification};
+
+    fn key_pair(algorithm: KeyGenAlgorithm) -> (RecipientKey, Vec<u8>) {
+        let usages: &[&str] = match algorithm {
+            KeyGenAlgorithm::Rsa(_) => &["decrypt", "unwrapKey"],
+            _ => &["deriveBits"],
+        };
+        let key = subtle::generate_key(&algorithm, true, usages).unwrap();
+        let pkcs8 = subtle::export_key("pkcs8", &key).unwrap();
+        let spki = subtle::export_key("spki", &subtle::get_public_key(&key).unwrap()).unwrap();
+        let recipient = match algorithm {
+            KeyGenAlgorithm::Ecc(params) => RecipientKey::Ecc {
+                named_curve: params.named_curve,
+                spki,
+            },
+            KeyGenAlgorithm::Rsa(params) => RecipientKey::Rsa {
+                modulus_length: params.modulus_length,
+                spki,
+            },
+            _ => unreachable!(),
+        };
+        (recipient, pkcs8)
+    }
+
+    fn ecc(named_curve: &str) -> KeyGenAlgorithm {
+        KeyGenAlgorithm::Ecc(EcKeyGenParams {
+            named_curve: named_curve.to_string(),
+        })
+    }
+
+    fn round_trip(algorithm: KeyGenAlgorithm) {
+        let _host = install(MockHost::new());
+        let (recipient, pkcs8) = key_pair(algorithm);
+        let envelope = seal(&recipient, b"for your eyes only").unwrap();
+        assert_eq!(open(&envelope, &pkcs8).unwrap(), b"for your eyes only");
+    }
+
+    #[test]
+    fn p256_envelopes_open() {
+        round_trip(ecc("P-256"));
+    }
+
+    #[test]
+    fn p384_envelopes_open() {
+        round_trip(ecc("P-384"));
+    }
+
+    #[test]
+    fn rsa_envelopes_open() {
+        round_trip(KeyGenAlgorithm::Rsa(RsaHashedKeyGenParams::default()));
+    }
+
+    #[test]
+    fn json_notifications_open() {
+        let host = install(MockHost::new());
+        let (recipient, pkcs8) = key_pair(ecc("P-256"));
+        send_json(&recipient, &vec!["a", "b"]).unwrap();
+        let notifications = host.take_notifications();
+        let [Notification::Message(message)] = notifications.as_slice() else {
+            panic!("unexpected notifications {notifications:?}");
+        };
+        assert!(!message.contains("\"a\""));
+        let value: Vec<String> = open_json(message, &pkcs8).unwrap();
+        assert_eq!(value, ["a", "b"]);
+    }
+
+    #[test]
+    fn envelopes_of_other_recipients_do_not_open() {
+        let _host = install(MockHost::new());
+        let (recipient, _) = key_pair(ecc("P-256"));
+        let (_, other) = key_pair(ecc("P-256"));
+        let envelope = seal(&recipient, b"payload").unwrap();
+        assert!(open(&envelope, &other).is_err());
+    }
+}
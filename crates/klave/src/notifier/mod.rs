@@ -0,0 +1,114 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::sdk;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "crypto")]
+pub mod encrypted;
+#[cfg(feature = "stream")]
+pub mod stream;
+
+/// Error sent to the client through `notify-error`.
+///
+/// Errors go through their own import, so clients can tell them apart from the
+/// intermediate messages of `notify` and the final ones of `on-success-notify`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    /// Machine readable code, `<subsystem>.<kind>` for SDK errors
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorPayload {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> ErrorPayload {
+        ErrorPayload {
+            code: code.into(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details<T: Serialize>(mut self, details: &T) -> Result<ErrorPayload> {
+        self.details = Some(serde_json::to_value(details).or_error(
+            Subsystem::Notifier,
+            ErrorKind::Serialization,
+            "Failed to serialize error details",
+        )?);
+        Ok(self)
+    }
+}
+
+/// Payload of an SDK error, without its source, which may hold raw host messages
+impl From<&Error> for ErrorPayload {
+    fn from(error: &Error) -> Self {
+        ErrorPayload::new(
+            format!("{}.{}", error.subsystem(), error.kind().code()),
+            format!("{}: {}", error.kind(), error.message()),
+        )
+    }
+}
+
+pub fn send_string(param: &str) {
+    sdk::notify(param);
+}
+
+pub fn send_json<T: Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string(value).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to serialize notification",
+    )?; // Convert struct to JSON string
+    sdk::notify(&json);
+    Ok(())
+}
+
+/// Send `payload` to the client as a JSON error
+pub fn send_error(payload: &ErrorPayload) -> Result<()> {
+    let json = serde_json::to_string(payload).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to serialize error notification",
+    )?;
+    sdk::notify_error(&json);
+    Ok(())
+}
+
+pub fn on_success_notify(message: &str) {
+    sdk::on_success_notify(message);
+}
+
+/// Send `value` as JSON once the transaction is committed
+pub fn on_success_notify_json<T: Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string(value).or_error(
+        Subsystem::Notifier,
+        ErrorKind::Serialization,
+        "Failed to serialize notification",
+    )?;
+    sdk::on_success_notify(&json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_payloads_leave_out_host_errors() {
+        let error = Error::host(
+            Subsystem::Ledger,
+            "Failed to write key 'k'",
+            "internal error at 0x7ffd: token=abc".to_string(),
+        );
+        let payload = ErrorPayload::from(&error);
+        assert_eq!(
+            payload.code,
+            format!("{}.{}", Subsystem::Ledger, ErrorKind::Host.code())
+        );
+        assert_eq!(
+            payload.message,
+            format!("{}: Failed to write key 'k'", ErrorKind::Host)
+        );
+        assert!(!payload.message.contains("token"));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::sdk;
+use crate::util::to_hex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Default maximum size of a chunk notification, in bytes
+pub const DEFAULT_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Notification carrying part of a streamed payload.
+///
+/// Chunks are numbered from 0; the last one has `last` set and the hex SHA-256 of the whole payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub stream: String,
+    pub seq: u64,
+    pub data: String,
+    #[serde(default)]
+    pub last: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+/// Payload sent to the client as a sequence of [`Chunk`] notifications.
+///
+/// A JSON value is split at arbitrary character boundaries, while items are sent one JSON
+/// document per line and never split, so clients can process each chunk as it arrives.
+pub struct Stream {
+    id: String,
+    chunk_size: usize,
+    seq: u64,
+    buffer: String,
+    /// Length of `buffer` once escaped in the `data` of a chunk
+    buffer_len: usize,
+    /// Room for `buffer_len` in the chunk numbered `seq`
+    budget: usize,
+    hasher: Sha256,
+}
+
+impl Stream {
+    /// New stream identified by `id`, e.g. the id of the current query
+    pub fn new(id: &str) -> Stream {
+        let mut stream = Stream {
+            id: id.to_string(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            seq: 0,
+            buffer: String::new(),
+            buffer_len: 0,
+            budget: 0,
+            hasher: Sha256::new(),
+        };
+        stream.budget = stream.data_budget();
+        stream
+    }
+
+    /// Maximum size of a chunk notification in bytes, JSON escaping of the data included.
+    ///
+    /// Only a chunk made of a single item, or of a single character for sizes too small to hold
+    /// the rest of the chunk, can exceed it.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Stream {
+        self.chunk_size = chunk_size.max(1);
+        self.budget = self.data_budget();
+        self
+    }
+
+    /// Room left for the escaped data of the next chunk, counting the digest of the last one
+    fn data_budget(&self) -> usize {
+        let envelope = Chunk {
+            stream: self.id.clone(),
+            seq: self.seq,
+            data: String::new(),
+            last: true,
+            digest: Some("0".repeat(64)),
+        };
+        let envelope_len = serde_json::to_string(&envelope).map_or(0, |json| json.len());
+        self.chunk_size.saturating_sub(envelope_len)
+    }
+
+    pub fn send_str(mut self, payload: &str) -> Result<()> {
+        self.hasher.update(payload.as_bytes());
+        for c in payload.chars() {
+            let len = escaped_len(c);
+            if !self.buffer.is_empty() && self.buffer_len + len > self.budget {
+                self.flush()?;
+            }
+            self.buffer.push(c);
+            self.buffer_len += len;
+        }
+        self.finish()
+    }
+
+    pub fn send_json<T: Serialize>(self, value: &T) -> Result<()> {
+        let json = serde_json::to_string(value).or_error(
+            Subsystem::Notifier,
+            ErrorKind::Serialization,
+            "Failed to serialize notification",
+        )?;
+        self.send_str(&json)
+    }
+
+    /// Send each item as a line of JSON, flushing a chunk whenever the chunk size is reached
+    pub fn send_items<T: Serialize>(mut self, items: impl IntoIterator<Item = T>) -> Result<()> {
+        for item in items {
+            let mut line = serde_json::to_string(&item).or_error(
+                Subsystem::Notifier,
+                ErrorKind::Serialization,
+                "Failed to serialize notification",
+            )?;
+            line.push('\n');
+            let len = line.chars().map(escaped_len).sum::<usize>();
+            if !self.buffer.is_empty() && self.buffer_len + len > self.budget {
+                self.flush()?;
+            }
+            self.hasher.update(line.as_bytes());
+            self.buffer.push_str(&line);
+            self.buffer_len += len;
+        }
+        self.finish()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let data = std::mem::take(&mut self.buffer);
+        self.buffer_len = 0;
+        self.send_chunk(data, None)
+    }
+
+    fn finish(mut self) -> Result<()> {
+        let digest = to_hex(&std::mem::take(&mut self.hasher).finalize());
+        let data = std::mem::take(&mut self.buffer);
+        self.send_chunk(data, Some(digest))
+    }
+
+    fn send_chunk(&mut self, data: String, digest: Option<String>) -> Result<()> {
+        let chunk = Chunk {
+            stream: self.id.clone(),
+            seq: self.seq,
+            data,
+            last: digest.is_some(),
+            digest,
+        };
+        let json = serde_json::to_string(&chunk).or_error(
+            Subsystem::Notifier,
+            ErrorKind::Serialization,
+            "Failed to serialize notification",
+        )?;
+        sdk::notify(&json);
+        self.seq += 1;
+        self.budget = self.data_budget();
+        Ok(())
+    }
+}
+
+/// Client side reassembly of the chunks of one stream, in any order
+#[derive(Debug, Default)]
+pub struct Reassembly {
+    stream: Option<String>,
+    chunks: BTreeMap<u64, String>,
+    last: Option<(u64, String)>,
+}
+
+impl Reassembly {
+    pub fn new() -> Reassembly {
+        Reassembly::default()
+    }
+
+    /// Add a chunk; returns whether every chunk of the stream has been received
+    pub fn push(&mut self, chunk: Chunk) -> Result<bool> {
+        match &self.stream {
+            Some(stream) if *stream != chunk.stream => {
+                return Err(Error::invalid_input(
+                    Subsystem::Notifier,
+                    format!(
+                        "Chunk of stream {} does not belong to stream {stream}",
+                        chunk.stream
+                    ),
+                ));
+            }
+            Some(_) => (),
+            None => self.stream = Some(chunk.stream),
+        }
+        if self.chunks.contains_key(&chunk.seq) {
+            return Err(Error::new(
+                Subsystem::Notifier,
+                ErrorKind::AlreadyExists,
+                format!("Chunk {} was already received", chunk.seq),
+            ));
+        }
+        if chunk.last {
+            let Some(digest) = chunk.digest else {
+                return Err(Error::invalid_input(
+                    Subsystem::Notifier,
+                    "Last chunk has no digest",
+                ));
+            };
+            self.last = Some((chunk.seq, digest));
+        }
+        self.chunks.insert(chunk.seq, chunk.data);
+        Ok(self.is_complete())
+    }
+
+    /// Parse a notification sent by [`Stream`] and add it
+    pub fn push_message(&mut self, message: &str) -> Result<bool> {
+        let chunk = serde_json::from_str(message).or_error(
+            Subsystem::Notifier,
+            ErrorKind::Serialization,
+            "Notification is not a stream chunk",
+        )?;
+        self.push(chunk)
+    }
+
+    /// Whether the chunks received are exactly those numbered from 0 to the last one
+    pub fn is_complete(&self) -> bool {
+        self.last.as_ref().is_some_and(|(seq, _)| {
+            self.chunks.len() as u64 == seq + 1
+                && self
+                    .chunks
+                    .last_key_value()
+                    .is_some_and(|(max, _)| max == seq)
+        })
+    }
+
+    /// Payload of a complete stream, once its digest is checked
+    pub fn finish(self) -> Result<String> {
+        if !self.is_complete() {
+            return Err(Error::invalid_input(
+                Subsystem::Notifier,
+                "Stream is incomplete",
+            ));
+        }
+        let payload = self.chunks.into_values().collect::<String>();
+        let digest = self.last.map(|(_, digest)| digest).unwrap_or_default();
+        if to_hex(&Sha256::digest(payload.as_bytes())) != digest.to_lowercase() {
+            return Err(Error::invalid_input(
+                Subsystem::Notifier,
+                "Stream digest does not match its payload",
+            ));
+        }
+        Ok(payload)
+    }
+
+    /// Value sent with [`Stream::send_json`]
+    pub fn finish_json<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_str(&self.finish()?).or_error(
+            Subsystem::Notifier,
+            ErrorKind::Serialization,
+            "Failed to deserialize stream payload",
+        )
+    }
+
+    /// Items sent with [`Stream::send_items`]
+    pub fn finish_items<T: DeserializeOwned>(self) -> Result<Vec<T>> {
+        self.finish()?
+            .lines()
+            .map(|line| {
+                serde_json::from_str(line).or_error(
+                    Subsystem::Notifier,
+                    ErrorKind::Serialization,
+                    "Failed to deserialize stream item",
+                )
+            })
+            .collect()
+    }
+}
+
+/// Length of `c` in a JSON string, as escaped by `serde_json`
+fn escaped_len(c: char) -> usize {
+    match c {
+        '"' | '\\' | '\u{8}' | '\u{c}' | '\n' | '\r' | '\t' => 2,
+        '\0'..='\u{1f}' => 6,
+        c => c.len_utf8(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost, Notification};
+
+    fn messages(host: &MockHost) -> Vec<String> {
+        host.take_notifications()
+            .into_iter()
+            .map(|notification| match notification {
+                Notification::Message(message) => message,
+                other => panic!("unexpected notification {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_bound_escaped_payloads() {
+        let host = install(MockHost::new());
+        let payload = r#"\"quoted\" \\path\\ "#.repeat(40) + "\n\té";
+        Stream::new("query-1")
+            .with_chunk_size(200)
+            .send_str(&payload)
+            .unwrap();
+
+        let messages = messages(&host);
+        assert!(messages.len() > 1);
+        let mut reassembly = Reassembly::new();
+        for message in &messages {
+            assert!(message.len() <= 200, "{} bytes: {message}", message.len());
+            reassembly.push_message(message).unwrap();
+        }
+        assert_eq!(reassembly.finish().unwrap(), payload);
+    }
+
+    #[test]
+    fn chunks_bound_escaped_items() {
+        let host = install(MockHost::new());
+        let items = (0..30)
+            .map(|i| format!(r#"say "{i}" \ "{i}""#))
+            .collect::<Vec<_>>();
+        Stream::new("query-2")
+            .with_chunk_size(256)
+            .send_items(&items)
+            .unwrap();
+
+        let messages = messages(&host);
+        assert!(messages.len() > 1);
+        let mut reassembly = Reassembly::new();
+        for message in messages.iter().rev() {
+            assert!(message.len() <= 256, "{} bytes: {message}", message.len());
+            reassembly.push_message(message).unwrap();
+        }
+        assert_eq!(reassembly.finish_items::<String>().unwrap(), items);
+    }
+
+    #[test]
+    fn chunks_past_the_last_do_not_complete_the_stream() {
+        let chunk = |seq, last| Chunk {
+            stream: "s".to_string(),
+            seq,
+            data: format!("{seq}"),
+            last,
+            digest: last.then(|| to_hex(&Sha256::digest(b"012"))),
+        };
+        let mut reassembly = Reassembly::new();
+        assert!(!reassembly.push(chunk(0, false)).unwrap());
+        assert!(!reassembly.push(chunk(5, false)).unwrap());
+        assert!(!reassembly.push(chunk(2, true)).unwrap());
+        assert!(reassembly.finish().is_err());
+
+        let mut reassembly = Reassembly::new();
+        for seq in [2, 0, 1] {
+            reassembly.push(chunk(seq, seq == 2)).unwrap();
+        }
+        assert!(reassembly.is_complete());
+        assert_eq!(reassembly.finish().unwrap(), "012");
+    }
+}
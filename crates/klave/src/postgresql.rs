@@ -1,3 +1,4 @@
+use crate::error::{Error, Result, Subsystem};
 use crate::sdk;
 
 pub struct Connection {
@@ -9,28 +10,34 @@ impl Connection {
         format!("host={host} dbname={dbname} user={user} password={password}")
     }
 
-    pub fn open(uri: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let handle = sdk::connection_open(uri)?;
+    pub fn open(uri: &str) -> Result<Self> {
+        let handle = sdk::connection_open(uri)
+            .map_err(|err| Error::host(Subsystem::Sql, "Failed to open connection", err))?;
         Ok(Connection { handle })
     }
 
-    fn normalise_sql(input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    fn normalise_sql(input: &str) -> Result<String> {
         let normalized = input.split_whitespace().collect::<Vec<_>>().join(" ");
 
         if normalized.is_empty() {
-            return Err("SQL input cannot be empty".into());
+            return Err(Error::invalid_input(
+                Subsystem::Sql,
+                "SQL input cannot be empty",
+            ));
         }
 
         Ok(normalized)
     }
 
-    pub fn query(&self, query: &str) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn query(&self, query: &str) -> Result<String> {
         let query = Self::normalise_sql(query)?;
-        sdk::sql_query(&self.handle, &query).map_err(Into::into)
+        sdk::sql_query(&self.handle, &query)
+            .map_err(|err| Error::host(Subsystem::Sql, "Failed to run query", err))
     }
 
-    pub fn execute(&self, command: &str) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn execute(&self, command: &str) -> Result<String> {
         let command = Self::normalise_sql(command)?;
-        sdk::sql_exec(&self.handle, &command).map_err(Into::into)
+        sdk::sql_exec(&self.handle, &command)
+            .map_err(|err| Error::host(Subsystem::Sql, "Failed to execute command", err))
     }
 }
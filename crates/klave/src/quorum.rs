@@ -0,0 +1,354 @@
+use crate::auth::length_prefixed;
+use crate::context::{self, Timestamp};
+use crate::crypto::ecc;
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::ledger;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+const QUORUM_TABLE: &str = "__klave_quorum";
+
+const SIGNING_DOMAIN: &str = "klave-quorum-v1";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Approver {
+    /// Identity of the approver, as reported by the `sender` context value
+    pub id: String,
+    /// Curve of the public key, `P-256` when not given
+    #[serde(default = "default_curve")]
+    pub named_curve: String,
+    /// SPKI-encoded public key used to check the approver's signature
+    pub public_key: Vec<u8>,
+}
+
+fn default_curve() -> String {
+    "P-256".to_string()
+}
+
+impl Approver {
+    /// Approver signing with a P-256 key
+    pub fn new(id: &str, public_key: &[u8]) -> Approver {
+        Approver {
+            id: id.to_string(),
+            named_curve: default_curve(),
+            public_key: public_key.to_vec(),
+        }
+    }
+
+    pub fn with_curve(mut self, named_curve: &str) -> Approver {
+        self.named_curve = named_curve.to_string();
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Approval {
+    pub approver: String,
+    pub signature: Vec<u8>,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Pending,
+    Approved,
+    Executed,
+    Expired,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Proposal {
+    pub id: String,
+    pub proposer: String,
+    pub action: String,
+    pub approvers: Vec<Approver>,
+    pub threshold: u32,
+    pub deadline: Timestamp,
+    pub approvals: Vec<Approval>,
+    pub executed: bool,
+}
+
+impl Proposal {
+    /// Bytes an approver must sign to approve this proposal: the domain tag, id, threshold,
+    /// deadline and action, each prefixed by its length as a big-endian u32
+    pub fn signing_payload(&self) -> Vec<u8> {
+        length_prefixed(&[
+            SIGNING_DOMAIN.as_bytes(),
+            self.id.as_bytes(),
+            &self.threshold.to_be_bytes(),
+            &self.deadline.as_nanos().to_be_bytes(),
+            self.action.as_bytes(),
+        ])
+    }
+
+    /// Status of the proposal at the given trusted time
+    pub fn status(&self, now: Timestamp) -> ProposalStatus {
+        if self.executed {
+            ProposalStatus::Executed
+        } else if now > self.deadline {
+            ProposalStatus::Expired
+        } else if self.approvals.len() as u32 >= self.threshold {
+            ProposalStatus::Approved
+        } else {
+            ProposalStatus::Pending
+        }
+    }
+}
+
+fn save(proposal: &Proposal) -> Result<()> {
+    ledger::get_table(QUORUM_TABLE).set_json(&proposal.id, proposal)
+}
+
+/// Create a proposal holding a serialized action that runs once `threshold`
+/// distinct approvers have signed it before `deadline`
+pub fn create<T: Serialize>(
+    id: &str,
+    action: &T,
+    approvers: Vec<Approver>,
+    threshold: u32,
+    deadline: Timestamp,
+) -> Result<Proposal> {
+    if id.is_empty() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid proposal id: cannot be empty",
+        ));
+    }
+    if threshold == 0 || threshold as usize > approvers.len() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid threshold {threshold}: must be between 1 and {}",
+                approvers.len()
+            ),
+        ));
+    }
+    for (i, approver) in approvers.iter().enumerate() {
+        if approvers[..i].iter().any(|a| a.id == approver.id) {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::InvalidInput,
+                format!("Invalid approvers: {} is listed twice", approver.id),
+            ));
+        }
+    }
+    let context = context::current();
+    if deadline <= context.trusted_time()? {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid deadline: must be in the future",
+        ));
+    }
+    if ledger::get_table(QUORUM_TABLE).exists(id)? {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::AlreadyExists,
+            format!("Invalid proposal id: {id} already exists"),
+        ));
+    }
+
+    let proposal = Proposal {
+        id: id.to_string(),
+        proposer: context.sender()?,
+        action: serde_json::to_string(action).or_error(
+            Subsystem::Access,
+            ErrorKind::Serialization,
+            "Failed to serialize value",
+        )?,
+        approvers,
+        threshold,
+        deadline,
+        approvals: vec![],
+        executed: false,
+    };
+    save(&proposal)?;
+    Ok(proposal)
+}
+
+/// Retrieve a proposal
+pub fn get(id: &str) -> Result<Proposal> {
+    ledger::get_table(QUORUM_TABLE).get_json(id)
+}
+
+/// Record the caller's approval of a proposal.
+/// The signature must be an ECDSA (SHA-256) signature of [`Proposal::signing_payload`]
+/// with the key of the approver.
+pub fn approve(id: &str, signature: &[u8]) -> Result<ProposalStatus> {
+    let context = context::current();
+    let mut proposal = get(id)?;
+    let now = context.trusted_time()?;
+    match proposal.status(now) {
+        ProposalStatus::Pending => (),
+        ProposalStatus::Approved => return Ok(ProposalStatus::Approved),
+        ProposalStatus::Executed => {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::AlreadyExists,
+                format!("Proposal {id} already executed"),
+            ))
+        }
+        ProposalStatus::Expired => {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::Expired,
+                format!("Proposal {id} has expired"),
+            ))
+        }
+    }
+
+    let caller = context.sender()?;
+    let approver = proposal
+        .approvers
+        .iter()
+        .find(|a| a.id == caller)
+        .ok_or_else(|| {
+            Error::new(
+                Subsystem::Access,
+                ErrorKind::PermissionDenied,
+                format!("Access denied: {caller} is not an approver of {id}"),
+            )
+        })?;
+    if proposal.approvals.iter().any(|a| a.approver == caller) {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::AlreadyExists,
+            format!("{caller} already approved {id}"),
+        ));
+    }
+
+    let result = ecc::verify_with_public_key(
+        &approver.named_curve,
+        &approver.public_key,
+        &proposal.signing_payload(),
+        signature,
+    )?;
+    if !result.is_valid() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::PermissionDenied,
+            format!("Invalid signature from {caller} for {id}"),
+        ));
+    }
+
+    proposal.approvals.push(Approval {
+        approver: caller,
+        signature: signature.to_vec(),
+        time: now,
+    });
+    save(&proposal)?;
+    Ok(proposal.status(now))
+}
+
+/// Run the action of an approved proposal and mark it as executed.
+/// Fails if the quorum has not been reached, the deadline has passed or the proposal already ran.
+pub fn execute<T, R, F>(id: &str, action: F) -> Result<R>
+where
+    T: DeserializeOwned,
+    F: FnOnce(T) -> Result<R>,
+{
+    let mut proposal = get(id)?;
+    match proposal.status(context::current().trusted_time()?) {
+        ProposalStatus::Approved => (),
+        ProposalStatus::Pending => {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::PermissionDenied,
+                format!(
+                    "Proposal {id} has {} of {} approvals",
+                    proposal.approvals.len(),
+                    proposal.threshold
+                ),
+            ))
+        }
+        ProposalStatus::Executed => {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::AlreadyExists,
+                format!("Proposal {id} already executed"),
+            ))
+        }
+        ProposalStatus::Expired => {
+            return Err(Error::new(
+                Subsystem::Access,
+                ErrorKind::Expired,
+                format!("Proposal {id} has expired"),
+            ))
+        }
+    }
+
+    let result = action(serde_json::from_str(&proposal.action).or_error(
+        Subsystem::Access,
+        ErrorKind::Serialization,
+        "Failed to deserialize value",
+    )?)?;
+    proposal.executed = true;
+    save(&proposal)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::subtle::{
+        self, EcKeyGenParams, EcdsaParams, KeyGenAlgorithm, SignAlgorithm,
+    };
+    use crate::host::mock::{install, MockHost};
+
+    fn proposal(id: &str, action: &str) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            proposer: "alice".to_string(),
+            action: action.to_string(),
+            approvers: vec![],
+            threshold: 1,
+            deadline: Timestamp::from_nanos(5),
+            approvals: vec![],
+            executed: false,
+        }
+    }
+
+    #[test]
+    fn signing_payloads_of_colon_separated_fields_differ() {
+        assert_ne!(
+            proposal("p:1:0.000000005:q", "r").signing_payload(),
+            proposal("p", "q:1:0.000000005:r").signing_payload()
+        );
+    }
+
+    #[test]
+    fn approval_with_a_p384_key() {
+        let host = install(MockHost::new());
+        host.set_context(context::TRUSTED_TIME, "1000");
+        host.set_context(context::SENDER, "bob");
+        let key = subtle::generate_key(
+            &KeyGenAlgorithm::Ecc(EcKeyGenParams {
+                named_curve: "P-384".to_string(),
+            }),
+            true,
+            &["sign", "verify"],
+        )
+        .unwrap();
+        let spki = subtle::export_key("spki", &subtle::get_public_key(&key).unwrap()).unwrap();
+        let approvers = vec![Approver::new("bob", &spki).with_curve("P-384")];
+        let proposal = create("p1", &"action", approvers, 1, Timestamp::from_nanos(2000)).unwrap();
+        let signature = subtle::sign(
+            &SignAlgorithm::Ecdsa(EcdsaParams::default()),
+            &key,
+            &proposal.signing_payload(),
+        )
+        .unwrap();
+
+        let mut forged = signature.clone();
+        forged[10] ^= 1;
+        assert_eq!(
+            approve("p1", &forged).unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(approve("p1", &signature).unwrap(), ProposalStatus::Approved);
+        let action = execute("p1", |action: String| Ok(action)).unwrap();
+        assert_eq!(action, "action");
+    }
+}
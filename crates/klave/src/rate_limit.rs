@@ -0,0 +1,212 @@
+use crate::context;
+use crate::error::{Error, ErrorKind, Result, Subsystem};
+use crate::ledger;
+use crate::util::compound_key;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::time::Duration;
+
+const RATE_LIMIT_TABLE: &str = "__klave_rate_limit";
+
+/// Token bucket configuration of a route: up to `capacity` calls in a burst,
+/// refilled by one token every `refill_interval`
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub capacity: u32,
+    pub refill_interval: Duration,
+}
+
+impl Limit {
+    pub fn new(capacity: u32, refill_interval: Duration) -> Limit {
+        Limit {
+            capacity,
+            refill_interval,
+        }
+    }
+
+    pub fn per_second(calls: u32) -> Limit {
+        Limit::new(calls, Duration::from_secs(1) / calls.max(1))
+    }
+
+    pub fn per_minute(calls: u32) -> Limit {
+        Limit::new(calls, Duration::from_secs(60) / calls.max(1))
+    }
+
+    pub fn per_hour(calls: u32) -> Limit {
+        Limit::new(calls, Duration::from_secs(60 * 60) / calls.max(1))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Bucket {
+    tokens: u32,
+    last_refill: u64,
+}
+
+/// Error returned when a caller has exhausted its bucket for a route
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateLimited {
+    pub route: String,
+    pub caller: String,
+    /// Time after which the next call will be accepted, in milliseconds
+    pub retry_after_ms: u64,
+}
+
+impl RateLimited {
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_millis(self.retry_after_ms)
+    }
+}
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Rate limited: {} exceeded the limit of {}, retry after {} ms",
+            self.caller, self.route, self.retry_after_ms
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Retrieve the rate limiting details of an error returned by [`check`]
+pub fn rate_limited(error: &Error) -> Option<&RateLimited> {
+    std::error::Error::source(error).and_then(|source| source.downcast_ref::<RateLimited>())
+}
+
+fn require_transaction() -> Result<()> {
+    context::current().require_transaction(Subsystem::Access, "Rate limiting")
+}
+
+fn bucket_key(route: &str, caller: &str) -> String {
+    compound_key(&[route, caller])
+}
+
+/// Consume one token of the caller's bucket for `route`.
+///
+/// Fails with [`ErrorKind::RateLimited`] when the bucket is empty; the error's source is a
+/// [`RateLimited`] carrying a retry-after hint, see [`rate_limited`].
+///
+/// Bucket state lives in the ledger, whose writes are dropped at the end of a query, so
+/// queries cannot be rate limited: outside a transaction, it fails with
+/// [`ErrorKind::Unsupported`] rather than letting every call through. Expensive work, such
+/// as ML inference or HTTPS outcalls, must run in a transaction to be protected.
+pub fn check(route: &str, limit: &Limit) -> Result<()> {
+    if limit.capacity == 0 || limit.refill_interval.is_zero() {
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::InvalidInput,
+            "Invalid rate limit: capacity and refill interval must be positive",
+        ));
+    }
+    require_transaction()?;
+    let context = context::current();
+    let caller = context.sender()?;
+    let now = context.trusted_time()?.as_nanos();
+    let interval = u64::try_from(limit.refill_interval.as_nanos()).unwrap_or(u64::MAX);
+
+    let table = ledger::get_table(RATE_LIMIT_TABLE);
+    let key = bucket_key(route, &caller);
+    let mut bucket = if table.exists(&key)? {
+        table.get_json::<Bucket>(&key)?
+    } else {
+        Bucket {
+            tokens: limit.capacity,
+            last_refill: now,
+        }
+    };
+
+    let refills = now.saturating_sub(bucket.last_refill) / interval;
+    if refills > 0 {
+        let tokens = u64::from(bucket.tokens).saturating_add(refills);
+        bucket.tokens = tokens.min(u64::from(limit.capacity)) as u32;
+        bucket.last_refill = if bucket.tokens == limit.capacity {
+            now
+        } else {
+            bucket.last_refill + refills * interval
+        };
+    }
+    bucket.tokens = bucket.tokens.min(limit.capacity);
+
+    if bucket.tokens == 0 {
+        let retry_after = (bucket.last_refill + interval).saturating_sub(now);
+        let rate_limited = RateLimited {
+            route: route.to_string(),
+            caller,
+            retry_after_ms: retry_after.div_ceil(1_000_000),
+        };
+        return Err(Error::new(
+            Subsystem::Access,
+            ErrorKind::RateLimited,
+            format!("Too many calls to {route}"),
+        )
+        .with_source(rate_limited));
+    }
+
+    bucket.tokens -= 1;
+    table.set_json(&key, &bucket)
+}
+
+/// Reset the bucket of a caller for `route`; like [`check`], it must be called from a transaction
+pub fn reset(route: &str, caller: &str) -> Result<()> {
+    require_transaction()?;
+    let table = ledger::get_table(RATE_LIMIT_TABLE);
+    let key = bucket_key(route, caller);
+    if table.exists(&key)? {
+        table.remove(&key)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+
+    #[test]
+    fn queries_are_refused() {
+        let host = install(MockHost::new());
+        host.set_context(context::SENDER, "alice");
+        host.set_context(context::TRUSTED_TIME, "1000");
+        host.set_context(context::QUERY_ID, "q1");
+        let err = check("route", &Limit::per_minute(1)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(
+            reset("route", "alice").unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn transactions_consume_the_bucket() {
+        let host = install(MockHost::new());
+        host.set_context(context::SENDER, "alice");
+        host.set_context(context::TRUSTED_TIME, "1000");
+        host.set_context(context::TRANSACTION_ID, "t1");
+        let limit = Limit::per_minute(2);
+        check("route", &limit).unwrap();
+        check("route", &limit).unwrap();
+        let err = check("route", &limit).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert_eq!(rate_limited(&err).unwrap().retry_after_ms, 30_000);
+        reset("route", "alice").unwrap();
+        check("route", &limit).unwrap();
+    }
+
+    #[test]
+    fn routes_and_callers_with_colons_have_their_own_buckets() {
+        let host = install(MockHost::new());
+        host.set_context(context::TRUSTED_TIME, "1000");
+        host.set_context(context::TRANSACTION_ID, "t1");
+        let limit = Limit::per_minute(1);
+        host.set_context(context::SENDER, "b:c");
+        check("a", &limit).unwrap();
+        host.set_context(context::SENDER, "c");
+        check("a:b", &limit).unwrap();
+        assert_eq!(
+            check("a:b", &limit).unwrap_err().kind(),
+            ErrorKind::RateLimited
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use crate::acl;
+use crate::crypto::aes::{self, KeyAES};
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::ledger;
+use crate::util::percent_encode;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+const SECRETS_TABLE: &str = "__klave_secrets";
+/// Name of the enclave AES key the secrets are encrypted with
+const SECRETS_KEY: &str = "__klave_secrets";
+const REDACTED: &str = "[REDACTED]";
+
+thread_local! {
+    /// Encoded forms of the secrets read so far, removed from error messages.
+    ///
+    /// Apps have no hook at the end of a call, so they are kept for the lifetime of the instance.
+    static LOADED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSecret {
+    version: u64,
+    updated_by: String,
+    /// Base64 of the encrypted [`SealedSecret`]
+    ciphertext: String,
+}
+
+/// Plaintext of a secret; the name is checked on decryption so that records cannot be swapped
+#[derive(Serialize, Deserialize)]
+struct SealedSecret {
+    name: String,
+    value: String,
+}
+
+/// Metadata of a stored secret, without its value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretInfo {
+    pub name: String,
+    /// Incremented by each rotation, from 1
+    pub version: u64,
+    pub updated_by: String,
+}
+
+/// Decrypted secret. `Debug` and `Display` never show its value.
+#[derive(Clone)]
+pub struct Secret {
+    name: String,
+    version: u64,
+    value: String,
+}
+
+impl Secret {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Plaintext value; keep it out of logs, notifications and the ledger
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Secret")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("value", &REDACTED)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Secret: name: {}, version: {}", self.name, self.version)
+    }
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            "Invalid secret name: cannot be empty",
+        ));
+    }
+    Ok(())
+}
+
+fn encryption_key() -> Result<KeyAES> {
+    match aes::generate_key(SECRETS_KEY) {
+        Ok(key) => Ok(key),
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => aes::get_key(SECRETS_KEY),
+        Err(err) => Err(err),
+    }
+}
+
+fn read(name: &str) -> Result<StoredSecret> {
+    let table = ledger::get_table(SECRETS_TABLE);
+    if !table.exists(name)? {
+        return Err(Error::not_found(
+            Subsystem::Crypto,
+            format!("Secret {name} does not exist"),
+        ));
+    }
+    table.get_json(name)
+}
+
+fn write(name: &str, value: &str, version: u64) -> Result<u64> {
+    let caller = acl::caller()?;
+    let sealed = serde_json::to_vec(&SealedSecret {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+    .or_error(
+        Subsystem::Crypto,
+        ErrorKind::Serialization,
+        "Failed to serialize secret",
+    )?;
+    let ciphertext = encryption_key()?.encrypt(&sealed)?;
+    ledger::get_table(SECRETS_TABLE).set_json(
+        name,
+        &StoredSecret {
+            version,
+            updated_by: caller,
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        },
+    )?;
+    Ok(version)
+}
+
+/// Store a new secret. The caller must be the owner or an admin; it must be called from a transaction.
+pub fn create(name: &str, value: &str) -> Result<u64> {
+    check_name(name)?;
+    acl::require_role(acl::ADMIN_ROLE)?;
+    if ledger::get_table(SECRETS_TABLE).exists(name)? {
+        return Err(Error::new(
+            Subsystem::Crypto,
+            ErrorKind::AlreadyExists,
+            format!("Secret {name} already exists"),
+        ));
+    }
+    write(name, value, 1)
+}
+
+/// Replace the value of a secret and return its new version. The caller must be the owner or an admin.
+pub fn rotate(name: &str, value: &str) -> Result<u64> {
+    acl::require_role(acl::ADMIN_ROLE)?;
+    let version = read(name)?.version + 1;
+    write(name, value, version)
+}
+
+/// Delete a secret. The caller must be the owner or an admin.
+pub fn remove(name: &str) -> Result<()> {
+    acl::require_role(acl::ADMIN_ROLE)?;
+    read(name)?;
+    ledger::get_table(SECRETS_TABLE).remove(name)
+}
+
+/// Name, version and author of the last change of a secret
+pub fn info(name: &str) -> Result<SecretInfo> {
+    let stored = read(name)?;
+    Ok(SecretInfo {
+        name: name.to_string(),
+        version: stored.version,
+        updated_by: stored.updated_by,
+    })
+}
+
+/// Names of the stored secrets, sorted
+pub fn names() -> Result<Vec<String>> {
+    let mut names = ledger::get_table(SECRETS_TABLE).list_keys()?;
+    names.sort();
+    Ok(names)
+}
+
+/// Decrypt a secret for the current call.
+///
+/// Its value is redacted from the error messages of [`crate::https::request`] and from [`redact`] from then on.
+pub fn get(name: &str) -> Result<Secret> {
+    let stored = read(name)?;
+    let ciphertext = general_purpose::STANDARD
+        .decode(&stored.ciphertext)
+        .or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Secret ciphertext is not valid base64",
+        )?;
+    let sealed: SealedSecret =
+        serde_json::from_slice(&aes::get_key(SECRETS_KEY)?.decrypt(&ciphertext)?).or_error(
+            Subsystem::Crypto,
+            ErrorKind::Serialization,
+            "Failed to deserialize secret",
+        )?;
+    if sealed.name != name {
+        return Err(Error::invalid_input(
+            Subsystem::Crypto,
+            format!("Secret {name} holds the value of another secret"),
+        ));
+    }
+    remember(&sealed.value);
+    Ok(Secret {
+        name: name.to_string(),
+        version: stored.version,
+        value: sealed.value,
+    })
+}
+
+fn remember(value: &str) {
+    if value.is_empty() {
+        return;
+    }
+    // As escaped inside a JSON string, without the enclosing quotes only
+    let json = serde_json::to_string(value).unwrap_or_default();
+    let escaped = json
+        .strip_prefix('"')
+        .and_then(|json| json.strip_suffix('"'))
+        .unwrap_or(value);
+    let forms = [
+        value.to_string(),
+        percent_encode(value, b""),
+        escaped.to_string(),
+    ];
+    LOADED.with(|loaded| {
+        let mut loaded = loaded.borrow_mut();
+        for form in forms {
+            if !loaded.contains(&form) {
+                loaded.push(form);
+            }
+        }
+    });
+}
+
+/// `text` with the values of the secrets read so far replaced by `[REDACTED]`
+pub fn redact(text: &str) -> String {
+    LOADED.with(|loaded| {
+        let mut loaded = loaded.borrow().clone();
+        // Longest first, so that a secret containing another is replaced whole
+        loaded.sort_by_key(|form| std::cmp::Reverse(form.len()));
+        loaded
+            .iter()
+            .fold(text.to_string(), |text, form| text.replace(form, REDACTED))
+    })
+}
+
+/// Where a secret goes in an HTTPS request
+#[cfg(feature = "https")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Injection {
+    /// Header with the secret as value, e.g. `x-api-key`
+    Header(String),
+    /// `Authorization: Bearer <secret>`
+    Bearer,
+    /// Query parameter with the secret as value, e.g. `api_key`
+    Query(String),
+}
+
+/// Add the secret `name` to `request`, to be sent with [`crate::https::request`].
+///
+/// Headers carrying the secret are marked sensitive, and replace any previous value.
+#[cfg(feature = "https")]
+pub fn inject<B>(request: &mut http::Request<B>, name: &str, injection: &Injection) -> Result<()> {
+    use http::header::{HeaderName, HeaderValue, AUTHORIZATION};
+
+    let secret = get(name)?;
+    let header_value = |value: &str| {
+        let mut value = HeaderValue::from_str(value).map_err(|_| {
+            Error::invalid_input(
+                Subsystem::Https,
+                format!("Secret {name} is not a valid header value"),
+            )
+        })?;
+        value.set_sensitive(true);
+        Ok::<_, Error>(value)
+    };
+    match injection {
+        Injection::Header(header) => {
+            let header = header.parse::<HeaderName>().or_error(
+                Subsystem::Https,
+                ErrorKind::InvalidInput,
+                &format!("Invalid header name '{header}'"),
+            )?;
+            let value = header_value(secret.expose())?;
+            request.headers_mut().insert(header, value);
+        }
+        Injection::Bearer => {
+            let value = header_value(&format!("Bearer {}", secret.expose()))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+        Injection::Query(parameter) => {
+            let mut parts = request.uri().clone().into_parts();
+            let path_and_query = parts
+                .path_and_query
+                .as_ref()
+                .map_or("/", |path_and_query| path_and_query.as_str());
+            let separator = if path_and_query.contains('?') {
+                '&'
+            } else {
+                '?'
+            };
+            let path_and_query = format!(
+                "{path_and_query}{separator}{}={}",
+                percent_encode(parameter, b""),
+                percent_encode(secret.expose(), b"")
+            );
+            parts.path_and_query = Some(path_and_query.parse().or_error(
+                Subsystem::Https,
+                ErrorKind::InvalidInput,
+                "Invalid request path",
+            )?);
+            *request.uri_mut() = http::Uri::from_parts(parts).or_error(
+                Subsystem::Https,
+                ErrorKind::InvalidInput,
+                "Invalid request URI",
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context;
+    use crate::host::mock::{install, MockHost};
+    use std::rc::Rc;
+
+    fn setup() -> Rc<MockHost> {
+        let host = install(MockHost::new());
+        host.set_context(context::TRANSACTION_ID, "t1");
+        host.set_context(context::SENDER, "alice");
+        acl::bootstrap("alice").unwrap();
+        host
+    }
+
+    #[test]
+    fn only_admins_change_secrets() {
+        let host = setup();
+        create("api", "v1").unwrap();
+
+        host.set_context(context::SENDER, "bob");
+        for err in [
+            create("other", "v1").unwrap_err(),
+            rotate("api", "v2").unwrap_err(),
+            remove("api").unwrap_err(),
+        ] {
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        }
+
+        host.set_context(context::SENDER, "alice");
+        acl::grant_role("bob", acl::ADMIN_ROLE).unwrap();
+        host.set_context(context::SENDER, "bob");
+        assert_eq!(rotate("api", "v2").unwrap(), 2);
+        assert_eq!(info("api").unwrap().updated_by, "bob");
+        assert_eq!(get("api").unwrap().expose(), "v2");
+        remove("api").unwrap();
+        assert_eq!(get("api").unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn short_ciphertexts_are_rejected() {
+        setup();
+        create("api", "v1").unwrap();
+        ledger::get_table(SECRETS_TABLE)
+            .set_json(
+                "api",
+                &StoredSecret {
+                    version: 1,
+                    updated_by: "mallory".to_string(),
+                    ciphertext: general_purpose::STANDARD.encode([0u8; 5]),
+                },
+            )
+            .unwrap();
+        assert_eq!(get("api").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn quotes_of_values_are_kept_in_their_json_form() {
+        setup();
+        create("quoted", "x\"").unwrap();
+        get("quoted").unwrap();
+        assert_eq!(redact(r#"{"token":"x\""}"#), r#"{"token":"[REDACTED]"}"#);
+        assert_eq!(redact(r"C:\x\y"), r"C:\x\y");
+    }
+
+    #[cfg(feature = "https")]
+    mod https {
+        use super::*;
+        use crate::https::{Client, RetryPolicy, StatusError};
+        use http::{Method, Request, Response};
+
+        fn request() -> Request<String> {
+            Request::get("https://api.example.com/items?page=1")
+                .body(String::new())
+                .unwrap()
+        }
+
+        #[test]
+        fn secrets_are_injected() {
+            setup();
+            create("key", "s3cr3t value").unwrap();
+
+            let mut request = request();
+            inject(&mut request, "key", &Injection::Header("x-api-key".into())).unwrap();
+            inject(&mut request, "key", &Injection::Bearer).unwrap();
+            inject(&mut request, "key", &Injection::Query("api key".into())).unwrap();
+            assert_eq!(request.headers()["x-api-key"], "s3cr3t value");
+            assert!(request.headers()["x-api-key"].is_sensitive());
+            assert_eq!(request.headers()["authorization"], "Bearer s3cr3t value");
+            assert!(request.headers()["authorization"].is_sensitive());
+            assert_eq!(
+                request.uri(),
+                "https://api.example.com/items?page=1&api%20key=s3cr3t%20value"
+            );
+            assert_eq!(
+                inject(&mut request, "missing", &Injection::Bearer)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::NotFound
+            );
+        }
+
+        #[test]
+        fn host_errors_and_status_bodies_are_redacted() {
+            let host = setup();
+            create("key", "s3cr3t").unwrap();
+            host.set_https_responder(|request| {
+                if request.uri().path() == "/down" {
+                    return Err(format!("could not reach {}", request.uri()));
+                }
+                Ok(Response::builder()
+                    .status(401)
+                    .body(format!("{} is not allowed", request.uri()))
+                    .unwrap())
+            });
+
+            let mut down = Request::get("https://api.example.com/down")
+                .body(String::new())
+                .unwrap();
+            inject(&mut down, "key", &Injection::Query("key".into())).unwrap();
+            let err = crate::https::request(&down).unwrap_err();
+            assert_eq!(
+                err.host_error(),
+                Some("could not reach https://api.example.com:443/down?key=[REDACTED]")
+            );
+
+            let client = Client::builder()
+                .base_url("https://api.example.com")
+                .retry(RetryPolicy::none())
+                .build()
+                .unwrap();
+            let mut request = client
+                .request(Method::GET, "/items")
+                .unwrap()
+                .body(String::new())
+                .unwrap();
+            inject(&mut request, "key", &Injection::Query("key".into())).unwrap();
+            let err = client.send(&request).unwrap_err();
+            assert_eq!(
+                StatusError::of(&err).unwrap().body,
+                "https://api.example.com:443/items?key=[REDACTED] is not allowed"
+            );
+            assert!(!format!("{err} {err:?}").contains("s3cr3t"));
+        }
+    }
+}
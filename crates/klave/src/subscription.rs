@@ -1,9 +0,0 @@
-use crate::sdk;
-
-pub fn set_replay_start() {
-    sdk::start_recording();
-}
-
-pub fn set_replay_stop() {
-    sdk::start_recording();
-}
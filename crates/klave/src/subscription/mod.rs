@@ -0,0 +1,37 @@
+use crate::sdk;
+
+#[cfg(feature = "ledger")]
+pub mod topic;
+
+/// Ledger reads of the current query are recorded while this guard is alive.
+///
+/// The host replays the query to the client whenever a recorded entry changes;
+/// recording stops when the guard is dropped, even on early returns.
+#[must_use = "recording stops as soon as the guard is dropped"]
+pub struct Recording {
+    _private: (),
+}
+
+impl Recording {
+    pub fn start() -> Recording {
+        sdk::start_recording();
+        Recording { _private: () }
+    }
+
+    /// Stop recording before the end of the scope
+    pub fn stop(self) {}
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        sdk::stop_recording();
+    }
+}
+
+pub fn set_replay_start() {
+    sdk::start_recording();
+}
+
+pub fn set_replay_stop() {
+    sdk::stop_recording();
+}
@@ -0,0 +1,235 @@
+use super::Recording;
+use crate::error::{Error, ErrorKind, Result, ResultExt, Subsystem};
+use crate::ledger;
+use crate::notifier;
+use crate::util::read_or_default;
+use serde::{Deserialize, Serialize};
+
+/// Latest event of each key, at `{topic}:{key}`
+const TOPICS_TABLE: &str = "__klave_topics";
+/// Number of keys of each topic at `{topic}`, and the name of its n-th key at `{topic}:{n}`
+const TOPIC_KEYS_TABLE: &str = "__klave_topic_keys";
+
+/// Latest value published on a key of a topic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub topic: String,
+    pub key: String,
+    /// Position of the event among the events published on its key, from 1
+    pub seq: u64,
+    pub data: serde_json::Value,
+}
+
+/// Named channel that transaction handlers publish events to.
+///
+/// Only the latest event of each key is kept, in its own ledger entry, where subscribed queries read it.
+pub struct Topic {
+    name: String,
+}
+
+impl Topic {
+    /// Topic names cannot be empty nor contain `:`
+    pub fn new(name: &str) -> Result<Topic> {
+        if name.is_empty() || name.contains(':') {
+            return Err(Error::invalid_input(
+                Subsystem::Notifier,
+                format!("Invalid topic name '{name}': cannot be empty nor contain ':'"),
+            ));
+        }
+        Ok(Topic {
+            name: name.to_string(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Publish `data` on `key`, replacing its previous event, and return the sequence number of the event
+    pub fn publish<T: Serialize>(&self, key: &str, data: &T) -> Result<u64> {
+        let seq = match self.latest(key)? {
+            Some(previous) => previous.seq + 1,
+            None => {
+                self.add_key(key)?;
+                1
+            }
+        };
+        let event = Event {
+            topic: self.name.clone(),
+            key: key.to_string(),
+            seq,
+            data: serde_json::to_value(data).or_error(
+                Subsystem::Notifier,
+                ErrorKind::Serialization,
+                "Failed to serialize event",
+            )?,
+        };
+        ledger::get_table(TOPICS_TABLE).set_json(&event_key(&self.name, key), &event)?;
+        Ok(seq)
+    }
+
+    /// Latest event published on `key`
+    pub fn latest(&self, key: &str) -> Result<Option<Event>> {
+        read_or_default(
+            &ledger::get_table(TOPICS_TABLE),
+            &event_key(&self.name, key),
+        )
+    }
+
+    /// Keys that were published on, in order of first publication
+    pub fn keys(&self) -> Result<Vec<String>> {
+        let table = ledger::get_table(TOPIC_KEYS_TABLE);
+        let count: u64 = read_or_default(&table, &self.name)?;
+        (0..count)
+            .map(|n| table.get_json(&key_entry(&self.name, n)))
+            .collect()
+    }
+
+    fn add_key(&self, key: &str) -> Result<()> {
+        let table = ledger::get_table(TOPIC_KEYS_TABLE);
+        let count: u64 = read_or_default(&table, &self.name)?;
+        table.set_json(&key_entry(&self.name, count), &key)?;
+        table.set_json(&self.name, &(count + 1))
+    }
+}
+
+/// Topics and keys a subscriber receives events for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Filter {
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Keys starting with one of these are matched too
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+}
+
+impl Filter {
+    /// Whether `key` is selected; every key is when there are neither keys nor prefixes
+    pub fn matches_key(&self, key: &str) -> bool {
+        (self.keys.is_empty() && self.prefixes.is_empty())
+            || self.keys.iter().any(|k| k == key)
+            || self
+                .prefixes
+                .iter()
+                .any(|prefix| key.starts_with(prefix.as_str()))
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.topics.contains(&event.topic) && self.matches_key(&event.key)
+    }
+}
+
+/// Send the latest events matching `filter` to the client, and have the query replayed when they change.
+///
+/// Meant to be called by a query handler: events are sent as JSON through `notify`, in topic
+/// then key order, and replays send them again, so clients should skip `seq` numbers they have seen.
+/// Only the events of the selected keys are recorded, so publishing on other keys does not replay the query;
+/// subscribing to every key of a topic, or to prefixes, also replays it when a new key is published on.
+pub fn subscribe(filter: &Filter) -> Result<()> {
+    let topics = filter
+        .topics
+        .iter()
+        .map(|topic| Topic::new(topic))
+        .collect::<Result<Vec<_>>>()?;
+    let _recording = Recording::start();
+    for topic in &topics {
+        let keys = if filter.keys.is_empty() || !filter.prefixes.is_empty() {
+            topic
+                .keys()?
+                .into_iter()
+                .filter(|key| filter.matches_key(key))
+                .collect()
+        } else {
+            filter.keys.clone()
+        };
+        for key in keys {
+            if let Some(event) = topic.latest(&key)? {
+                notifier::send_json(&event)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn event_key(topic: &str, key: &str) -> String {
+    format!("{topic}:{key}")
+}
+
+fn key_entry(topic: &str, n: u64) -> String {
+    format!("{topic}:{n}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{install, MockHost};
+    use serde_json::json;
+
+    fn event_reads(host: &MockHost) -> Vec<String> {
+        host.recorded_reads()
+            .into_iter()
+            .filter(|(table, _)| table == TOPICS_TABLE)
+            .map(|(_, key)| String::from_utf8(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn events_are_stored_per_key() {
+        let host = install(MockHost::new());
+        let prices = Topic::new("prices").unwrap();
+        assert_eq!(prices.publish("eur", &1).unwrap(), 1);
+        assert_eq!(prices.publish("usd", &2).unwrap(), 1);
+        let keys_before = host.table(TOPIC_KEYS_TABLE);
+        assert_eq!(prices.publish("eur", &3).unwrap(), 2);
+
+        // Publishing on a known key only rewrites the entry of that key
+        assert_eq!(host.table(TOPIC_KEYS_TABLE), keys_before);
+        assert_eq!(prices.keys().unwrap(), ["eur", "usd"]);
+        assert_eq!(prices.latest("eur").unwrap().unwrap().data, json!(3));
+        assert_eq!(prices.latest("gbp").unwrap(), None);
+        assert!(Topic::new("a:b").is_err());
+    }
+
+    #[test]
+    fn subscriptions_record_matching_keys_only() {
+        let host = install(MockHost::new());
+        let prices = Topic::new("prices").unwrap();
+        for key in ["eur", "usd", "usd-old"] {
+            prices.publish(key, &key).unwrap();
+        }
+        Topic::new("rates").unwrap().publish("eur", &0).unwrap();
+
+        subscribe(&Filter {
+            topics: vec!["prices".to_string()],
+            keys: vec!["eur".to_string()],
+            prefixes: vec![],
+        })
+        .unwrap();
+        assert!(!host.is_recording());
+        assert_eq!(event_reads(&host), ["prices:eur"]);
+
+        let host = install(MockHost::new());
+        for key in ["eur", "usd", "usd-old"] {
+            prices.publish(key, &key).unwrap();
+        }
+        subscribe(&Filter {
+            topics: vec!["prices".to_string()],
+            keys: vec![],
+            prefixes: vec!["usd".to_string()],
+        })
+        .unwrap();
+        assert_eq!(event_reads(&host), ["prices:usd", "prices:usd-old"]);
+        let sent = host
+            .take_notifications()
+            .into_iter()
+            .map(|notification| match notification {
+                crate::host::mock::Notification::Message(message) => {
+                    serde_json::from_str::<Event>(&message).unwrap().key
+                }
+                other => panic!("unexpected notification {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sent, ["usd", "usd-old"]);
+    }
+}
@@ -0,0 +1,59 @@
+/// Lowercase hex of `bytes`
+#[cfg(any(feature = "ledger", feature = "stream", feature = "crypto"))]
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut acc, b| {
+        use std::fmt::Write;
+        write!(acc, "{b:02x}").unwrap();
+        acc
+    })
+}
+
+/// Percent-encoding of every byte but unreserved characters and `keep`
+#[cfg(all(feature = "crypto", any(feature = "https", feature = "ledger")))]
+pub(crate) fn percent_encode(text: &str, keep: &[u8]) -> String {
+    text.bytes().fold(String::new(), |mut acc, b| {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') || keep.contains(&b)
+        {
+            acc.push(b as char);
+        } else {
+            use std::fmt::Write;
+            write!(acc, "%{b:02X}").unwrap();
+        }
+        acc
+    })
+}
+
+/// Ledger key made of `parts`, each prefixed with its length so that no two lists of parts
+/// give the same key, whatever characters they contain
+#[cfg(feature = "ledger")]
+pub(crate) fn compound_key(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| format!("{}:{part}", part.len()))
+        .collect()
+}
+
+/// JSON value stored at `key`, or the default value when there is none
+#[cfg(feature = "ledger")]
+pub(crate) fn read_or_default<T: Default + serde::de::DeserializeOwned>(
+    table: &crate::ledger::Table,
+    key: &str,
+) -> crate::Result<T> {
+    if table.exists(key)? {
+        table.get_json(key)
+    } else {
+        Ok(T::default())
+    }
+}
+
+#[cfg(all(test, feature = "ledger"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_keys_are_unambiguous() {
+        assert_eq!(compound_key(&["route", "alice"]), "5:route5:alice");
+        assert_ne!(compound_key(&["a:b", "c"]), compound_key(&["a", "b:c"]));
+        assert_ne!(compound_key(&["ab", ""]), compound_key(&["a", "b"]));
+    }
+}